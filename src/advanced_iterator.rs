@@ -1,37 +1,74 @@
 //! Advanced iterator operations.
 //!
 
+use std::cell::RefCell;
 use std::fmt::{ Display, Write };
+use std::io;
+use std::iter::{ Fuse, Peekable };
+use std::rc::Rc;
 
 pub trait AdvancedIterator: Iterator {
     /// Returns an iterators of iterators (chunks), where each subiterator
     /// iterates over a `size` number of elements from the original iterator.
     /// The last chunk may be shorter, if there are not enough elements in the
     /// original iterator.
+    ///
+    /// The chunks take their elements from the original iterator as they
+    /// are iterated, without allocating. A chunk is over once the next one
+    /// is taken: the elements it didn't get to are skipped.
+    ///
+    /// Panics if `size` is zero. Callers taking the size from their own
+    /// callers make it at least one.
     fn chunk(self, size: usize) -> Chunk<Self>
         where Self: Sized
     {
-        Chunk{ inner: self, size: size }
+        assert!(size > 0);
+
+        Chunk { shared: Rc::new(RefCell::new(ChunkState { inner: self.fuse(),
+                                                            size,
+                                                            index: 0,
+                                                            taken: size,
+                                                            first: None })) }
     }
 
-    /// Alternate elements from every iterator in the original iterator until
-    /// the first one which runs out of elements.
+    /// Alternate elements from every iterator in the original iterator in
+    /// rounds of an element from each, as long as all of them have one left
+    /// for a whole round. Empty if there are no iterators.
     fn interleave(self) -> Interleave<Self::Item>
         where Self: Sized, Self::Item: Iterator
     {
-        let inner = self.collect::<Vec<_>>();
-        assert!(inner.len() > 0);
+        Interleave { inner: self.map(Iterator::peekable).collect(), index: 0, done: false }
+    }
 
-        Interleave{ inner: inner, index: 0 }
+    /// Alternate elements from every iterator in the original iterator until
+    /// all of them run out of elements, taking `fill` in place of the
+    /// elements of those that ran out before.
+    fn interleave_longest(self, fill: <Self::Item as Iterator>::Item)
+        -> InterleaveLongest<Self::Item>
+        where Self: Sized, Self::Item: Iterator, <Self::Item as Iterator>::Item: Clone
+    {
+        InterleaveLongest { inner: self.map(Iterator::peekable).collect(), index: 0, fill }
     }
 
     /// Transpose an iterator of iterators. The outer iterator must be finite
-    /// and additionally implement ExactSizeIterator.
+    /// and additionally implement ExactSizeIterator. There are as many rows
+    /// as the shortest of the iterators has elements.
     fn transpose(self) -> Chunk<Interleave<Self::Item>>
         where Self: Sized + ExactSizeIterator, Self::Item: Iterator
     {
         let len = self.len();
-        self.interleave().chunk(len)
+        self.interleave().chunk(len.max(1))
+    }
+
+    /// Transpose an iterator of iterators like `transpose`, but with as many
+    /// rows as the longest of the iterators has elements, padded with `fill`.
+    fn transpose_longest(self, fill: <Self::Item as Iterator>::Item)
+        -> Chunk<InterleaveLongest<Self::Item>>
+        where Self: Sized + ExactSizeIterator, Self::Item: Iterator,
+              <Self::Item as Iterator>::Item: Clone
+    {
+        let len = self.len();
+        self.interleave_longest(fill).chunk(len.max(1))
     }
 
     /// Chains all the iterators in the original iterator into a single
@@ -46,19 +83,53 @@ pub trait AdvancedIterator: Iterator {
     /// `separator`.
     fn join(&mut self, separator: &str) -> String
         where Self::Item: Display
+    {
+        self.join_with(separator)
+    }
+
+    /// Join the elements in the iterator into a `String` separated by
+    /// `separator`, which can be anything displayable, like a `char`.
+    fn join_with<S>(&mut self, separator: S) -> String
+        where Self::Item: Display, S: Display
     {
         let mut result = String::new();
 
         if let Some(first) = self.next() {
             write!(&mut result, "{}", first).unwrap();
 
-            while let Some(item) = self.next() {
+            for item in self {
                 write!(&mut result, "{}{}", separator, item).unwrap();
             }
         }
 
         result
     }
+
+    /// Yields the elements in the iterator with `separator` between every
+    /// two of them, taking each element only when it is needed, so that
+    /// separated output can be written as it is produced. Named apart from
+    /// the unstable `Iterator::intersperse`, which would shadow it.
+    fn intersperse_lazy(self, separator: Self::Item) -> Intersperse<Self>
+        where Self: Sized, Self::Item: Clone
+    {
+        Intersperse { inner: self.peekable(), separator, separate: false }
+    }
+
+    /// Writes the elements in the iterator to `out` separated by
+    /// `separator`, like `join`, but each as soon as it is produced.
+    fn write_join<W, S>(&mut self, out: &mut W, separator: S) -> io::Result<()>
+        where Self::Item: Display, W: io::Write + ?Sized, S: Display
+    {
+        if let Some(first) = self.next() {
+            write!(out, "{}", first)?;
+
+            for item in self {
+                write!(out, "{}{}", separator, item)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<I> AdvancedIterator for I where I: Iterator {}
@@ -85,6 +156,19 @@ fn chunk() {
     assert!(chunks.next().is_none());
 }
 
+#[test]
+fn chunk_skips_the_rest_of_a_chunk_left_behind() {
+    let mut chunks = (0..7).chunk(3);
+
+    let mut first = chunks.next().unwrap();
+    assert_eq!(first.next(), Some(0));
+
+    assert_eq!(chunks.next().unwrap().collect::<Vec<_>>(), [3, 4, 5]);
+    assert_eq!(first.next(), None);
+    assert_eq!(chunks.next().unwrap().collect::<Vec<_>>(), [6]);
+    assert!(chunks.next().is_none());
+}
+
 #[test]
 fn interleave() {
     let input = vec![0..3, 4..7, 7..11];
@@ -98,6 +182,33 @@ fn interleave() {
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn interleave_stops_at_the_shortest_iterator() {
+    let mut ragged = vec![0..3, 4..6, 7..11].into_iter().interleave();
+    assert_eq!(ragged.by_ref().collect::<Vec<_>>(), [0, 4, 7, 1, 5, 8]);
+    assert_eq!(ragged.next(), None);
+
+    let rows = vec![0..3, 4..6, 7..11].into_iter()
+                                      .transpose()
+                                      .map(|row| row.collect())
+                                      .collect::<Vec<Vec<_>>>();
+    assert_eq!(rows, [[0, 4, 7], [1, 5, 8]]);
+
+    assert_eq!(Vec::<::std::ops::Range<i32>>::new().into_iter().interleave().next(), None);
+}
+
+#[test]
+fn interleave_longest_pads_shorter_iterators() {
+    let actual = vec![0..2, 4..5, 7..10].into_iter().interleave_longest(-1).collect::<Vec<_>>();
+    assert_eq!(actual, [0, 4, 7, 1, -1, 8, -1, -1, 9]);
+
+    let rows = vec![vec!["a", "b"].into_iter(), vec!["c"].into_iter()].into_iter()
+                                                                      .transpose_longest("")
+                                                                      .map(|row| row.collect())
+                                                                      .collect::<Vec<Vec<_>>>();
+    assert_eq!(rows, [["a", "c"], ["b", ""]]);
+}
+
 #[test]
 fn chain_all() {
     let input = vec![0..5, 5..10, 10..15];
@@ -114,42 +225,142 @@ fn join() {
     assert_eq!(input.iter().join(","), "foo,bar,baz");
 }
 
+#[test]
+fn join_with() {
+    assert_eq!((1..4).join_with(','), "1,2,3");
+    assert_eq!((1..4).join_with(0), "10203");
+    assert_eq!((1..1).join_with(','), "");
+}
+
+#[test]
+fn intersperse_lazy() {
+    let actual = ["foo", "bar", "baz"].iter().cloned().intersperse_lazy(", ");
+    assert_eq!(actual.collect::<String>(), "foo, bar, baz");
+
+    assert_eq!((0..1).intersperse_lazy(-1).collect::<Vec<_>>(), [0]);
+    assert_eq!((0..0).intersperse_lazy(-1).next(), None);
+}
+
+#[test]
+fn intersperse_lazy_takes_elements_as_needed() {
+    use std::cell::Cell;
+
+    let taken  = Cell::new(0);
+    let inner  = (0..3).inspect(|_| taken.set(taken.get() + 1));
+    let mut interspersed = inner.intersperse_lazy(-1);
+
+    assert_eq!(interspersed.by_ref().take(2).collect::<Vec<_>>(), [0, -1]);
+    assert_eq!(taken.get(), 2);
+}
+
+#[test]
+fn write_join() {
+    let input = ["foo", "bar", "baz"];
+    let mut output = Vec::new();
+
+    input.iter().write_join(&mut output, ", ").unwrap();
+    assert_eq!(output, b"foo, bar, baz");
+}
+
 //------------------------------------------------------------------------------
-struct Chunk<I: Iterator> {
-    inner: I,
-    size: usize
+pub struct Chunk<I: Iterator> {
+    shared: Rc<RefCell<ChunkState<I>>>
+}
+
+/// State shared by the chunks and the iterator handing them out.
+struct ChunkState<I: Iterator> {
+    inner: Fuse<I>,
+    size: usize,
+    /// Number of the current chunk, the only one still yielding elements.
+    index: usize,
+    /// Elements of the current chunk taken from `inner` so far.
+    taken: usize,
+    /// First element of the current chunk, taken to find out whether there
+    /// is one, until the chunk yields it.
+    first: Option<I::Item>
 }
 
 impl<I> Iterator for Chunk<I> where I: Iterator {
-    type Item = ::std::vec::IntoIter<I::Item>;
+    type Item = ChunkIter<I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut state = self.shared.borrow_mut();
+
+        while state.taken < state.size && state.inner.next().is_some() {
+            state.taken += 1;
+        }
+
+        let first = state.inner.next()?;
+        state.index += 1;
+        state.taken = 1;
+        state.first = Some(first);
+
+        Some(ChunkIter { shared: self.shared.clone(), index: state.index })
+    }
+}
+
+/// Elements of one chunk of `Chunk`.
+pub struct ChunkIter<I: Iterator> {
+    shared: Rc<RefCell<ChunkState<I>>>,
+    index: usize
+}
+
+impl<I> Iterator for ChunkIter<I> where I: Iterator {
+    type Item = I::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // TODO: is there a way to avoid the collect and do it lazyly?
-        let result = self.inner.by_ref().take(self.size).collect::<Vec<_>>();
+        let mut state = self.shared.borrow_mut();
 
-        if result.is_empty() {
+        if state.index != self.index {
             return None;
-        } else {
-            return Some(result.into_iter());
         }
+        if let Some(first) = state.first.take() {
+            return Some(first);
+        }
+        if state.taken == state.size {
+            return None;
+        }
+
+        state.taken += 1;
+        state.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let state = self.shared.borrow();
+
+        if state.index != self.index {
+            return (0, Some(0));
+        }
+
+        let first = usize::from(state.first.is_some());
+        let left  = state.size - state.taken;
+        let (lower, upper) = state.inner.size_hint();
+
+        (first + lower.min(left), Some(first + upper.map_or(left, |upper| upper.min(left))))
     }
 }
 
 //------------------------------------------------------------------------------
-struct Interleave<I> {
-    inner: Vec<I>,
-    index: usize
+pub struct Interleave<I: Iterator> {
+    inner: Vec<Peekable<I>>,
+    index: usize,
+    /// Whether one of the iterators ran out, which ends the interleaving.
+    done: bool
 }
 
 impl<I> Iterator for Interleave<I> where I: Iterator {
     type Item = I::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let result = match self.inner[self.index].next() {
-            Some(e) => Some(e),
-            None    => None
-        };
+        // Every round starts only if all of the iterators have elements left.
+        if self.index == 0 && !self.done {
+            self.done = self.inner.iter_mut().any(|inner| inner.peek().is_none());
+        }
+        if self.done || self.inner.is_empty() {
+            return None;
+        }
 
+        let result = self.inner[self.index].next();
         self.index = (self.index + 1) % self.inner.len();
 
         result
@@ -157,7 +368,54 @@ impl<I> Iterator for Interleave<I> where I: Iterator {
 }
 
 //------------------------------------------------------------------------------
-struct ChainAll<I: Iterator> {
+pub struct InterleaveLongest<I: Iterator> {
+    inner: Vec<Peekable<I>>,
+    index: usize,
+    fill: I::Item
+}
+
+impl<I> Iterator for InterleaveLongest<I> where I: Iterator, I::Item: Clone {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Every round starts only if one of the iterators has elements left.
+        if self.index == 0 && self.inner.iter_mut().all(|inner| inner.peek().is_none()) {
+            return None;
+        }
+
+        let result = self.inner[self.index].next().unwrap_or_else(|| self.fill.clone());
+
+        self.index = (self.index + 1) % self.inner.len();
+
+        Some(result)
+    }
+}
+
+//------------------------------------------------------------------------------
+pub struct Intersperse<I: Iterator> {
+    inner: Peekable<I>,
+    separator: I::Item,
+    /// Whether an element was yielded last, so that a separator is next if
+    /// there are more.
+    separate: bool
+}
+
+impl<I> Iterator for Intersperse<I> where I: Iterator, I::Item: Clone {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.separate && self.inner.peek().is_some() {
+            self.separate = false;
+            return Some(self.separator.clone());
+        }
+
+        self.separate = true;
+        self.inner.next()
+    }
+}
+
+//------------------------------------------------------------------------------
+pub struct ChainAll<I: Iterator> {
     current: Option<I::Item>,
     all: I
 }
@@ -0,0 +1,73 @@
+//! Agenda output.
+//!
+//! Every day is a line with its weekday and date, followed by what happens
+//! on it: the events given as the legend, which the other formats list
+//! after the calendar, are listed on their days instead. Weeks are
+//! separated by blank lines. Days are styled as in the text output.
+
+use date::{ weekday, Date, DateRange };
+use events::Event;
+use advanced_iterator::AdvancedIterator;
+use format::{ change_label, clock_change, day_role, daylight_of, days_per_week, paint,
+              render_to_string, week_ranges, Options, Renderer };
+use std::io::{ self, Write };
+
+#[derive(Copy, Clone, Debug)]
+pub struct Agenda;
+
+impl Renderer for Agenda {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        render_to_string(self, range, options, legend)
+    }
+
+    /// Writes a week at a time.
+    fn render_to(&self, out: &mut dyn Write, range: DateRange, options: &Options,
+                 legend: &[Event]) -> io::Result<()>
+    {
+        week_ranges(range, options)
+             .into_iter()
+             .map(|week| {
+                 week.filter(|&date| weekday(date) < days_per_week(options))
+                     .map(|date| render_day(date, options, legend))
+                     .collect::<String>()
+             })
+             .filter(|week| !week.is_empty())
+             .write_join(out, "\n")
+    }
+}
+
+fn render_day(date: Date, options: &Options, events: &[Event]) -> String {
+    let day = date.format("%a %Y-%m-%d").to_string();
+    let descriptions = events.iter()
+                             .filter(|event| event.dates.contains(date))
+                             .map(|event| event.description.clone())
+                             .chain(daylight_of(date, options).map(|daylight| {
+                                 format!("☀ {}", daylight)
+                             }))
+                             .chain(clock_change(date, options).map(|change| {
+                                 format!("clocks {}", change_label(change))
+                             }))
+                             .collect::<Vec<_>>();
+
+    let line = format!("{}  {}", paint(&[(day, day_role(date, options))], options),
+                                 descriptions.join("; "));
+    format!("{}\n", line.trim_end())
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn render_lists_events_on_their_days() {
+    use chrono::{ TimeZone, UTC };
+
+    let range  = DateRange::new(UTC.ymd(2015, 1, 3), UTC.ymd(2015, 1, 7));
+    let events = [Event::new(DateRange::new(UTC.ymd(2015, 1, 4), UTC.ymd(2015, 1, 6)), "Trip"),
+                  Event::new(DateRange::new(UTC.ymd(2015, 1, 5), UTC.ymd(2015, 1, 6)), "Talk")];
+
+    assert_eq!(Agenda.render(range, &Options::default(), &events),
+               "Sat 2015-01-03\n\
+                Sun 2015-01-04  Trip\n\
+                \n\
+                Mon 2015-01-05  Trip; Talk\n\
+                Tue 2015-01-06\n");
+}
@@ -0,0 +1,130 @@
+//! Benchmark of the rendering pipeline.
+//!
+//! `calendar bench [--years=<num>]` renders the given number of years
+//! (default: 100) and reports how long each phase took and, if built with
+//! the `bench` feature, how many allocations it made, so that changes
+//! affecting performance can be compared reproducibly. The command is left
+//! out of the usage, as it is only of interest to developers.
+
+use calendar::advanced_iterator::AdvancedIterator;
+use calendar::date::DateRange;
+use calendar::format::{ layout_month, layout_month_by_days, Options, Renderer, Text };
+use chrono::{ TimeZone, UTC };
+#[cfg(feature = "bench")]
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::io::{ self, Write };
+#[cfg(feature = "bench")]
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+pub const DEFAULT_YEARS: u32 = 100;
+/// The synthetic workload starts at this year.
+const FIRST_YEAR: i32 = 2000;
+
+/// Number of allocations made so far by the program.
+#[cfg(feature = "bench")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting allocations for the phases. Registered
+/// as the global allocator of the program with the `bench` feature only,
+/// as counting costs an atomic increment per allocation.
+#[cfg(feature = "bench")]
+pub struct CountingAllocator;
+
+#[cfg(feature = "bench")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Time taken and allocations made by a phase. Allocations are counted
+/// across all threads, so they are only exact while nothing else runs, and
+/// only with the `bench` feature.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Phase {
+    pub name:        &'static str,
+    pub time:        Duration,
+    pub allocations: Option<usize>
+}
+
+/// Number of allocations made so far, if they are counted.
+#[cfg(feature = "bench")]
+fn allocations() -> Option<usize> {
+    Some(ALLOCATIONS.load(Ordering::Relaxed))
+}
+
+/// Number of allocations made so far, if they are counted.
+#[cfg(not(feature = "bench"))]
+fn allocations() -> Option<usize> {
+    None
+}
+
+/// Renders `years` years, timing every phase separately.
+pub fn run(years: u32) -> Vec<Phase> {
+    let range   = DateRange::new(UTC.ymd(FIRST_YEAR, 1, 1),
+                                 UTC.ymd(FIRST_YEAR + years as i32, 1, 1));
+    let options = Options::default();
+    let mut result = Vec::new();
+
+    let mut time = |name, phase: &mut dyn FnMut()| {
+        let before = allocations();
+        let start  = Instant::now();
+        phase();
+        result.push(Phase { name,
+                            time:        start.elapsed(),
+                            allocations: allocations().zip(before)
+                                                      .map(|(after, before)| after - before) });
+    };
+
+    time("date iteration", &mut || { assert!(range.count() > 0); });
+    time("grouping", &mut || {
+        let weeks = range.by_month().flat_map(|month| month.by_week()).count();
+        assert!(weeks > 0);
+    });
+    // Lines of months, as the text output lays them out.
+    time("chunking", &mut || {
+        let rows = range.by_month().chunk(3).map(|row| row.count()).sum::<usize>();
+        assert!(rows > 0);
+    });
+    // Plain months are laid out arithmetically, others day by day.
+    time("layout", &mut || {
+        let lines = range.by_month().flat_map(|month| layout_month(month, &options)).count();
+        assert!(lines > 0);
+    });
+    time("layout by days", &mut || {
+        let lines = range.by_month()
+                         .flat_map(|month| layout_month_by_days(month, &options))
+                         .count();
+        assert!(lines > 0);
+    });
+
+    let mut output = String::new();
+    time("formatting", &mut || {
+        output = Text { months_per_line: 3, banner: true }.render(range, &options, &[]);
+    });
+    time("writing", &mut || { io::sink().write_all(output.as_bytes()).unwrap(); });
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn run_times_every_phase() {
+    let phases = run(1);
+
+    assert_eq!(phases.iter().map(|phase| phase.name).collect::<Vec<_>>(),
+               ["date iteration", "grouping", "chunking", "layout", "layout by days",
+                "formatting", "writing"]);
+}
@@ -0,0 +1,168 @@
+//! Named dates.
+//!
+//! Bookmarks live in `~/.config/calendar/bookmarks` (honoring
+//! `$XDG_CONFIG_HOME`), one `name = YYYY-MM-DD` per line. Wherever a date is
+//! expected on the command line, the name of a bookmark can be given instead.
+
+use calendar::date::Date;
+use calendar::parse::parse_date;
+use config;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bookmarks {
+    /// File the bookmarks are saved to, if there is one.
+    path:  Option<PathBuf>,
+    dates: BTreeMap<String, Date>
+}
+
+impl Bookmarks {
+    /// Reads the bookmarks from the file. A missing file means there are no
+    /// bookmarks yet.
+    pub fn load(path: PathBuf) -> Result<Bookmarks, String> {
+        let dates = match fs::read_to_string(&path) {
+            Ok(input) => parse(&input).map_err(|e| format!("{}: {}", path.display(), e))?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e))
+        };
+
+        Ok(Bookmarks { path: Some(path), dates })
+    }
+
+    /// The bookmarks in the user's configuration directory. Without one
+    /// there are no bookmarks, and they can't be saved.
+    pub fn user() -> Result<Bookmarks, String> {
+        match Bookmarks::user_path() {
+            Some(path) => Bookmarks::load(path),
+            None       => Ok(Bookmarks::default())
+        }
+    }
+
+    /// Path of the bookmarks in the user's configuration directory, if
+    /// there is one.
+    pub fn user_path() -> Option<PathBuf> {
+        config::dir().map(|dir| dir.join("bookmarks"))
+    }
+
+    /// Whether the date or range of dates could name a bookmark, which
+    /// otherwise don't need to be read to expand it.
+    pub fn named_in(input: &str) -> bool {
+        input.split("..").any(|part| valid_name(part.trim()))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Date> {
+        self.dates.get(name).cloned()
+    }
+
+    /// Returns the bookmarks ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Date)> {
+        self.dates.iter().map(|(name, &date)| (&**name, date))
+    }
+
+    /// Adds a bookmark, replacing any previous one of the same name.
+    pub fn add(&mut self, name: &str, date: Date) -> Result<(), String> {
+        if !valid_name(name) {
+            return Err(format!("invalid bookmark name: '{}' (must start with a letter \
+                                and contain only letters, digits, '-' and '_')", name));
+        }
+
+        self.dates.insert(name.to_string(), date);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<Date, String> {
+        self.dates.remove(name).ok_or_else(|| format!("no bookmark named '{}'", name))
+    }
+
+    /// Writes the bookmarks back to the file they were loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let path  = self.path.as_ref().ok_or("can't determine the configuration directory")?;
+        let error = |e: io::Error| format!("{}: {}", path.display(), e);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+
+        let output = self.iter()
+                         .map(|(name, date)| format!("{} = {}\n", name, date.format("%Y-%m-%d")))
+                         .collect::<String>();
+        fs::write(path, output).map_err(error)
+    }
+
+    /// Replaces the names of bookmarks in a date or a range of dates
+    /// (`FIRST..LAST`) with their dates. Everything else is left as is.
+    pub fn expand(&self, input: &str) -> String {
+        input.split("..")
+             .map(|part| match self.get(part.trim()) {
+                 Some(date) => date.format("%Y-%m-%d").to_string(),
+                 None       => part.to_string()
+             })
+             .collect::<Vec<_>>()
+             .join("..")
+    }
+}
+
+/// Names start with a letter, so that they can't be mistaken for dates.
+fn valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_alphabetic)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+fn parse(input: &str) -> Result<BTreeMap<String, Date>, String> {
+    let mut result = BTreeMap::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+
+        let error = |e| format!("line {}: {}", index + 1, e);
+        let mut parts = line.splitn(2, '=').map(str::trim);
+
+        match (parts.next(), parts.next()) {
+            (Some(name), Some(date)) if valid_name(name) => {
+                result.insert(name.to_string(), parse_date(date).map_err(error)?);
+            },
+            _ => return Err(error(format!("expected 'name = YYYY-MM-DD', got '{}'", line)))
+        }
+    }
+
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn bookmarks_can_be_saved_and_loaded() {
+    use chrono::{ TimeZone, UTC };
+    use std::env;
+
+    let dir  = env::temp_dir().join(format!("calendar-bookmarks-test-{}",
+                                            ::std::process::id()));
+    let path = dir.join("bookmarks");
+
+    let mut bookmarks = Bookmarks::load(path.clone()).unwrap();
+    assert_eq!(bookmarks.iter().count(), 0);
+
+    bookmarks.add("release-freeze", UTC.ymd(2024, 11, 15)).unwrap();
+    bookmarks.add("kickoff", UTC.ymd(2024, 1, 8)).unwrap();
+    assert!(bookmarks.add("2024-12-24", UTC.ymd(2024, 12, 24)).is_err());
+    bookmarks.save().unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(),
+               "kickoff = 2024-01-08\nrelease-freeze = 2024-11-15\n");
+
+    let mut bookmarks = Bookmarks::load(path).unwrap();
+    assert_eq!(bookmarks.get("release-freeze"), Some(UTC.ymd(2024, 11, 15)));
+    assert_eq!(bookmarks.expand("kickoff..release-freeze"), "2024-01-08..2024-11-15");
+    assert_eq!(bookmarks.expand("2024-03..kickoff"), "2024-03..2024-01-08");
+    assert!(Bookmarks::named_in("2024-03..kickoff"));
+    assert!(!Bookmarks::named_in("2024-03..2024-04"));
+
+    assert_eq!(bookmarks.remove("kickoff"), Ok(UTC.ymd(2024, 1, 8)));
+    assert!(bookmarks.remove("kickoff").is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+}
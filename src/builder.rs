@@ -0,0 +1,480 @@
+//! Builder for configuring and rendering a calendar.
+
+use agenda::Agenda;
+use cal::Cal;
+use calendars::System;
+use chrono::Weekday;
+use date::{ dates, Date, DateRange, Reckoning, WeekNumbering, Zone };
+use diagnostics::{ Diagnostics, Kind };
+use events::Event;
+use format::{ self, Format, Frequencies, LayoutConfig, Options, Renderer, Text, TitleFormat };
+use html::Html;
+use linear::Linear;
+use list::List;
+use markdown::Markdown;
+use std::fmt;
+use std::io::{ self, Write };
+use style::{ Scale, Style };
+use sun::Location;
+use svg::{ PageSize, Svg };
+use width::Width;
+#[cfg(feature = "serde")]
+use serde::ser::{ Serialize, SerializeStruct, Serializer };
+
+/// A calendar of a range of months, configured with builder-style methods:
+///
+/// ```
+/// extern crate calendar;
+/// extern crate chrono;
+///
+/// use calendar::Calendar;
+/// use chrono::Weekday;
+///
+/// let calendar = Calendar::year(2015).unwrap()
+///                                    .months_per_line(4)
+///                                    .week_start(Weekday::Sun)
+///                                    .to_string();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Calendar {
+    range:           DateRange,
+    /// The year of a calendar of a whole year, whose days depend on the
+    /// reckoning.
+    year:            Option<i32>,
+    options:         Options,
+    months_per_line: usize,
+    banner:          bool,
+    format:          Format,
+    page_size:       PageSize,
+    legend:          Vec<Event>,
+    annotated:       bool,
+    jobs:            usize
+}
+
+impl Calendar {
+    /// Calendar of the whole year, in the calendar of `reckoning`, or an
+    /// error if the year isn't supported.
+    pub fn year(year: i32) -> Result<Calendar, String> {
+        Ok(Calendar { year: Some(year), ..Calendar::range(dates(year)?) })
+    }
+
+    /// Calendar of the months in the range. Months only partially in the
+    /// range show just the days in the range.
+    pub fn range(range: DateRange) -> Calendar {
+        Calendar {
+            range,
+            year:            None,
+            options:         Options::default(),
+            months_per_line: 3,
+            banner:          true,
+            format:          Format::Text,
+            page_size:       PageSize::A4,
+            legend:          Vec::new(),
+            annotated:       false,
+            jobs:            1
+        }
+    }
+
+    /// Number of months laid out side by side in text and SVG output, at
+    /// least one.
+    pub fn months_per_line(mut self, months_per_line: usize) -> Calendar {
+        self.months_per_line = months_per_line.max(1);
+        self
+    }
+
+    /// Whether text output of whole years starts with a centered banner
+    /// with the year. On by default.
+    pub fn banner(mut self, banner: bool) -> Calendar {
+        self.banner = banner;
+        self
+    }
+
+    pub fn week_start(mut self, week_start: Weekday) -> Calendar {
+        self.options.week_start = week_start;
+        self
+    }
+
+    /// Splits weeks like the numbering does, if they start on its first
+    /// day, e.g. US weeks at New Year.
+    pub fn week_numbering(mut self, numbering: Option<WeekNumbering>) -> Calendar {
+        self.options.week_numbering = numbering;
+        self
+    }
+
+    /// Shows only Monday to Friday, with weeks starting on Monday.
+    pub fn workweek(mut self, workweek: bool) -> Calendar {
+        self.options.workweek = workweek;
+        self
+    }
+
+    /// Shows the weeks of text output as columns rather than rows.
+    pub fn vertical(mut self, vertical: bool) -> Calendar {
+        self.options.vertical = vertical;
+        self
+    }
+
+    /// Starts years with the given month rather than January, e.g. 4 for
+    /// fiscal years from April to March.
+    pub fn year_start(mut self, month: u32) -> Calendar {
+        self.options.year_start = month;
+        self
+    }
+
+    /// Numbers the days by the day of the year rather than of the month.
+    pub fn julian(mut self, julian: bool) -> Calendar {
+        self.options.julian = julian;
+        self
+    }
+
+    /// Names the days in the given calendar, leaving out the days skipped
+    /// by a reform. Dates given to the builder are dates of the proleptic
+    /// Gregorian calendar, see `Reckoning::rename`.
+    pub fn reckoning(mut self, reckoning: Reckoning) -> Calendar {
+        self.options.reckoning = reckoning;
+        if let Some(year) = self.year {
+            self.range = reckoning.dates(year);
+        }
+        self
+    }
+
+    /// Shows the day of every date in another calendar below it in text
+    /// output, or beside it in linear output.
+    pub fn overlay(mut self, system: Option<System>) -> Calendar {
+        self.options.overlay = system;
+        self
+    }
+
+    /// Marks new, full and quarter moons after the day number in text
+    /// output.
+    pub fn moon(mut self, moon: bool) -> Calendar {
+        self.options.moon = moon;
+        self
+    }
+
+    /// Geometry of text output.
+    pub fn layout(mut self, layout: LayoutConfig) -> Calendar {
+        self.options.layout = layout;
+        self
+    }
+
+    /// Measurement of text width, for terminals that render some
+    /// characters wider or narrower than Unicode says.
+    pub fn width(mut self, width: Width) -> Calendar {
+        self.options.width = width;
+        self
+    }
+
+    /// Lists the weeks of every month latest first.
+    pub fn flip_weeks(mut self, flip_weeks: bool) -> Calendar {
+        self.options.flip_weeks = flip_weeks;
+        self
+    }
+
+    pub fn weekend_style(mut self, style: Style) -> Calendar {
+        self.options.weekend = style;
+        self
+    }
+
+    pub fn holiday_style(mut self, style: Style) -> Calendar {
+        self.options.holiday = style;
+        self
+    }
+
+    pub fn holidays<I: IntoIterator<Item = Date>>(mut self, dates: I) -> Calendar {
+        self.options.holidays.extend(dates);
+        self
+    }
+
+    /// Names of holidays, listed on their days in list output. The dates
+    /// are marked as holidays as well.
+    pub fn holiday_names<I: IntoIterator<Item = (Date, String)>>(mut self, names: I) -> Calendar {
+        for (date, name) in names {
+            self.options.holidays.insert(date);
+            self.options.holiday_names.entry(date).or_default().push(name);
+        }
+        self
+    }
+
+    /// Whether list output leaves out days without holidays or events.
+    pub fn annotated(mut self, annotated: bool) -> Calendar {
+        self.annotated = annotated;
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Calendar {
+        self.options.highlight = style;
+        self
+    }
+
+    /// Highlights the date. Can be called repeatedly.
+    pub fn highlight(mut self, date: Date) -> Calendar {
+        self.options.highlights.insert(date);
+        self
+    }
+
+    /// Colors days by how often they occur in `heat`, in the styles of the
+    /// scale.
+    pub fn heat(mut self, heat: Frequencies, scale: Scale) -> Calendar {
+        self.options.heat       = heat;
+        self.options.heat_scale = scale;
+        self
+    }
+
+    /// Shows the date in the given style, unless it's highlighted. Can be
+    /// called repeatedly.
+    pub fn mark(mut self, date: Date, style: Style) -> Calendar {
+        self.options.marks.insert(date, style);
+        self
+    }
+
+    /// Starts a new line of months at the month of the date, under a
+    /// divider with the label. Can be called repeatedly.
+    pub fn break_at(mut self, date: Date, label: &str) -> Calendar {
+        self.options.breaks.push((date, label.to_string()));
+        self
+    }
+
+    pub fn today(mut self, today: Date) -> Calendar {
+        self.options.today = Some(today);
+        self
+    }
+
+    /// Marks the days of the events, and those taking several days with a
+    /// bar across them. Recurring events should be expanded first, see
+    /// `events::expand`.
+    pub fn events(mut self, events: &[Event]) -> Calendar {
+        for event in events {
+            if event.dates.days() > 1 {
+                self.options.bars.add(event.dates);
+            } else {
+                self.options.events.insert(event.dates.start);
+            }
+        }
+        self
+    }
+
+    /// Format of the month titles of text output, e.g. `"%b %Y".parse()?`.
+    pub fn title_format(mut self, format: TitleFormat) -> Calendar {
+        self.options.title_format = format;
+        self
+    }
+
+    /// Whether months in text output have a row of weekday names.
+    pub fn weekday_header(mut self, weekday_header: bool) -> Calendar {
+        self.options.weekday_header = weekday_header;
+        self
+    }
+
+    /// Shows when the sun is up at the location, in the time of the zone,
+    /// and marks the days its clocks change.
+    pub fn daylight(mut self, location: Option<Location>, zone: Zone) -> Calendar {
+        self.options.location = location;
+        self.options.zone     = zone;
+        self
+    }
+
+    /// Lays out the legend below every month in text output, rather than
+    /// after the calendar.
+    pub fn footnotes(mut self, footnotes: bool) -> Calendar {
+        self.options.footnotes = footnotes;
+        self
+    }
+
+    /// Lists the events after the calendar, in the given order.
+    pub fn legend(mut self, events: Vec<Event>) -> Calendar {
+        self.legend = events;
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Calendar {
+        self.format = format;
+        self
+    }
+
+    /// Number of years of text output rendered at a time, each in a thread
+    /// of its own. The output is the same for any number. Other formats are
+    /// rendered in one go.
+    pub fn jobs(mut self, jobs: usize) -> Calendar {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Page size of SVG output.
+    pub fn page_size(mut self, page_size: PageSize) -> Calendar {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Renders the calendar like `render`, along with warnings about what
+    /// was given for it but isn't shown.
+    pub fn render_with_diagnostics(&self) -> (String, Diagnostics) {
+        (self.render(), self.diagnostics())
+    }
+
+    /// Warnings about what was given for the calendar but isn't shown.
+    pub fn diagnostics(&self) -> Diagnostics {
+        let mut diagnostics = Diagnostics::new();
+
+        for (date, label) in &self.options.breaks {
+            if !self.range.contains(*date) {
+                diagnostics.warn(Kind::OutOfRange,
+                                 format!("break '{}' on {} is outside the calendar", label,
+                                         date.format("%Y-%m-%d")));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Checks that the calendar can be rendered in the configured format:
+    /// that its months and legend fit on the page of SVG output.
+    pub fn check(&self) -> Result<(), String> {
+        if self.format == Format::Svg {
+            self.svg().check(format::months(self.range, &self.options).len(), self.legend.len())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Renders the calendar in the configured format.
+    pub fn render(&self) -> String {
+        if self.format == Format::Text {
+            let mut result = Vec::new();
+            self.render_to(&mut result).expect("writing to a vector can't fail");
+            String::from_utf8(result).expect("rendered calendars are UTF-8")
+        } else {
+            self.renderer().render(self.range, &self.options, &self.legend)
+        }
+    }
+
+    /// Writes the calendar `render` returns to `out`, a line of months at
+    /// a time in text output, so that long ranges appear as they are
+    /// rendered. Wrap writers like `Stdout`, which flushes every line, in a
+    /// `BufWriter`.
+    pub fn render_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if self.format == Format::Text {
+            self.text().render_years_parallel(out, self.range, &self.options, &self.legend,
+                                              self.jobs)
+        } else {
+            self.renderer().render_to(out, self.range, &self.options, &self.legend)
+        }
+    }
+
+    fn text(&self) -> Text {
+        Text { months_per_line: self.months_per_line, banner: self.banner }
+    }
+
+    fn svg(&self) -> Svg {
+        Svg { page: self.page_size, months_per_line: self.months_per_line }
+    }
+
+    fn renderer(&self) -> Box<dyn Renderer> {
+        match self.format {
+            Format::Text     => Box::new(self.text()),
+            Format::Html     => Box::new(Html { months_per_line: self.months_per_line }),
+            Format::Markdown => Box::new(Markdown { months_per_line: self.months_per_line }),
+            Format::Linear   => Box::new(Linear),
+            Format::Agenda   => Box::new(Agenda),
+            Format::List     => Box::new(List { annotated: self.annotated }),
+            Format::Svg      => Box::new(self.svg()),
+            Format::Cal      => Box::new(Cal)
+        }
+    }
+}
+
+impl fmt::Display for Calendar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Calendar {
+    /// Serializes the calendar as its range, its format and the output it
+    /// renders, like `{"range": {...}, "format": "html", "output": "..."}`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Calendar", 3)?;
+        state.serialize_field("range", &self.range)?;
+        state.serialize_field("format", &self.format)?;
+        state.serialize_field("output", &self.render())?;
+        state.end()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn calendar_renders_with_configured_options() {
+    use chrono::{ TimeZone, UTC };
+
+    let calendar = Calendar::year(2015).unwrap()
+                                       .months_per_line(4)
+                                       .week_start(Weekday::Sun)
+                                       .highlight_style(Style::Reverse)
+                                       .highlight(UTC.ymd(2015, 1, 3))
+                                       .to_string();
+    let lines = calendar.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 1 + 3 * 7);
+    assert_eq!(lines[0].trim(), "2015");
+    assert!(lines[2].starts_with("              1  2  \x1b[7m3\x1b[0m "));
+}
+
+#[test]
+fn render_to_writes_the_calendar() {
+    use chrono::{ TimeZone, UTC };
+
+    let range    = DateRange::new(UTC.ymd(2014, 12, 30), UTC.ymd(2015, 1, 3));
+    let expected = [
+        (Format::Text, "                               2014                               \n\
+                        \x20      December       \n\
+                        \x20   30 31             \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \n\
+                        \x20                              2015                               \n\
+                        \x20      January        \n\
+                        \x20          1  2       \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n\
+                        \x20                     \n"),
+        (Format::Linear, "Dec 2014\nTu 30\nWe 31\n\nJan 2015\nTh  1\nFr  2\n"),
+        (Format::Agenda, "Tue 2014-12-30\nWed 2014-12-31\nThu 2015-01-01\nFri 2015-01-02\n"),
+        (Format::List,   "2014-12-30 Tuesday\n2014-12-31 Wednesday\n2015-01-01 Thursday\n\
+                          2015-01-02 Friday\n")
+    ];
+
+    for &(format, expected) in &expected {
+        let calendar   = Calendar::range(range).format(format);
+        let mut output = Vec::new();
+
+        calendar.render_to(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected, "{:?}", format);
+        assert_eq!(calendar.render(), expected, "{:?}", format);
+    }
+}
+
+#[test]
+fn jobs_render_years_in_parallel_in_order() {
+    use chrono::{ TimeZone, UTC };
+
+    let calendar = Calendar::range(DateRange::new(UTC.ymd(2013, 7, 1), UTC.ymd(2020, 3, 1)))
+                            .legend(vec![Event::new(DateRange::new(UTC.ymd(2015, 1, 1),
+                                                                   UTC.ymd(2015, 1, 2)),
+                                                    "New Year")]);
+
+    assert_eq!(calendar.clone().jobs(3).render(), calendar.render());
+}
+
+#[test]
+fn months_per_line_is_at_least_one() {
+    let calendar = Calendar::year(2015).unwrap();
+
+    assert_eq!(calendar.clone().months_per_line(0).render(),
+               calendar.months_per_line(1).render());
+}
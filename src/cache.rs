@@ -0,0 +1,224 @@
+//! On-disk cache.
+//!
+//! Cached data lives in `~/.cache/calendar` (honoring `$XDG_CACHE_HOME`),
+//! with a subdirectory for each kind of data. Every file in a subdirectory
+//! is one cache entry.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{ Duration, SystemTime };
+
+/// Kind of cached data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Calendars fetched from remote sources.
+    Remote,
+    /// Rendered calendars.
+    Render
+}
+
+impl Kind {
+    pub const ALL: [Kind; 2] = [Kind::Remote, Kind::Render];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Remote => "remote",
+            Kind::Render => "render"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Kind, String> {
+        Kind::ALL.iter()
+                 .cloned()
+                 .find(|kind| kind.name() == name)
+                 .ok_or_else(|| format!("unknown cache: '{}' (available: remote, render)",
+                                        name))
+    }
+}
+
+/// A cached file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name:     String,
+    pub size:     u64,
+    pub modified: SystemTime
+}
+
+impl Entry {
+    /// How long ago the entry was written.
+    pub fn age(&self) -> Duration {
+        SystemTime::now().duration_since(self.modified)
+                         .unwrap_or_else(|_| Duration::from_secs(0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cache {
+    root: PathBuf
+}
+
+impl Cache {
+    pub fn new(root: PathBuf) -> Cache {
+        Cache { root }
+    }
+
+    /// The cache in the user's cache directory.
+    pub fn user() -> Option<Cache> {
+        let root = env::var_os("XDG_CACHE_HOME")
+                      .map(PathBuf::from)
+                      .or_else(|| {
+                          env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache"))
+                      });
+
+        root.map(|root| Cache::new(root.join("calendar")))
+    }
+
+    /// Directory containing the entries of the given kind.
+    pub fn dir(&self, kind: Kind) -> PathBuf {
+        self.root.join(kind.name())
+    }
+
+    /// Returns the entries of the given kind, ordered by name.
+    pub fn entries(&self, kind: Kind) -> io::Result<Vec<Entry>> {
+        let dir = match fs::read_dir(self.dir(kind)) {
+            Ok(dir) => dir,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e)  => return Err(e)
+        };
+
+        let mut result = Vec::new();
+
+        for entry in dir {
+            let entry    = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() { continue; }
+
+            result.push(Entry {
+                name:     entry.file_name().to_string_lossy().into_owned(),
+                size:     metadata.len(),
+                modified: metadata.modified()?
+            });
+        }
+
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(result)
+    }
+
+    /// Returns the contents of the entry of the given kind, if it exists.
+    pub fn read(&self, kind: Kind, name: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.dir(kind).join(name)) {
+            Ok(contents)                                      => Ok(Some(contents)),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e)                                            => Err(e)
+        }
+    }
+
+    /// Writes the entry of the given kind, replacing any previous one.
+    pub fn write(&self, kind: Kind, name: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(self.dir(kind))?;
+        fs::write(self.dir(kind).join(name), contents)
+    }
+
+    /// Removes all entries of the given kind. Returns the number of removed
+    /// entries.
+    pub fn clear(&self, kind: Kind) -> io::Result<usize> {
+        let entries = self.entries(kind)?;
+
+        for entry in &entries {
+            fs::remove_file(self.dir(kind).join(&entry.name))?;
+        }
+
+        Ok(entries.len())
+    }
+}
+
+/// Formats a size in bytes in a human readable way, e.g. `12.3 KiB`.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut value = size as f64 / 1024.0;
+    let mut unit  = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit  += 1;
+    }
+
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Formats a duration in a human readable way, e.g. `3 hours`.
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+
+    let (count, unit) = match seconds {
+        0..=59         => return "less than a minute".to_string(),
+        60..=3599      => (seconds / 60,    "minute"),
+        3600..=86399   => (seconds / 3600,  "hour"),
+        _              => (seconds / 86400, "day")
+    };
+
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn entries_can_be_listed_and_cleared() {
+    let root  = env::temp_dir().join(format!("calendar-cache-test-{}",
+                                             ::std::process::id()));
+    let cache = Cache::new(root.clone());
+
+    assert_eq!(cache.entries(Kind::Remote).unwrap(), vec![]);
+
+    fs::create_dir_all(cache.dir(Kind::Remote)).unwrap();
+    fs::write(cache.dir(Kind::Remote).join("b.ics"), "BEGIN:VCALENDAR").unwrap();
+    fs::write(cache.dir(Kind::Remote).join("a.ics"), "").unwrap();
+
+    let entries = cache.entries(Kind::Remote).unwrap();
+    assert_eq!(entries.iter().map(|e| &*e.name).collect::<Vec<_>>(),
+               vec!["a.ics", "b.ics"]);
+    assert_eq!(entries[1].size, 15);
+
+    assert_eq!(cache.clear(Kind::Render).unwrap(), 0);
+    assert_eq!(cache.clear(Kind::Remote).unwrap(), 2);
+    assert_eq!(cache.entries(Kind::Remote).unwrap(), vec![]);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn entries_can_be_written_and_read() {
+    let root  = env::temp_dir().join(format!("calendar-cache-rw-test-{}",
+                                             ::std::process::id()));
+    let cache = Cache::new(root.clone());
+
+    assert_eq!(cache.read(Kind::Render, "prompt").unwrap(), None);
+
+    cache.write(Kind::Render, "prompt", "one").unwrap();
+    cache.write(Kind::Render, "prompt", "two").unwrap();
+    assert_eq!(cache.read(Kind::Render, "prompt").unwrap(), Some("two".to_string()));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn format_size_uses_binary_units() {
+    assert_eq!(format_size(512),         "512 B");
+    assert_eq!(format_size(12_595),      "12.3 KiB");
+    assert_eq!(format_size(3 << 30),     "3.0 GiB");
+}
+
+#[test]
+fn format_age_uses_largest_unit() {
+    assert_eq!(format_age(Duration::from_secs(30)),     "less than a minute");
+    assert_eq!(format_age(Duration::from_secs(60)),     "1 minute");
+    assert_eq!(format_age(Duration::from_secs(7300)),   "2 hours");
+    assert_eq!(format_age(Duration::from_secs(259200)), "3 days");
+}
@@ -0,0 +1,141 @@
+//! Output compatible with POSIX `cal`.
+//!
+//! Reproduces the layout of the traditional `cal`, so that scripts parsing
+//! its output keep working: weeks start on Sunday, every month is 20
+//! columns wide, months are separated by two spaces and trailing blanks are
+//! trimmed. Styles, events and the legend are not shown.
+
+use advanced_iterator::AdvancedIterator;
+use chrono::Datelike;
+use date::DateRange;
+use events::Event;
+use format::{ layout_grid, month_name, months, year_dates, Options, Renderer };
+
+/// Width of a month: seven days of two digits separated by spaces.
+const MONTH_WIDTH: usize = 20;
+const MONTHS_PER_LINE: usize = 3;
+const WEEKS: usize = 6;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Cal;
+
+impl Renderer for Cal {
+    /// Whole years are preceded by the year, like `cal 2015` does, and
+    /// their months are titled with just the name. Otherwise the months are
+    /// titled with their name and year, like `cal 2 2015` does.
+    fn render(&self, range: DateRange, options: &Options, _legend: &[Event]) -> String {
+        let year  = |date| options.reckoning.ymd(date).0;
+        let years = range.group_by(|&date| year(date)).collect::<Vec<_>>();
+
+        if years.iter().all(|&dates| dates == year_dates(year(dates.start), options)) {
+            years.into_iter().map(|dates| render_year(dates, options)).join("\n")
+        } else {
+            render_months(range, true, options)
+        }
+    }
+}
+
+fn render_year(year: DateRange, options: &Options) -> String {
+    let title = options.width.center(&options.reckoning.ymd(year.start).0.to_string(),
+                                     MONTHS_PER_LINE * MONTH_WIDTH + (MONTHS_PER_LINE - 1) * 2);
+
+    format!("{}\n\n{}", title.trim_end(), render_months(year, false, options))
+}
+
+/// Lays out the months three per line, titled with their name and, if
+/// `with_year`, their year.
+fn render_months(range: DateRange, with_year: bool, options: &Options) -> String {
+    let months = months(range, options).into_iter().map(|month| {
+        month_lines(month, with_year, options).into_iter().map(|line| line + "  ")
+    });
+
+    layout_grid(months, MONTHS_PER_LINE, " ".repeat(MONTH_WIDTH + 2))
+        .map(|line| format!("{}\n", line.concat().trim_end()))
+        .chunk(2 + WEEKS)
+        .map(|row| row.collect::<String>())
+        .join("\n")
+}
+
+/// Lines of a month, each `MONTH_WIDTH` wide: the title, the weekday names
+/// and six weeks.
+fn month_lines(month: DateRange, with_year: bool, options: &Options) -> Vec<String> {
+    let title = if with_year {
+        format!("{} {}", month_name(month.start, options), options.reckoning.ymd(month.start).0)
+    } else {
+        month_name(month.start, options)
+    };
+    let mut result = vec![options.width.center(&title, MONTH_WIDTH),
+                          "Su Mo Tu We Th Fr Sa".to_string()];
+
+    let mut week = vec!["  ".to_string(); 7];
+
+    for date in month {
+        let column = date.weekday().num_days_from_sunday() as usize;
+        week[column] = format!("{: >2}", options.reckoning.ymd(date).2);
+
+        if column == 6 {
+            result.push(week.join(" "));
+            week = vec!["  ".to_string(); 7];
+        }
+    }
+
+    if week.iter().any(|day| day != "  ") {
+        result.push(week.join(" "));
+    }
+
+    result.resize(2 + WEEKS, " ".repeat(MONTH_WIDTH));
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+#[cfg(test)]
+use date::{ dates, Reckoning };
+
+#[test]
+fn render_lays_out_month_like_cal() {
+    let month = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+
+    assert_eq!(Cal.render(month, &Options::default(), &[]), "     March 2015
+Su Mo Tu We Th Fr Sa
+ 1  2  3  4  5  6  7
+ 8  9 10 11 12 13 14
+15 16 17 18 19 20 21
+22 23 24 25 26 27 28
+29 30 31
+
+");
+}
+
+#[test]
+fn render_leaves_out_days_skipped_by_the_reform() {
+    let reform  = Reckoning::reform_in(1752).unwrap();
+    let month   = DateRange::new(reform.rename(UTC.ymd(1752, 9, 1)),
+                                 reform.rename(UTC.ymd(1752, 10, 1)));
+    let options = Options { reckoning: reform, ..Options::default() };
+
+    assert_eq!(Cal.render(month, &options, &[]), "   September 1752
+Su Mo Tu We Th Fr Sa
+       1  2 14 15 16
+17 18 19 20 21 22 23
+24 25 26 27 28 29 30
+
+
+
+");
+}
+
+#[test]
+fn render_lays_out_year_like_cal() {
+    let output = Cal.render(dates(2015).unwrap(), &Options::default(), &[]);
+    let lines  = output.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[..5], ["                              2015",
+                            "",
+                            "      January               February               March",
+                            "Su Mo Tu We Th Fr Sa  Su Mo Tu We Th Fr Sa  Su Mo Tu We Th Fr Sa",
+                            "             1  2  3   1  2  3  4  5  6  7   1  2  3  4  5  6  7"]);
+    assert_eq!(lines.len(), 2 + 4 * 8 + 3);
+}
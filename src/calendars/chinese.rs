@@ -0,0 +1,280 @@
+//! The Chinese calendar.
+//!
+//! Months start on the day of the new moon, and the month containing the
+//! winter solstice is the eleventh. Between two of them, there are usually
+//! twelve months, but every two or three years there are thirteen, and the
+//! first of these without a major solar term (a multiple of 30° of the
+//! sun's longitude) is a leap month, numbered like the month before it.
+//! Days are those of Beijing, at UTC+8.
+//!
+//! The moments of new moons and solar terms are computed with the
+//! abbreviated series of Meeus, *Astronomical Algorithms*, accurate to a
+//! few minutes, so a month may start a day off when a new moon falls within
+//! minutes of midnight. The rules are those in use since 1645, also applied
+//! to earlier years.
+
+use date::Date;
+use std::f64::consts::PI;
+use std::fmt;
+use super::fixed;
+
+/// Fixed day of the first day of the first sexagenary cycle, February 15,
+/// 2637 BC.
+const EPOCH: i64 = -963_099;
+
+/// Mean time from one new moon to the next, in days.
+const SYNODIC_MONTH: f64 = 29.530_588_861;
+/// Mean time the sun takes to return to the same longitude, in days.
+const TROPICAL_YEAR: f64 = 365.242_189;
+
+const MONTHS: [&str; 12] = ["正", "二", "三", "四", "五", "六", "七", "八", "九", "十", "冬",
+                            "腊"];
+const LEAP: &str = "闰";
+const ANIMALS: [&str; 12] = ["Rat", "Ox", "Tiger", "Rabbit", "Dragon", "Snake", "Horse",
+                             "Goat", "Monkey", "Rooster", "Dog", "Pig"];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChineseDate {
+    /// Year of the sexagenary cycle, from 1 to 60.
+    pub year:  u32,
+    pub month: u32,
+    /// Whether the month is the leap month following the month `month`.
+    pub leap:  bool,
+    pub day:   u32
+}
+
+impl ChineseDate {
+    pub fn from_date(date: Date) -> ChineseDate {
+        let date = fixed(date);
+
+        let s1       = winter_solstice_on_or_before(date);
+        let s2       = winter_solstice_on_or_before(s1 + 370);
+        let next_m11 = new_moon_before(s2 + 1);
+        let m12      = new_moon_on_or_after(s1 + 1);
+        let months   = |from: i64, to: i64| ((to - from) as f64 / SYNODIC_MONTH).round() as i64;
+
+        // Thirteen months from one eleventh month to the next.
+        let leap_year  = months(m12, next_m11) == 12;
+        let start      = new_moon_before(date + 1);
+        let leaps      = i64::from(leap_year && prior_leap_month(m12, start));
+        let month      = (months(m12, start) - leaps - 1).rem_euclid(12) + 1;
+        let leap       = leap_year && no_major_solar_term(start)
+                         && !prior_leap_month(m12, new_moon_before(start));
+        let elapsed    = (1.5 - month as f64 / 12.0 + (date - EPOCH) as f64 / TROPICAL_YEAR)
+                             .floor() as i64;
+
+        ChineseDate { year:  ((elapsed - 1).rem_euclid(60) + 1) as u32,
+                      month: month as u32,
+                      leap,
+                      day:   (date - start + 1) as u32 }
+    }
+
+    /// The month as a single character, `闰` for a leap month.
+    pub fn abbreviation(&self) -> &'static str {
+        if self.leap { LEAP } else { MONTHS[self.month as usize - 1] }
+    }
+
+    /// Animal of the year's earthly branch.
+    pub fn animal(&self) -> &'static str {
+        ANIMALS[(self.year as usize - 1) % 12]
+    }
+}
+
+impl fmt::Display for ChineseDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}month {} day {}, year of the {}", if self.leap { "leap " } else { "" },
+               self.month, self.day, self.animal())
+    }
+}
+
+//------------------------------------------------------------------------------
+// Days in Beijing
+
+/// Moment in universal time of midnight starting the day in Beijing.
+fn midnight(date: i64) -> f64 {
+    date as f64 - 8.0 / 24.0
+}
+
+/// Day in Beijing of the moment in universal time.
+fn day_of(moment: f64) -> i64 {
+    (moment + 8.0 / 24.0).floor() as i64
+}
+
+fn winter_solstice_on_or_before(date: i64) -> i64 {
+    let mut solstice = solar_longitude_after(270.0, midnight(date - 370));
+    loop {
+        let next = solar_longitude_after(270.0, solstice + 1.0);
+        if day_of(next) > date {
+            return day_of(solstice);
+        }
+        solstice = next;
+    }
+}
+
+fn new_moon_on_or_after(date: i64) -> i64 {
+    day_of(new_moon_after(midnight(date)))
+}
+
+fn new_moon_before(date: i64) -> i64 {
+    day_of(new_moon_before_moment(midnight(date)))
+}
+
+/// The last major solar term reached at the start of the day, numbered
+/// like the months it belongs to: the winter solstice is the eleventh.
+fn major_solar_term(date: i64) -> i64 {
+    let longitude = solar_longitude(midnight(date));
+    (1 + (longitude / 30.0).floor() as i64).rem_euclid(12) + 1
+}
+
+/// Whether no major solar term falls in the month starting on the day.
+fn no_major_solar_term(month: i64) -> bool {
+    major_solar_term(month) == major_solar_term(new_moon_on_or_after(month + 1))
+}
+
+/// Whether a leap month starts on or after `first` and on or before
+/// `month`, both days of new moons.
+fn prior_leap_month(first: i64, mut month: i64) -> bool {
+    while month >= first {
+        if no_major_solar_term(month) {
+            return true;
+        }
+        month = new_moon_before(month);
+    }
+    false
+}
+
+//------------------------------------------------------------------------------
+// Astronomy. Moments are fixed days with a fraction, in universal time.
+
+/// Julian day of the moment.
+fn julian_day(moment: f64) -> f64 {
+    moment + 1_721_424.5
+}
+
+/// Difference between dynamical and universal time, in days, by a long-term
+/// parabola.
+fn delta_t(moment: f64) -> f64 {
+    let centuries = (moment / 365.2425 - 1820.0) / 100.0;
+    (-20.0 + 32.0 * centuries * centuries) / 86_400.0
+}
+
+fn sin(degrees: f64) -> f64 {
+    (degrees * PI / 180.0).sin()
+}
+
+/// Apparent longitude of the sun at the moment, in degrees from 0 to 360.
+fn solar_longitude(moment: f64) -> f64 {
+    let t  = (julian_day(moment + delta_t(moment)) - 2_451_545.0) / 36_525.0;
+    let l0 = 280.466_46 + 36_000.769_83 * t + 0.000_303_2 * t * t;
+    let m  = 357.529_11 + 35_999.050_29 * t - 0.000_153_7 * t * t;
+    let c  = (1.914_602 - 0.004_817 * t - 0.000_014 * t * t) * sin(m)
+             + (0.019_993 - 0.000_101 * t) * sin(2.0 * m)
+             + 0.000_289 * sin(3.0 * m);
+    let omega = 125.04 - 1_934.136 * t;
+
+    (l0 + c - 0.005_69 - 0.004_78 * sin(omega)).rem_euclid(360.0)
+}
+
+/// First moment at or after the given one when the sun reaches the
+/// longitude.
+fn solar_longitude_after(longitude: f64, moment: f64) -> f64 {
+    let rate     = TROPICAL_YEAR / 360.0;
+    let estimate = moment + rate * (longitude - solar_longitude(moment)).rem_euclid(360.0);
+    let (mut low, mut high) = (moment.max(estimate - 5.0), estimate + 5.0);
+
+    // Past the longitude, the difference wraps around to just above zero.
+    while high - low > 1e-5 {
+        let middle = (low + high) / 2.0;
+        if (solar_longitude(middle) - longitude).rem_euclid(360.0) < 180.0 {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    high
+}
+
+/// Moment of the new moon `k` lunations after that of January 6, 2000.
+fn new_moon(k: i64) -> f64 {
+    let k  = k as f64;
+    let t  = k / 1_236.85;
+    let e  = 1.0 - 0.002_516 * t - 0.000_007_4 * t * t;
+    let m  = 2.553_4 + 29.105_356_70 * k - 0.000_001_4 * t * t;
+    let mm = 201.564_3 + 385.816_935_28 * k + 0.010_758_2 * t * t;
+    let f  = 160.710_8 + 390.670_502_84 * k - 0.001_611_8 * t * t;
+    let omega = 124.774_6 - 1.563_755_88 * k + 0.002_067_2 * t * t;
+
+    let correction = -0.407_20 * sin(mm)
+                     + 0.172_41 * e * sin(m)
+                     + 0.016_08 * sin(2.0 * mm)
+                     + 0.010_39 * sin(2.0 * f)
+                     + 0.007_39 * e * sin(mm - m)
+                     - 0.005_14 * e * sin(mm + m)
+                     + 0.002_08 * e * e * sin(2.0 * m)
+                     - 0.001_11 * sin(mm - 2.0 * f)
+                     - 0.000_57 * sin(mm + 2.0 * f)
+                     + 0.000_56 * e * sin(2.0 * mm + m)
+                     - 0.000_42 * sin(3.0 * mm)
+                     + 0.000_42 * e * sin(m + 2.0 * f)
+                     + 0.000_38 * e * sin(m - 2.0 * f)
+                     - 0.000_24 * e * sin(2.0 * mm - m)
+                     - 0.000_17 * sin(omega)
+                     - 0.000_07 * sin(mm + 2.0 * m)
+                     + 0.000_04 * sin(2.0 * mm - 2.0 * f)
+                     + 0.000_04 * sin(3.0 * m)
+                     + 0.000_03 * sin(mm + m - 2.0 * f)
+                     + 0.000_03 * sin(2.0 * mm + 2.0 * f)
+                     - 0.000_03 * sin(mm + m + 2.0 * f)
+                     + 0.000_03 * sin(mm - m + 2.0 * f)
+                     - 0.000_02 * sin(mm - m - 2.0 * f)
+                     - 0.000_02 * sin(3.0 * mm + m)
+                     + 0.000_02 * sin(4.0 * mm);
+
+    let jde    = 2_451_550.097_66 + SYNODIC_MONTH * k + 0.000_154_37 * t * t + correction;
+    let moment = jde - 1_721_424.5;
+    moment - delta_t(moment)
+}
+
+/// Lunation of the mean new moon nearest to the moment.
+fn lunation(moment: f64) -> i64 {
+    ((julian_day(moment) - 2_451_550.1) / SYNODIC_MONTH).round() as i64
+}
+
+/// Moment of the first new moon at or after the given one.
+fn new_moon_after(moment: f64) -> f64 {
+    let mut k = lunation(moment) - 1;
+    while new_moon(k) < moment {
+        k += 1;
+    }
+    new_moon(k)
+}
+
+/// Moment of the last new moon before the given one.
+fn new_moon_before_moment(moment: f64) -> f64 {
+    let mut k = lunation(moment) + 1;
+    while new_moon(k) >= moment {
+        k -= 1;
+    }
+    new_moon(k)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn from_date_finds_new_years_and_leap_months() {
+    use chrono::{ TimeZone, UTC };
+
+    let chinese = |year, month, day| ChineseDate::from_date(UTC.ymd(year, month, day));
+
+    assert_eq!(chinese(2015, 2, 19), ChineseDate { year: 32, month: 1, leap: false, day: 1 });
+    assert_eq!(chinese(2015, 2, 18), ChineseDate { year: 31, month: 12, leap: false, day: 30 });
+    assert_eq!(chinese(2015, 9, 27).to_string(), "month 8 day 15, year of the Goat");
+    assert_eq!(chinese(1945, 11, 12), ChineseDate { year: 22, month: 10, leap: false, day: 8 });
+    assert_eq!(chinese(2024, 2, 10).to_string(), "month 1 day 1, year of the Dragon");
+
+    // 2020 has a leap fourth month, 2023 a leap second one.
+    assert_eq!(chinese(2020, 5, 23), ChineseDate { year: 37, month: 4, leap: true, day: 1 });
+    assert_eq!(chinese(2020, 6, 21), ChineseDate { year: 37, month: 5, leap: false, day: 1 });
+    assert_eq!(chinese(2023, 3, 22), ChineseDate { year: 40, month: 2, leap: true, day: 1 });
+}
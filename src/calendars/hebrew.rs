@@ -0,0 +1,140 @@
+//! The Hebrew calendar.
+//!
+//! Months follow the mean lunar month, and 7 of every 19 years have a
+//! thirteenth month, Adar I, inserted before Adar. Years start with
+//! Tishri, on the day of the mean new moon (the molad) unless the rules of
+//! postponement move it to one of the next days. Months are numbered from
+//! Nisan, as in the Torah, so Tishri is the seventh.
+
+use date::Date;
+use std::fmt;
+use super::fixed;
+
+/// Fixed day of 1 Tishri AM 1, October 7, 3761 BC in the Julian calendar.
+const EPOCH: i64 = -1_373_427;
+
+const NISAN:  u32 = 1;
+const TISHRI: u32 = 7;
+const ADAR:   u32 = 12;
+
+const MONTHS: [(&str, &str); 13] = [
+    ("Nisan", "Ni"), ("Iyyar", "Iy"), ("Sivan", "Si"), ("Tammuz", "Ta"), ("Av", "Av"),
+    ("Elul", "El"), ("Tishri", "Ti"), ("Heshvan", "He"), ("Kislev", "Ki"), ("Tevet", "Te"),
+    ("Shevat", "Sh"), ("Adar", "Ad"), ("Adar II", "A2")
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HebrewDate {
+    pub year:  i64,
+    /// Month from 1 (Nisan) to 12 (Adar), or 13 (Adar II) in leap years.
+    pub month: u32,
+    pub day:   u32
+}
+
+impl HebrewDate {
+    pub fn from_date(date: Date) -> HebrewDate {
+        let date = fixed(date);
+
+        // The estimate by the mean year is at most a year late.
+        let mut year = ((date - EPOCH) as f64 / (35_975_351.0 / 98_496.0)).floor() as i64;
+        while new_year(year + 1) <= date {
+            year += 1;
+        }
+
+        let mut start = new_year(year);
+        for month in (TISHRI..=last_month(year)).chain(NISAN..TISHRI) {
+            let days = i64::from(days_in_month(year, month));
+            if date < start + days {
+                return HebrewDate { year, month, day: (date - start + 1) as u32 };
+            }
+            start += days;
+        }
+
+        unreachable!("the year starting on the day or before contains it")
+    }
+
+    /// Two-letter abbreviation of the month, `A1` for Adar I.
+    pub fn abbreviation(&self) -> &'static str {
+        if self.month == ADAR && is_leap_year(self.year) { "A1" } else { self.month_name().1 }
+    }
+
+    fn month_name(&self) -> (&'static str, &'static str) {
+        MONTHS[self.month as usize - 1]
+    }
+}
+
+impl fmt::Display for HebrewDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let month = if self.month == ADAR && is_leap_year(self.year) {
+            "Adar I"
+        } else {
+            self.month_name().0
+        };
+
+        write!(f, "{} {} {}", self.day, month, self.year)
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(19) < 7
+}
+
+fn last_month(year: i64) -> u32 {
+    if is_leap_year(year) { 13 } else { 12 }
+}
+
+/// Days from the epoch to the molad of Tishri of the year, moved to the
+/// next day if that would put Yom Kippur or Hoshana Rabbah next to the
+/// Sabbath.
+fn elapsed_days(year: i64) -> i64 {
+    let months = (235 * year - 234).div_euclid(19);
+    let parts  = 12_084 + 13_753 * months;
+    let days   = 29 * months + parts.div_euclid(25_920);
+
+    if (3 * (days + 1)).rem_euclid(7) < 3 { days + 1 } else { days }
+}
+
+/// Fixed day of 1 Tishri of the year, postponed further if the year would
+/// otherwise have an impossible length.
+fn new_year(year: i64) -> i64 {
+    let (previous, this, next) = (elapsed_days(year - 1), elapsed_days(year),
+                                  elapsed_days(year + 1));
+    let delay = if next - this == 356 {
+        2
+    } else if this - previous == 382 {
+        1
+    } else {
+        0
+    };
+
+    EPOCH + this + delay
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    let days_in_year = new_year(year + 1) - new_year(year);
+    let short = match month {
+        2 | 4 | 6 | 10 | 13 => true,
+        ADAR                => !is_leap_year(year),
+        8                   => days_in_year % 10 != 5,
+        9                   => days_in_year % 10 == 3,
+        _                   => false
+    };
+
+    if short { 29 } else { 30 }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn from_date_follows_the_rules_of_postponement() {
+    use chrono::{ TimeZone, UTC };
+
+    let hebrew = |year, month, day| HebrewDate::from_date(UTC.ymd(year, month, day)).to_string();
+
+    assert_eq!(hebrew(1945, 11, 12), "7 Kislev 5706");
+    assert_eq!(hebrew(2015, 9, 14), "1 Tishri 5776");
+    assert_eq!(hebrew(2015, 9, 13), "29 Elul 5775");
+    assert_eq!(hebrew(2024, 3, 24), "14 Adar II 5784");
+    assert_eq!(hebrew(2024, 2, 23), "14 Adar I 5784");
+    assert_eq!(hebrew(2024, 4, 23), "15 Nisan 5784");
+}
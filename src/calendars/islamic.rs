@@ -0,0 +1,67 @@
+//! The arithmetic Islamic calendar.
+//!
+//! Years have twelve months of alternately 30 and 29 days, with a day added
+//! to the last month in 11 of every 30 years, counted from 1 Muharram 1 AH,
+//! July 16, 622 in the Julian calendar.
+
+use date::Date;
+use std::fmt;
+use super::fixed;
+
+/// Fixed day of 1 Muharram 1 AH.
+const EPOCH: i64 = 227_015;
+
+const MONTHS: [(&str, &str); 12] = [
+    ("Muharram", "Mu"), ("Safar", "Sa"), ("Rabi al-Awwal", "R1"), ("Rabi al-Thani", "R2"),
+    ("Jumada al-Awwal", "J1"), ("Jumada al-Thani", "J2"), ("Rajab", "Rj"), ("Shaban", "Sh"),
+    ("Ramadan", "Ra"), ("Shawwal", "Sw"), ("Dhu al-Qadah", "DQ"), ("Dhu al-Hijjah", "DH")
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IslamicDate {
+    pub year:  i64,
+    /// Month from 1 (Muharram) to 12 (Dhu al-Hijjah).
+    pub month: u32,
+    pub day:   u32
+}
+
+impl IslamicDate {
+    pub fn from_date(date: Date) -> IslamicDate {
+        let date  = fixed(date);
+        let year  = (30 * (date - EPOCH) + 10_646).div_euclid(10_631);
+        let prior = date - first_day(year, 1);
+        let month = (11 * prior + 330).div_euclid(325);
+
+        IslamicDate { year, month: month as u32, day: (date - first_day(year, month) + 1) as u32 }
+    }
+
+    /// Two-letter abbreviation of the month.
+    pub fn abbreviation(&self) -> &'static str {
+        MONTHS[self.month as usize - 1].1
+    }
+}
+
+impl fmt::Display for IslamicDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, MONTHS[self.month as usize - 1].0, self.year)
+    }
+}
+
+/// Fixed day of the first day of the month.
+fn first_day(year: i64, month: i64) -> i64 {
+    EPOCH + 29 * (month - 1) + (6 * month - 1).div_euclid(11) + 354 * (year - 1)
+          + (3 + 11 * year).div_euclid(30)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn from_date_counts_months_from_the_hijra() {
+    use chrono::{ TimeZone, UTC };
+
+    assert_eq!(IslamicDate::from_date(UTC.ymd(1945, 11, 12)),
+               IslamicDate { year: 1364, month: 12, day: 6 });
+    assert_eq!(IslamicDate::from_date(UTC.ymd(622, 7, 19)),
+               IslamicDate { year: 1, month: 1, day: 1 });
+    assert_eq!(IslamicDate::from_date(UTC.ymd(2024, 3, 11)).to_string(), "1 Ramadan 1445");
+}
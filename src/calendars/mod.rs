@@ -0,0 +1,99 @@
+//! Other calendars, shown alongside the Gregorian one.
+//!
+//! Each module converts Gregorian dates into its calendar. The `System`
+//! names them for the options, and labels the days of an overlay: the day
+//! of the month, or on the first day of a month an abbreviation of the
+//! month's name.
+
+pub mod chinese;
+pub mod hebrew;
+pub mod islamic;
+
+use chrono::Datelike;
+use date::Date;
+use std::fmt;
+use std::str::FromStr;
+
+/// Calendar that can be shown alongside the Gregorian one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum System {
+    Hebrew,
+    /// The arithmetic Islamic calendar. Where months start with the
+    /// sighting of the crescent, they may start a day or two later.
+    Islamic,
+    Chinese
+}
+
+impl System {
+    pub const ALL: [System; 3] = [System::Hebrew, System::Islamic, System::Chinese];
+
+    /// Short label of the day for a cell of the calendar: the day of the
+    /// month, or the abbreviated month on its first day.
+    pub fn label(self, date: Date) -> String {
+        match self {
+            System::Hebrew => {
+                let date = hebrew::HebrewDate::from_date(date);
+                if date.day == 1 { date.abbreviation().to_string() } else { date.day.to_string() }
+            },
+            System::Islamic => {
+                let date = islamic::IslamicDate::from_date(date);
+                if date.day == 1 { date.abbreviation().to_string() } else { date.day.to_string() }
+            },
+            System::Chinese => {
+                let date = chinese::ChineseDate::from_date(date);
+                if date.day == 1 { date.abbreviation().to_string() } else { date.day.to_string() }
+            }
+        }
+    }
+
+    /// The date written out in this calendar, e.g. `1 Tishri 5776`.
+    pub fn name(self, date: Date) -> String {
+        match self {
+            System::Hebrew  => hebrew::HebrewDate::from_date(date).to_string(),
+            System::Islamic => islamic::IslamicDate::from_date(date).to_string(),
+            System::Chinese => chinese::ChineseDate::from_date(date).to_string()
+        }
+    }
+}
+
+impl fmt::Display for System {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            System::Hebrew  => "hebrew",
+            System::Islamic => "islamic",
+            System::Chinese => "chinese"
+        })
+    }
+}
+
+impl FromStr for System {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<System, String> {
+        System::ALL.iter()
+                   .find(|system| system.to_string() == s)
+                   .cloned()
+                   .ok_or_else(|| format!("unknown calendar: '{}' (available: hebrew, islamic, \
+                                           chinese)", s))
+    }
+}
+
+/// Number of the day counted from January 1 of year 1 in the proleptic
+/// Gregorian calendar, which is day 1. The conversions count in these.
+fn fixed(date: Date) -> i64 {
+    i64::from(date.num_days_from_ce())
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn labels_show_the_month_on_its_first_day() {
+    use chrono::{ TimeZone, UTC };
+
+    assert_eq!(System::Hebrew.label(UTC.ymd(2015, 9, 14)), "Ti");
+    assert_eq!(System::Hebrew.label(UTC.ymd(2015, 9, 15)), "2");
+    assert_eq!(System::Islamic.name(UTC.ymd(2015, 10, 15)), "1 Muharram 1437");
+    assert_eq!(System::Chinese.label(UTC.ymd(2015, 2, 19)), "正");
+    assert_eq!("chinese".parse(), Ok(System::Chinese));
+    assert!("mayan".parse::<System>().is_err());
+}
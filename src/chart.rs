@@ -0,0 +1,101 @@
+//! Horizontal bar charts for the terminal.
+//!
+//! Numeric reports are rendered as a labelled bar per row. Bars are drawn
+//! with the Unicode block elements, which have eight steps per column, so
+//! even narrow charts show small differences. Used by the load command.
+
+use width::Width;
+
+/// Blocks filling one to seven eighths of a column.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+const FULL_BLOCK: char = '█';
+
+/// A row of a chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bar {
+    pub label:      String,
+    pub value:      f64,
+    /// Text following the bar, e.g. the value itself.
+    pub annotation: String
+}
+
+impl Bar {
+    pub fn new(label: &str, value: f64, annotation: &str) -> Bar {
+        Bar { label: label.to_string(), value, annotation: annotation.to_string() }
+    }
+}
+
+/// What value a full-width bar stands for.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Scale {
+    /// The largest value of the chart.
+    Max,
+    /// The given value. Larger values are cut off and marked with a `+`.
+    Fixed(f64)
+}
+
+/// Renders a line per bar: the label, padded to the longest one as
+/// measured by `measure`, the bar of up to `width` columns and the
+/// annotation.
+pub fn render(bars: &[Bar], width: usize, scale: Scale, measure: &Width) -> String {
+    let label_width = bars.iter().map(|bar| measure.of(&bar.label)).max().unwrap_or(0);
+    let full = match scale {
+        Scale::Max        => bars.iter().map(|bar| bar.value).fold(0.0, f64::max),
+        Scale::Fixed(max) => max
+    };
+
+    let mut result = String::new();
+
+    for bar in bars {
+        let line = format!("{}{}  {}{} {}",
+                           bar.label,
+                           " ".repeat(label_width - measure.of(&bar.label)),
+                           draw(bar.value, full, width),
+                           if bar.value > full { "+" } else { " " },
+                           bar.annotation);
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Draws a bar of the value, `width` columns for `full`, padded with
+/// spaces to `width`.
+fn draw(value: f64, full: f64, width: usize) -> String {
+    let eighths = if full > 0.0 {
+        ((value.max(0.0) / full * (width * 8) as f64).round() as usize).min(width * 8)
+    } else {
+        0
+    };
+
+    let mut result = FULL_BLOCK.to_string().repeat(eighths / 8);
+    if eighths % 8 > 0 {
+        result.push(PARTIAL_BLOCKS[eighths % 8 - 1]);
+    }
+
+    let drawn = result.chars().count();
+    result + &" ".repeat(width - drawn)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn render_scales_bars_to_largest_value() {
+    let bars = vec![Bar::new("Mon", 4.0, "4"),
+                    Bar::new("Tuesday", 1.0, "1"),
+                    Bar::new("Wed", 0.0, "0")];
+
+    assert_eq!(render(&bars, 4, Scale::Max, &Width::default()), "\
+Mon      ████  4
+Tuesday  █     1
+Wed            0
+");
+}
+
+#[test]
+fn render_draws_fractions_and_cuts_off_large_values() {
+    let bars = vec![Bar::new("a", 0.3, ""), Bar::new("b", 1.5, "")];
+
+    assert_eq!(render(&bars, 2, Scale::Fixed(1.0), &Width::default()), "a  ▋\nb  ██+\n");
+}
@@ -0,0 +1,557 @@
+//! Configuration file.
+//!
+//! The configuration file is written in TOML and lives in
+//! `~/.config/calendar/config.toml`. Settings at the top level are the
+//! defaults of every run, and named presets bundle settings that are only
+//! applied when selected. Both take the options of the command line:
+//!
+//! ```toml
+//! week-start = "sunday"
+//! holidays = "US"
+//!
+//! [preset.work]
+//! months-per-line = 4
+//! holidays = "DE"
+//! events = "~/work/events.txt"
+//! ```
+//!
+//! People whose calendars are planned together each have a profile, which
+//! takes the same settings as a preset (their own event sources, say) plus
+//! the file listing their days off, their yearly vacation budget and the
+//! color their days off are shown in with `--overlay`:
+//!
+//! ```toml
+//! [person.alice]
+//! events = "~/team/alice.txt"
+//! time-off = "~/team/alice-off.txt"
+//! vacation-days = 25
+//! color = "green"
+//! ```
+//!
+//! The `width` table, which has no command line counterpart, fixes the
+//! alignment on terminals that render some characters wider or narrower
+//! than Unicode says:
+//!
+//! ```toml
+//! [width]
+//! measure = "unicode"      # or "chars"
+//! ambiguous-wide = true    # East Asian ambiguous characters are 2 columns
+//! overrides = { "★" = 2 }
+//! ```
+
+use calendar::calendars::System;
+use calendar::date::{ Date, Reckoning, WeekNumbering, Zone };
+use calendar::format::{ Format, TitleFormat };
+use calendar::parse::DateOrder;
+use calendar::parse::{ parse_month, parse_weekday };
+use calendar::style::{ Scale, Style };
+use calendar::sun::Location;
+use calendar::svg::PageSize;
+use calendar::width::Width;
+use chrono::Weekday;
+use std::collections::{ BTreeMap, HashMap };
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{ Path, PathBuf };
+use toml::Value;
+use toml::value::Table;
+
+/// Settings that can be given on the command line or in the configuration
+/// file. Unset settings fall back to the next layer and eventually to the
+/// built-in defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub months_per_line: Option<usize>,
+    pub banner:          Option<bool>,
+    pub week_start:      Option<Weekday>,
+    pub week_numbering:  Option<WeekNumbering>,
+    pub year_start:      Option<u32>,
+    pub workweek:        Option<bool>,
+    pub flip:            Option<bool>,
+    pub vertical:        Option<bool>,
+    pub compact:         Option<bool>,
+    pub julian:          Option<bool>,
+    pub reform:          Option<Reckoning>,
+    pub overlay:         Option<System>,
+    pub moon:            Option<bool>,
+    pub title_format:    Option<TitleFormat>,
+    pub weekday_header:  Option<bool>,
+    pub location:        Option<Location>,
+    pub date_order:      Option<DateOrder>,
+    pub day_width:       Option<usize>,
+    pub month_gap:       Option<usize>,
+    pub weekends:        Option<Style>,
+    pub holidays:        Option<String>,
+    pub holiday_style:   Option<Style>,
+    pub heatmap:         Option<bool>,
+    pub heat_scale:      Option<Scale>,
+    pub events:          Option<PathBuf>,
+    pub ical:            Option<PathBuf>,
+    pub ical_url:        Option<String>,
+    pub legend:          Option<bool>,
+    pub ages:            Option<bool>,
+    pub footnotes:       Option<bool>,
+    pub format:          Option<Format>,
+    pub page_size:       Option<PageSize>,
+    pub width:           Option<Width>,
+    pub timezone:        Option<Zone>,
+    /// The date taken for today instead of the actual one, for output that
+    /// doesn't change from day to day. Only given on the command line.
+    pub today:           Option<Date>
+}
+
+impl Settings {
+    /// Returns these settings with the unset ones taken from `fallback`.
+    pub fn or(self, fallback: Settings) -> Settings {
+        Settings {
+            months_per_line: self.months_per_line.or(fallback.months_per_line),
+            banner:          self.banner.or(fallback.banner),
+            week_start:      self.week_start.or(fallback.week_start),
+            week_numbering:  self.week_numbering.or(fallback.week_numbering),
+            year_start:      self.year_start.or(fallback.year_start),
+            workweek:        self.workweek.or(fallback.workweek),
+            flip:            self.flip.or(fallback.flip),
+            vertical:        self.vertical.or(fallback.vertical),
+            compact:         self.compact.or(fallback.compact),
+            julian:          self.julian.or(fallback.julian),
+            reform:          self.reform.or(fallback.reform),
+            overlay:         self.overlay.or(fallback.overlay),
+            moon:            self.moon.or(fallback.moon),
+            title_format:    self.title_format.or(fallback.title_format),
+            weekday_header:  self.weekday_header.or(fallback.weekday_header),
+            location:        self.location.or(fallback.location),
+            date_order:      self.date_order.or(fallback.date_order),
+            day_width:       self.day_width.or(fallback.day_width),
+            month_gap:       self.month_gap.or(fallback.month_gap),
+            weekends:        self.weekends.or(fallback.weekends),
+            holidays:        self.holidays.or(fallback.holidays),
+            holiday_style:   self.holiday_style.or(fallback.holiday_style),
+            heatmap:         self.heatmap.or(fallback.heatmap),
+            heat_scale:      self.heat_scale.or(fallback.heat_scale),
+            events:          self.events.or(fallback.events),
+            ical:            self.ical.or(fallback.ical),
+            ical_url:        self.ical_url.or(fallback.ical_url),
+            legend:          self.legend.or(fallback.legend),
+            ages:            self.ages.or(fallback.ages),
+            footnotes:       self.footnotes.or(fallback.footnotes),
+            format:          self.format.or(fallback.format),
+            page_size:       self.page_size.or(fallback.page_size),
+            width:           self.width.or(fallback.width),
+            timezone:        self.timezone.or(fallback.timezone),
+            today:           self.today.or(fallback.today)
+        }
+    }
+
+    fn from_table(table: &Table) -> Result<Settings, String> {
+        let mut result = Settings::default();
+
+        for (key, value) in table {
+            match &**key {
+                "months-per-line" => result.months_per_line = Some(integer(key, value)?),
+                "banner"          => result.banner          = Some(boolean(key, value)?),
+                "week-start"      => result.week_start      = Some(weekday(key, value)?),
+                "week-numbering"  => result.week_numbering  = Some(string(key, value)?.parse()?),
+                "year-start"      => result.year_start      = Some(month(key, value)?),
+                "workweek"        => result.workweek        = Some(boolean(key, value)?),
+                "flip"            => result.flip            = Some(boolean(key, value)?),
+                "vertical"        => result.vertical        = Some(boolean(key, value)?),
+                "compact"         => result.compact         = Some(boolean(key, value)?),
+                "julian"          => result.julian          = Some(boolean(key, value)?),
+                "reform"          => result.reform          = Some(string(key, value)?.parse()?),
+                "calendar-overlay" => result.overlay        = Some(string(key, value)?.parse()?),
+                "moon"            => result.moon            = Some(boolean(key, value)?),
+                "title-format"    => result.title_format    = Some(string(key, value)?.parse()?),
+                "weekday-header"  => result.weekday_header  = Some(boolean(key, value)?),
+                "location"        => result.location        = Some(string(key, value)?.parse()?),
+                "date-order"      => result.date_order      = Some(string(key, value)?.parse()?),
+                "day-width"       => result.day_width       = Some(day_width(key, value)?),
+                "month-gap"       => result.month_gap       = Some(integer(key, value)?),
+                "weekends"        => result.weekends        = Some(style(key, value)?),
+                "holidays"        => result.holidays        = Some(string(key, value)?.to_string()),
+                "holiday-style"   => result.holiday_style   = Some(style(key, value)?),
+                "heatmap"         => result.heatmap         = Some(boolean(key, value)?),
+                "heat-scale"      => result.heat_scale      = Some(string(key, value)?.parse()?),
+                "events"          => result.events          = Some(path(key, value)?),
+                "ical"            => result.ical            = Some(path(key, value)?),
+                "ical-url"        => result.ical_url        = Some(string(key, value)?.to_string()),
+                "legend"          => result.legend          = Some(boolean(key, value)?),
+                "ages"            => result.ages            = Some(boolean(key, value)?),
+                "footnotes"       => result.footnotes       = Some(boolean(key, value)?),
+                "format"          => result.format          = Some(string(key, value)?.parse()?),
+                "page-size"       => result.page_size       = Some(string(key, value)?.parse()?),
+                "width"           => result.width           = Some(width(key, value)?),
+                "timezone"        => result.timezone        = Some(string(key, value)?.parse()?),
+                _                 => return Err(format!("unknown setting '{}'", key))
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Profile of a person.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Person {
+    /// Settings applied when the person is selected, like a preset.
+    pub settings:      Settings,
+    /// Events file listing the person's days off.
+    pub time_off:      Option<PathBuf>,
+    /// Days off the person has per year.
+    pub vacation_days: Option<u32>,
+    /// Style of the person's days off in an overlay.
+    pub color:         Option<Style>
+}
+
+impl Person {
+    fn from_table(table: &Table) -> Result<Person, String> {
+        let mut settings = table.clone();
+        let mut result   = Person::default();
+
+        if let Some(value) = settings.remove("time-off") {
+            result.time_off = Some(path("time-off", &value)?);
+        }
+        if let Some(value) = settings.remove("vacation-days") {
+            result.vacation_days = Some(days("vacation-days", &value)?);
+        }
+        if let Some(value) = settings.remove("color") {
+            result.color = Some(style("color", &value)?);
+        }
+
+        result.settings = Settings::from_table(&settings)?;
+        Ok(result)
+    }
+}
+
+/// Contents of the configuration file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// Settings at the top level, applied unless overridden.
+    pub defaults: Settings,
+    pub presets:  HashMap<String, Settings>,
+    pub people:   HashMap<String, Person>
+}
+
+impl Config {
+    /// Returns the settings of the preset with the given name.
+    pub fn preset(&self, name: &str) -> Result<Settings, String> {
+        self.presets.get(name)
+                    .cloned()
+                    .ok_or_else(|| format!("unknown preset '{}'", name))
+    }
+
+    /// Returns the profile of the person with the given name.
+    pub fn person(&self, name: &str) -> Result<Person, String> {
+        self.people.get(name)
+                   .cloned()
+                   .ok_or_else(|| format!("unknown person '{}'", name))
+    }
+}
+
+/// Parses the contents of a configuration file.
+pub fn parse(input: &str) -> Result<Config, String> {
+    let root = input.parse::<Value>().map_err(|e| e.to_string())?;
+    let mut result = Config::default();
+
+    let mut defaults = root.as_table().cloned().unwrap_or_default();
+    defaults.remove("preset");
+    defaults.remove("person");
+    result.defaults = Settings::from_table(&defaults)?;
+
+    if let Some(presets) = root.get("preset") {
+        let presets = presets.as_table()
+                             .ok_or("'preset' must be a table of presets")?;
+
+        for (name, preset) in presets {
+            let settings = preset.as_table()
+                                 .ok_or_else(|| format!("preset '{}' must be a table", name))
+                                 .and_then(Settings::from_table)
+                                 .map_err(|e| format!("preset '{}': {}", name, e))?;
+            result.presets.insert(name.clone(), settings);
+        }
+    }
+
+    if let Some(people) = root.get("person") {
+        let people = people.as_table()
+                           .ok_or("'person' must be a table of people")?;
+
+        for (name, person) in people {
+            let person = person.as_table()
+                               .ok_or_else(|| format!("person '{}' must be a table", name))
+                               .and_then(Person::from_table)
+                               .map_err(|e| format!("person '{}': {}", name, e))?;
+            result.people.insert(name.clone(), person);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads and parses a configuration file.
+pub fn load(path: &Path) -> Result<Config, String> {
+    let mut input = String::new();
+
+    File::open(path).and_then(|mut file| file.read_to_string(&mut input))
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    parse(&input).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Where the configuration comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The file at `default_path()`, if it exists.
+    Default,
+    /// The given file, which must exist.
+    File(PathBuf),
+    /// No configuration file at all, for predictable behavior in scripts.
+    Disabled
+}
+
+impl Source {
+    pub fn load(&self) -> Result<Config, String> {
+        match *self {
+            Source::Default => match default_path() {
+                Some(ref path) if path.exists() => load(path),
+                _                               => Ok(Config::default())
+            },
+            Source::File(ref path) => load(path),
+            Source::Disabled       => Ok(Config::default())
+        }
+    }
+}
+
+/// Returns the directory of the configuration file, honoring
+/// `$XDG_CONFIG_HOME`.
+pub fn dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+                                            .or_else(|| home().map(|home| home.join(".config")));
+
+    dir.map(|dir| dir.join("calendar"))
+}
+
+/// Returns the path of the configuration file.
+pub fn default_path() -> Option<PathBuf> {
+    dir().map(|dir| dir.join("config.toml"))
+}
+
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+fn string<'a>(key: &str, value: &'a Value) -> Result<&'a str, String> {
+    value.as_str().ok_or_else(|| format!("'{}' must be a string", key))
+}
+
+fn integer(key: &str, value: &Value) -> Result<usize, String> {
+    match value.as_integer() {
+        Some(num) if num > 0 => Ok(num as usize),
+        _ => Err(format!("'{}' must be a positive integer", key))
+    }
+}
+
+fn day_width(key: &str, value: &Value) -> Result<usize, String> {
+    match integer(key, value)? {
+        width if width >= 2 => Ok(width),
+        _ => Err(format!("'{}' must be at least 2", key))
+    }
+}
+
+/// Reads a number of days in a year, which can be none.
+fn days(key: &str, value: &Value) -> Result<u32, String> {
+    match value.as_integer() {
+        Some(days) if (0..=366).contains(&days) => Ok(days as u32),
+        _ => Err(format!("'{}' must be a number of days from 0 to 366", key))
+    }
+}
+
+fn boolean(key: &str, value: &Value) -> Result<bool, String> {
+    value.as_bool().ok_or_else(|| format!("'{}' must be true or false", key))
+}
+
+/// Reads a month, given by its number or name.
+fn month(key: &str, value: &Value) -> Result<u32, String> {
+    match (value.as_integer(), value.as_str()) {
+        (Some(month), _) if (1..=12).contains(&month) => Ok(month as u32),
+        (None, Some(name)) => parse_month(name),
+        _ => Err(format!("'{}' must be a month from 1 to 12 or its name", key))
+    }
+}
+
+fn weekday(key: &str, value: &Value) -> Result<Weekday, String> {
+    parse_weekday(string(key, value)?)
+}
+
+fn style(key: &str, value: &Value) -> Result<Style, String> {
+    string(key, value)?.parse()
+}
+
+fn width(key: &str, value: &Value) -> Result<Width, String> {
+    let table = value.as_table().ok_or_else(|| format!("'{}' must be a table", key))?;
+    let mut result = Width::default();
+
+    for (key, value) in table {
+        match &**key {
+            "measure"        => result.measure        = string(key, value)?.parse()?,
+            "ambiguous-wide" => result.ambiguous_wide = boolean(key, value)?,
+            "overrides"      => result.overrides      = overrides(key, value)?,
+            _                => return Err(format!("unknown width setting '{}'", key))
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads the widths of single characters.
+fn overrides(key: &str, value: &Value) -> Result<BTreeMap<char, usize>, String> {
+    let table = value.as_table().ok_or_else(|| format!("'{}' must be a table", key))?;
+    let mut result = BTreeMap::new();
+
+    for (glyph, width) in table {
+        let mut chars = glyph.chars();
+
+        match (chars.next(), chars.next(), width.as_integer()) {
+            (Some(c), None, Some(width)) if width >= 0 => { result.insert(c, width as usize); },
+            (Some(_), None, _) => return Err(format!("width of '{}' must be a non-negative \
+                                                      integer", glyph)),
+            _                  => return Err(format!("'{}' in '{}' is not a single character",
+                                                     glyph, key))
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads a path, expanding a leading `~` to the home directory.
+fn path(key: &str, value: &Value) -> Result<PathBuf, String> {
+    let value = string(key, value)?;
+
+    match (value.strip_prefix("~/"), home()) {
+        (Some(rest), Some(home)) => Ok(home.join(rest)),
+        _                        => Ok(PathBuf::from(value))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn parse_reads_presets() {
+    let input = "
+        [preset.work]
+        months-per-line = 4
+        holidays = \"DE\"
+        events = \"/work/events.txt\"
+
+        [preset.family]
+        weekends = \"dim\"
+        legend = true
+    ";
+
+    let config = parse(input).unwrap();
+
+    assert_eq!(config.preset("work").unwrap(), Settings {
+        months_per_line: Some(4),
+        holidays:        Some("DE".to_string()),
+        events:          Some(PathBuf::from("/work/events.txt")),
+        ..Settings::default()
+    });
+
+    assert_eq!(config.preset("family").unwrap(), Settings {
+        weekends: Some(Style::Dim),
+        legend:   Some(true),
+        ..Settings::default()
+    });
+
+    assert!(config.preset("school").is_err());
+}
+
+#[test]
+fn parse_reads_defaults_at_top_level() {
+    let config = parse("week-start = \"sunday\"\n[preset.work]\nholidays = \"DE\"").unwrap();
+
+    assert_eq!(config.defaults, Settings {
+        week_start: Some(Weekday::Sun),
+        ..Settings::default()
+    });
+    assert_eq!(config.preset("work").unwrap().week_start, None);
+    assert_eq!(parse("foo = 1"), Err("unknown setting 'foo'".to_string()));
+}
+
+#[test]
+fn parse_reads_people() {
+    let input = "
+        [person.alice]
+        events = \"/team/alice.txt\"
+        time-off = \"/team/alice-off.txt\"
+        vacation-days = 25
+        color = \"green\"
+
+        [person.bob]
+        holidays = \"DE\"
+    ";
+
+    let config = parse(input).unwrap();
+
+    assert_eq!(config.person("alice").unwrap(), Person {
+        settings:      Settings { events: Some(PathBuf::from("/team/alice.txt")),
+                                  ..Settings::default() },
+        time_off:      Some(PathBuf::from("/team/alice-off.txt")),
+        vacation_days: Some(25),
+        color:         Some(Style::Green)
+    });
+    assert_eq!(config.person("bob").unwrap().settings.holidays, Some("DE".to_string()));
+    assert!(config.person("carol").is_err());
+    assert!(parse("[person.alice]\ncolor = \"pink\"").unwrap_err()
+                                                   .starts_with("person 'alice': "));
+
+    let vacation_days = |days: i64| {
+        parse(&format!("[person.alice]\nvacation-days = {}", days))
+            .and_then(|config| config.person("alice"))
+            .map(|person| person.vacation_days)
+    };
+    assert_eq!(vacation_days(0), Ok(Some(0)));
+    assert!(vacation_days(-1).is_err());
+    assert!(vacation_days(4_294_967_321).is_err());
+}
+
+#[test]
+fn parse_reads_width() {
+    use calendar::width::Measure;
+
+    let config = parse("[width]\nmeasure = \"chars\"\noverrides = { \"★\" = 2 }").unwrap();
+    let width  = config.defaults.width.unwrap();
+
+    assert_eq!(width.measure, Measure::Chars);
+    assert_eq!(width.overrides.get(&'★'), Some(&2));
+    assert!(parse("[width]\noverrides = { \"ab\" = 2 }").is_err());
+    assert!(parse("[width]\nmeasure = \"pixels\"").is_err());
+}
+
+#[test]
+fn parse_rejects_invalid_settings() {
+    assert_eq!(parse("[preset.work]\nfoo = 1"),
+               Err("preset 'work': unknown setting 'foo'".to_string()));
+    assert_eq!(parse("[preset.work]\nmonths-per-line = 0"),
+               Err("preset 'work': 'months-per-line' must be a positive integer".to_string()));
+}
+
+#[test]
+fn disabled_source_loads_empty_config() {
+    assert_eq!(Source::Disabled.load(), Ok(Config::default()));
+    assert!(Source::File(PathBuf::from("/nonexistent/config.toml")).load()
+                                                                    .is_err());
+}
+
+#[test]
+fn settings_fall_back_to_unset_fields() {
+    let cli    = Settings { months_per_line: Some(2), ..Settings::default() };
+    let preset = Settings { months_per_line: Some(4),
+                            holidays:        Some("CZ".to_string()),
+                            ..Settings::default() };
+
+    let merged = cli.or(preset);
+
+    assert_eq!(merged.months_per_line, Some(2));
+    assert_eq!(merged.holidays,        Some("CZ".to_string()));
+    assert_eq!(merged.weekends,        None);
+}
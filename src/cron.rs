@@ -0,0 +1,186 @@
+//! Cron schedules.
+//!
+//! A schedule has the five fields of a crontab line: minute, hour, day of
+//! the month, month and day of the week. A field is `*`, a number, a range
+//! (`1-5`), a step (`*/15`, `1-10/2`) or a comma-separated list of them.
+//! Months and days of the week can also be given by their English
+//! abbreviations (`JAN`, `MON`); both 0 and 7 are Sunday. As in cron, if
+//! both the day of the month and of the week are restricted, a day matches
+//! if either of them does.
+
+use chrono::Datelike;
+use date::{ add_months, Date, DateRange };
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Years searched for occurrences, so that schedules that never match,
+/// e.g. February 30, don't search forever.
+const SEARCH_YEARS: i32 = 400;
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT",
+                            "NOV", "DEC"];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    minutes:  BTreeSet<u32>,
+    hours:    BTreeSet<u32>,
+    days:     BTreeSet<u32>,
+    months:   BTreeSet<u32>,
+    /// Days of the week, with Sunday as 0.
+    weekdays: BTreeSet<u32>,
+    /// Whether any day of the month matches, i.e. the field is `*`.
+    any_day:     bool,
+    /// Whether any day of the week matches.
+    any_weekday: bool
+}
+
+/// Time of an occurrence of a schedule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Occurrence {
+    pub date:   Date,
+    pub hour:   u32,
+    pub minute: u32
+}
+
+impl Schedule {
+    /// Whether the schedule runs on the day.
+    pub fn matches(&self, date: Date) -> bool {
+        let day     = self.days.contains(&date.day());
+        let weekday = self.weekdays.contains(&date.weekday().num_days_from_sunday());
+
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (true,  true)  => true,
+            (true,  false) => weekday,
+            (false, true)  => day,
+            (false, false) => day || weekday
+        };
+
+        day_matches && self.months.contains(&date.month())
+    }
+
+    /// Returns the first `count` occurrences on or after `start`. Fewer if
+    /// there are no more within `SEARCH_YEARS`.
+    pub fn first(&self, start: Date, count: usize) -> Vec<Occurrence> {
+        DateRange::new(start, add_months(start, 12 * SEARCH_YEARS))
+            .filter(|&date| self.matches(date))
+            .flat_map(|date| {
+                self.hours.iter().flat_map(move |&hour| {
+                    self.minutes.iter().map(move |&minute| Occurrence { date, hour, minute })
+                })
+            })
+            .take(count)
+            .collect()
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// Parses the five fields of a crontab line, e.g. `0 9 * * MON-FRI`.
+    fn from_str(s: &str) -> Result<Schedule, String> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        if fields.len() != 5 {
+            return Err(format!("expected five fields in cron schedule: '{}'", s));
+        }
+
+        let weekdays = parse_field(fields[4], 0, 7, &WEEKDAYS)?;
+
+        Ok(Schedule {
+            minutes:     parse_field(fields[0], 0, 59, &[])?,
+            hours:       parse_field(fields[1], 0, 23, &[])?,
+            days:        parse_field(fields[2], 1, 31, &[])?,
+            months:      parse_field(fields[3], 1, 12, &MONTHS)?,
+            weekdays:    weekdays.into_iter().map(|weekday| weekday % 7).collect(),
+            any_day:     fields[2] == "*",
+            any_weekday: fields[4] == "*"
+        })
+    }
+}
+
+/// Parses a field of values from `min` to `max`. `names` are the names of
+/// the values, starting with `min`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<BTreeSet<u32>, String> {
+    let value = |input: &str| {
+        let number = names.iter()
+                          .position(|name| name.eq_ignore_ascii_case(input))
+                          .map(|index| index as u32 + min)
+                          .or_else(|| input.parse().ok());
+
+        match number {
+            Some(number) if number >= min && number <= max => Ok(number),
+            _ => Err(format!("invalid value in cron field '{}': '{}'", field, input))
+        }
+    };
+
+    let mut result = BTreeSet::new();
+
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(index) => (&part[..index], Some(&part[index + 1..])),
+            None        => (part, None)
+        };
+
+        let (first, last) = match range.find('-') {
+            _ if range == "*" => (min, max),
+            Some(index)       => (value(&range[..index])?, value(&range[index + 1..])?),
+            None if step.is_some() => (value(range)?, max),
+            None              => { let number = value(range)?; (number, number) }
+        };
+        let step = match step.map(str::parse::<u32>) {
+            None                          => 1,
+            Some(Ok(step)) if step > 0    => step,
+            Some(_)                       => return Err(format!("invalid step in cron field \
+                                                                 '{}'", field))
+        };
+
+        if first > last {
+            return Err(format!("invalid range in cron field '{}'", field));
+        }
+
+        result.extend((first..=last).step_by(step as usize));
+    }
+
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+
+#[test]
+fn schedule_can_be_parsed() {
+    let schedule = "*/20 9-10 * JAN-MAR mon,FRI".parse::<Schedule>().unwrap();
+
+    assert_eq!(schedule.minutes.iter().cloned().collect::<Vec<_>>(), [0, 20, 40]);
+    assert_eq!(schedule.hours.iter().cloned().collect::<Vec<_>>(), [9, 10]);
+    assert_eq!(schedule.months.iter().cloned().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(schedule.weekdays.iter().cloned().collect::<Vec<_>>(), [1, 5]);
+
+    assert!("* * * *".parse::<Schedule>().is_err());
+    assert!("60 * * * *".parse::<Schedule>().is_err());
+    assert!("* * * * MON-XYZ".parse::<Schedule>().is_err());
+    assert!("*/0 * * * *".parse::<Schedule>().is_err());
+}
+
+#[test]
+fn first_lists_occurrences_in_order() {
+    let schedule = "30 8,17 * * 1-5".parse::<Schedule>().unwrap();
+    let times    = schedule.first(UTC.ymd(2015, 3, 6), 3);
+
+    assert_eq!(times, [Occurrence { date: UTC.ymd(2015, 3, 6), hour:  8, minute: 30 },
+                       Occurrence { date: UTC.ymd(2015, 3, 6), hour: 17, minute: 30 },
+                       Occurrence { date: UTC.ymd(2015, 3, 9), hour:  8, minute: 30 }]);
+}
+
+#[test]
+fn day_of_month_or_week_matches_if_both_are_restricted() {
+    let schedule = "0 0 13 * FRI".parse::<Schedule>().unwrap();
+
+    assert!(schedule.matches(UTC.ymd(2015, 3, 13)));
+    assert!(schedule.matches(UTC.ymd(2015, 3, 6)));
+    assert!(schedule.matches(UTC.ymd(2015, 4, 13)));
+    assert!(!schedule.matches(UTC.ymd(2015, 4, 14)));
+    assert!("0 0 30 2 *".parse::<Schedule>().unwrap().first(UTC.ymd(2015, 1, 1), 1).is_empty());
+}
@@ -1,49 +1,617 @@
 //! Utilities for working with dates.
 
-use chrono::{ Datelike, TimeZone, UTC };
+use chrono::{ DateTime, Datelike, FixedOffset, Local, NaiveDate, Offset, TimeZone, UTC, Weekday };
+use std::iter::StepBy;
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
 
-/// Date
+/// A calendar day. Times of day and time zones don't matter to the calendar,
+/// so all dates are in UTC. Only which day it is today depends on the time
+/// zone, see `today`.
+///
+/// Dates are in the proleptic Gregorian calendar, i.e. the Gregorian leap
+/// rules apply to years before its introduction in 1582 as well, and years
+/// are numbered astronomically: year 0 is 1 BC, year -44 is 45 BC.
 pub type Date = ::chrono::Date<UTC>;
 
-/// Which week in the year the date belongs to. Week number start at zero.
-fn week_number(date: &Date) -> u32 {
-    date.isoweekdate().1 - 1
+/// First year the calendar supports.
+pub const MIN_YEAR: i32 = -9999;
+
+/// Last year the calendar supports.
+pub const MAX_YEAR: i32 = 9999;
+
+/// Returns the year if the calendar supports it, and an error otherwise.
+pub fn check_year(year: i32) -> Result<i32, String> {
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return Err(format!("year {} is out of range (supported are years {} to {})", year,
+                           MIN_YEAR, MAX_YEAR));
+    }
+
+    Ok(year)
+}
+
+/// More days than there are between any two supported dates.
+const SUPPORTED_DAYS: u64 = (MAX_YEAR - MIN_YEAR + 1) as u64 * 366;
+
+/// Returns an error if moving `count` days is sure to leave the supported
+/// years, before the days are counted one by one.
+fn check_days(count: i64) -> Result<(), String> {
+    if count.unsigned_abs() > SUPPORTED_DAYS {
+        return Err(format!("{} days is out of range (supported are years {} to {})", count,
+                           MIN_YEAR, MAX_YEAR));
+    }
+
+    Ok(())
+}
+
+/// Returns the date `days` days after the date, or before it if `days` is
+/// negative, and an error if that is outside the supported years.
+pub fn add_days(date: Date, days: i64) -> Result<Date, String> {
+    check_days(days)?;
+
+    let result = date + ::chrono::Duration::days(days);
+    check_year(result.year())?;
+    Ok(result)
+}
+
+/// Time zone in which the current day is determined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Zone {
+    Utc,
+    /// The time zone of the system.
+    Local,
+    /// A fixed offset from UTC, in seconds east.
+    Fixed(i32)
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    /// Parses `utc`, `local` or an offset like `+02:00`, `-0530` or `+9`.
+    fn from_str(s: &str) -> Result<Zone, String> {
+        let error = || format!("invalid time zone: '{}' (expected utc, local or an offset \
+                                like +02:00)", s);
+
+        match &*s.to_lowercase() {
+            "utc" | "z" => return Ok(Zone::Utc),
+            "local"     => return Ok(Zone::Local),
+            _           => ()
+        }
+
+        let sign = match s.chars().next() {
+            Some('+') => 1,
+            Some('-') => -1,
+            _         => return Err(error())
+        };
+
+        let digits = s[1..].replace(':', "");
+        if digits.is_empty() || digits.len() > 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error());
+        }
+
+        let (hours, minutes) = if digits.len() <= 2 {
+            (&*digits, "0")
+        } else {
+            digits.split_at(digits.len() - 2)
+        };
+        let hours   = hours.parse::<i32>().map_err(|_| error())?;
+        let minutes = minutes.parse::<i32>().map_err(|_| error())?;
+
+        if hours > 23 || minutes > 59 {
+            return Err(error());
+        }
+
+        Ok(Zone::Fixed(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+/// Returns the current day in the time zone.
+pub fn today(zone: Zone) -> Date {
+    day_at(UTC::now(), zone)
+}
+
+/// Returns the day it is at the instant in the time zone.
+pub fn day_at(instant: DateTime<UTC>, zone: Zone) -> Date {
+    let day = match zone {
+        Zone::Utc          => instant.date().naive_utc(),
+        Zone::Local        => instant.with_timezone(&Local).date().naive_local(),
+        Zone::Fixed(east)  => instant.with_timezone(&FixedOffset::east(east)).date().naive_local()
+    };
+
+    UTC.from_utc_date(&day)
+}
+
+/// Offset of the time zone from UTC on the day, in seconds east, as it is at
+/// noon UTC. Differs from that of the day before where clocks change, for
+/// daylight saving time in the local time zone.
+pub fn utc_offset(date: Date, zone: Zone) -> i32 {
+    match zone {
+        Zone::Utc         => 0,
+        Zone::Local       => {
+            let noon = date.and_hms(12, 0, 0).with_timezone(&Local);
+            noon.offset().local_minus_utc().num_seconds() as i32
+        },
+        Zone::Fixed(east) => east
+    }
+}
+
+/// The ISO week the date belongs to: its ISO year, which differs from the
+/// calendar year for some days around New Year, and week number from 1.
+pub fn iso_week(date: &Date) -> (i32, u32) {
+    let (year, week, _) = date.isoweekdate();
+    (year, week)
 }
 
+/// Convention by which the weeks of a year are numbered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WeekNumbering {
+    /// ISO 8601: weeks start on Monday and week 1 is the one containing the
+    /// first Thursday, so that days around New Year can belong to the week
+    /// of the other year.
+    Iso,
+    /// Weeks start on Sunday and week 1 is the one containing January 1,
+    /// cut off at the end of the year.
+    Us,
+    /// Weeks start on Monday and week 1 is the first full week, so that
+    /// days before its Monday are in week 0.
+    Simple
+}
+
+impl WeekNumbering {
+    /// The day weeks start on.
+    pub fn first_day(self) -> Weekday {
+        match self {
+            WeekNumbering::Us => Weekday::Sun,
+            _                 => Weekday::Mon
+        }
+    }
+}
+
+impl FromStr for WeekNumbering {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WeekNumbering, String> {
+        match s {
+            "iso"    => Ok(WeekNumbering::Iso),
+            "us"     => Ok(WeekNumbering::Us),
+            "simple" => Ok(WeekNumbering::Simple),
+            _        => Err(format!("unknown week numbering: '{}' (available: iso, us, \
+                                     simple)", s))
+        }
+    }
+}
+
+/// The week the date belongs to under the numbering: the year it is
+/// counted in and its number.
+pub fn week_number(date: &Date, numbering: WeekNumbering) -> (i32, u32) {
+    let day = date.ordinal0();
+
+    match numbering {
+        WeekNumbering::Iso    => iso_week(date),
+        WeekNumbering::Us     => {
+            let first = UTC.ymd(date.year(), 1, 1).weekday().num_days_from_sunday();
+            (date.year(), (day + first) / 7 + 1)
+        },
+        WeekNumbering::Simple => (date.year(), (day + 7 - weekday(*date)) / 7)
+    }
+}
+
+/// Which quarter of the year the date belongs to, starting at zero.
+fn quarter(date: &Date) -> u32 {
+    date.month0() / 3
+}
+
+/// Day of the week of the date, starting with Monday as 0.
 pub fn weekday(date: Date) -> u32 {
     date.weekday().num_days_from_monday()
 }
 
+/// Returns the first day of the month `months` months after the month of
+/// the date. `months` may be negative.
+pub fn add_months(date: Date, months: i32) -> Date {
+    let index = date.year() * 12 + date.month0() as i32 + months;
+    UTC.ymd(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)
+}
+
+/// Returns the `n`-th (starting at 1) given day of the week in the month,
+/// e.g. the fourth Thursday of November, if the month has one.
+pub fn nth_weekday(year: i32, month: u32, day: Weekday, n: u32) -> Option<Date> {
+    let first = UTC.ymd_opt(year, month, 1).single()?;
+    months_from(first, 1).weekdays(day).nth(n.checked_sub(1)? as usize)
+}
+
+/// Returns the last given day of the week in the month, e.g. the last Monday
+/// of May. Panics if the month is invalid.
+pub fn last_weekday(year: i32, month: u32, day: Weekday) -> Date {
+    months_from(UTC.ymd(year, month, 1), 1).weekdays(day)
+                                           .next_back()
+                                           .expect("every month has each day of the week")
+}
+
+/// Returns the months from `before` months before the month of the date to
+/// `after` months after it, or an error if they go past the supported
+/// years.
+pub fn months_around(date: Date, before: u32, after: u32) -> Result<DateRange, String> {
+    // Months since year 0, which even the largest counts can't overflow.
+    let month = date.year() as i64 * 12 + date.month0() as i64;
+    check_year((month - before as i64).div_euclid(12) as i32)?;
+    check_year((month + after as i64).div_euclid(12) as i32)?;
+
+    Ok(DateRange::new(add_months(date, -(before as i32)), add_months(date, after as i32 + 1)))
+}
+
+/// Returns `count` months, starting with the month of the date.
+pub fn months_from(date: Date, count: u32) -> DateRange {
+    DateRange::new(add_months(date, 0), add_months(date, count as i32))
+}
+
+/// Julian day number of the date, i.e. the number of days since January 1,
+/// 4713 BC in the proleptic Julian calendar.
+pub fn julian_day(date: Date) -> i32 {
+    date.num_days_from_ce() + 1_721_425
+}
+
+/// Year, month and day of the date in the Julian calendar.
+pub fn julian_ymd(date: Date) -> (i32, u32, u32) {
+    let c = julian_day(date) + 32_082;
+    let d = (4 * c + 3).div_euclid(1461);
+    let e = c - (1461 * d).div_euclid(4);
+    let m = (5 * e + 2) / 153;
+
+    (d - 4800 + m / 10, (m + 3 - 12 * (m / 10)) as u32, (e - (153 * m + 2) / 5 + 1) as u32)
+}
+
+/// Returns the date with the given year, month and day in the Julian
+/// calendar, if there is one.
+pub fn from_julian_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+    // Any year with the same leap rule will do to check the day.
+    let leap = if year.rem_euclid(4) == 0 { 2000 } else { 2001 };
+    UTC.ymd_opt(leap, month, day).single()?;
+
+    let a = (14 - month as i32) / 12;
+    let y = year + 4800 - a;
+    let m = month as i32 + 12 * a - 3;
+    let jdn = day as i32 + (153 * m + 2) / 5 + 365 * y + y.div_euclid(4) - 32_083;
+
+    NaiveDate::from_num_days_from_ce_opt(jdn - 1_721_425).map(|date| UTC.from_utc_date(&date))
+}
+
+/// Number of the day in its year in the Julian calendar, starting at 1.
+fn julian_ordinal(date: Date) -> u32 {
+    let (year, month, day) = julian_ymd(date);
+    let february = if year.rem_euclid(4) == 0 { 29 } else { 28 };
+
+    [31, february, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][..month as usize - 1]
+        .iter().sum::<u32>() + day
+}
+
+/// Calendar the days are named in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Reckoning {
+    /// The Gregorian calendar, also for the days before it was introduced.
+    #[default]
+    Gregorian,
+    Julian,
+    /// The Julian calendar up to the day before the given one, and the
+    /// Gregorian calendar from it on. The days between the last Julian and
+    /// the first Gregorian date were skipped.
+    Reform(Date)
+}
+
+impl Reckoning {
+    /// The reform of the given year: October 1582, introducing the
+    /// Gregorian calendar, or September 1752, when Britain and its colonies
+    /// adopted it.
+    pub fn reform_in(year: i32) -> Result<Reckoning, String> {
+        match year {
+            1582 => Ok(Reckoning::Reform(UTC.ymd(1582, 10, 15))),
+            1752 => Ok(Reckoning::Reform(UTC.ymd(1752, 9, 14))),
+            _    => Err(format!("unknown calendar reform: '{}' (available: 1582, 1752)", year))
+        }
+    }
+
+    /// Year, month and day the date is named in this calendar.
+    pub fn ymd(self, date: Date) -> (i32, u32, u32) {
+        match self {
+            Reckoning::Reform(first) if date < first => julian_ymd(date),
+            Reckoning::Gregorian | Reckoning::Reform(_) => (date.year(), date.month(), date.day()),
+            Reckoning::Julian                           => julian_ymd(date)
+        }
+    }
+
+    /// Returns the date named with the year, month and day in this
+    /// calendar, if there is one. Days skipped by a reform have none.
+    pub fn date(self, year: i32, month: u32, day: u32) -> Option<Date> {
+        let gregorian = || UTC.ymd_opt(year, month, day).single();
+
+        match self {
+            Reckoning::Gregorian    => gregorian(),
+            Reckoning::Julian       => from_julian_ymd(year, month, day),
+            Reckoning::Reform(first) => {
+                gregorian().filter(|&date| date >= first)
+                           .or_else(|| from_julian_ymd(year, month, day)
+                                           .filter(|&date| date < first))
+            }
+        }
+    }
+
+    /// Returns the date named like the given one is in the Gregorian
+    /// calendar, or the first day after the days skipped by a reform if it
+    /// is one of them. Dates given by users are parsed as Gregorian dates,
+    /// and meant in the calendar they see.
+    pub fn rename(self, date: Date) -> Date {
+        match (self.date(date.year(), date.month(), date.day()), self) {
+            (Some(date), _)                  => date,
+            (None, Reckoning::Reform(first)) => first,
+            (None, _)                        => date
+        }
+    }
+
+    /// The days of the year in this calendar, from its January 1 to the
+    /// next one.
+    pub fn dates(self, year: i32) -> DateRange {
+        DateRange::new(self.rename(UTC.ymd(year, 1, 1)), self.rename(UTC.ymd(year + 1, 1, 1)))
+    }
+
+    /// Number of the day in its year in this calendar, starting at 1. It
+    /// follows the month and day the date is named with, so the numbers of
+    /// the days skipped by a reform are skipped too.
+    pub fn ordinal(self, date: Date) -> u32 {
+        match self {
+            Reckoning::Reform(first) if date < first => julian_ordinal(date),
+            Reckoning::Gregorian | Reckoning::Reform(_) => date.ordinal(),
+            Reckoning::Julian                           => julian_ordinal(date)
+        }
+    }
+}
+
+impl FromStr for Reckoning {
+    type Err = String;
+
+    /// Parses `gregorian`, `julian` or the year of a reform, see
+    /// `reform_in`.
+    fn from_str(s: &str) -> Result<Reckoning, String> {
+        match s {
+            "gregorian" => Ok(Reckoning::Gregorian),
+            "julian"    => Ok(Reckoning::Julian),
+            _           => Reckoning::reform_in(s.parse().map_err(|_| {
+                format!("unknown calendar: '{}' (expected gregorian, julian, 1582 or 1752)", s)
+            })?)
+        }
+    }
+}
+
+/// Whether the date is a business day: Monday to Friday, except holidays.
+pub fn is_business_day<F>(date: Date, is_holiday: F) -> bool
+    where F: Fn(Date) -> bool
+{
+    weekday(date) < 5 && !is_holiday(date)
+}
+
+/// Number of business days after `from` up to and including `to`. Negative
+/// if `to` is before `from`.
+pub fn business_days_between<F>(from: Date, to: Date, is_holiday: F) -> i64
+    where F: Fn(Date) -> bool
+{
+    let count = |range: DateRange| {
+        range.filter(|&date| is_business_day(date, &is_holiday)).count() as i64
+    };
+
+    if from <= to {
+        count(DateRange::new(from.succ(), to.succ()))
+    } else {
+        -count(DateRange::new(to.succ(), from.succ()))
+    }
+}
+
+/// Returns the business day `count` business days after the date, or
+/// before it if `count` is negative. Zero returns the date itself, even if
+/// it isn't a business day. Returns an error if the result is outside the
+/// supported years.
+pub fn add_business_days<F>(date: Date, count: i64, is_holiday: F) -> Result<Date, String>
+    where F: Fn(Date) -> bool
+{
+    check_days(count)?;
+
+    let step       = |date: Date| if count > 0 { date.succ() } else { date.pred() };
+    let mut result = date;
+
+    for _ in 0..count.unsigned_abs() {
+        loop {
+            result = step(result);
+            check_year(result.year())?;
+
+            if is_business_day(result, &is_holiday) {
+                break;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Number of days from `from` to `to`. Negative if `to` is before `from`.
+pub fn days_between(from: Date, to: Date) -> i64 {
+    i64::from(to.num_days_from_ce()) - i64::from(from.num_days_from_ce())
+}
+
+/// Whole months from `from` to `to`, and the days left over. A month after
+/// January 31 is the last day of February. Both are negative if `to` is
+/// before `from`.
+pub fn months_and_days_between(from: Date, to: Date) -> (i32, i64) {
+    if to < from {
+        let (months, days) = months_and_days_between(to, from);
+        return (-months, -days);
+    }
+
+    let mut months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    if to.day() < from.day() {
+        months -= 1;
+    }
+
+    let first  = add_months(from, months);
+    let last   = add_months(from, months + 1).pred();
+    let anchor = first.with_day(from.day()).unwrap_or(last);
+
+    (months, days_between(anchor, to))
+}
+
 //------------------------------------------------------------------------------
 
-/// Range of dates.
+/// Range of consecutive dates, from `start` up to but not including `end`.
+/// Iterating over the range yields its dates.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DateRange {
+    #[cfg_attr(feature = "serde", serde(with = "::serialization::date"))]
     pub start: Date,
+    #[cfg_attr(feature = "serde", serde(with = "::serialization::date"))]
     pub end:   Date
 }
 
 impl DateRange {
     pub fn new(start: Date, end: Date) -> DateRange {
-        DateRange{ start: start, end: end }
+        DateRange{ start, end }
     }
 
     fn empty(&self) -> bool {
         self.start >= self.end
     }
 
+    /// Number of days in the range.
+    pub fn days(&self) -> u32 {
+        (self.end.num_days_from_ce() - self.start.num_days_from_ce()).max(0) as u32
+    }
+
+    /// Returns the date `offset` days after the start of the range, if it
+    /// is in the range.
+    pub fn day(&self, offset: u32) -> Option<Date> {
+        if offset < self.days() {
+            self.start.checked_add(::chrono::Duration::days(offset as i64))
+        } else {
+            None
+        }
+    }
+
+    /// Whether the date is in the range.
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && date < self.end
+    }
+
+    /// Whether the two ranges have at least one day in common.
+    pub fn overlaps(&self, other: DateRange) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Returns the days the two ranges have in common, if any.
+    pub fn intersect(&self, other: DateRange) -> Option<DateRange> {
+        if self.overlaps(other) {
+            Some(DateRange::new(self.start.max(other.start), self.end.min(other.end)))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the range covering the days of both ranges, if they overlap
+    /// or one ends where the other starts, so that there's no gap between
+    /// them. An empty range adds nothing to the other.
+    pub fn union(&self, other: DateRange) -> Option<DateRange> {
+        if other.empty() {
+            Some(*self)
+        } else if self.empty() {
+            Some(other)
+        } else if self.start <= other.end && other.start <= self.end {
+            Some(DateRange::new(self.start.min(other.start), self.end.max(other.end)))
+        } else {
+            None
+        }
+    }
+
+    /// Splits the range into the days before the date and the days from the
+    /// date on. Either is empty if the date is outside the range.
+    pub fn split_at(&self, date: Date) -> (DateRange, DateRange) {
+        let middle = date.max(self.start).min(self.end.max(self.start));
+        (DateRange::new(self.start, middle), DateRange::new(middle, self.end))
+    }
+
+    /// Iterates over every `days`-th date of the range, starting with the
+    /// first one. Panics if `days` is 0.
+    pub fn every(self, days: u32) -> StepBy<DateRange> {
+        self.step_by(days as usize)
+    }
+
+    /// Iterates over the dates of the range falling on the day of the week.
+    pub fn weekdays(self, day: Weekday) -> StepBy<DateRange> {
+        let offset = (day.num_days_from_monday() + 7 - weekday(self.start)) % 7;
+        self.split_at(self.start + ::chrono::Duration::days(i64::from(offset))).1.every(7)
+    }
+
+    /// Splits the range into the parts falling into each year.
+    pub fn by_year(self) -> ByYear {
+        self.group_by(Date::year)
+    }
+
+    /// Splits the range into the parts falling into each year, with years
+    /// starting with the given month.
+    pub fn by_year_starting(self, month: u32) -> GroupBy<i32, impl FnMut(&Date) -> i32> {
+        self.group_by(move |date: &Date| year_starting(*date, month))
+    }
+
+    /// Splits the range into the parts falling into each month.
     pub fn by_month(self) -> ByMonth {
         self.group_by(Date::month)
     }
 
+    /// Splits the range into the parts falling into each quarter (January
+    /// to March, April to June and so on).
+    pub fn by_quarter(self) -> ByQuarter {
+        self.group_by(quarter)
+    }
+
+    /// Splits the range into the parts falling into each week (Monday to
+    /// Sunday). Weeks across New Year are kept whole.
     pub fn by_week(self) -> ByWeek {
-        self.group_by(week_number)
+        self.group_by(iso_week)
+    }
+
+    /// Splits the range into the parts falling into each ISO week, like
+    /// `by_week`, along with the ISO year and week number of each, e.g.
+    /// `(2015, 1)` for the week from 2014-12-29 to 2015-01-04.
+    pub fn by_iso_week(self) -> impl Iterator<Item = (i32, u32, DateRange)> {
+        self.by_numbered_week(WeekNumbering::Iso)
+    }
+
+    /// Splits the range into the weeks of the numbering, along with the year
+    /// and number of each. Unlike ISO weeks, weeks of the other numberings
+    /// are split at New Year.
+    pub fn by_numbered_week(self, numbering: WeekNumbering)
+                            -> impl Iterator<Item = (i32, u32, DateRange)> {
+        self.group_by(move |date: &Date| week_number(date, numbering)).map(move |week| {
+            let (year, number) = week_number(&week.start, numbering);
+            (year, number, week)
+        })
+    }
+
+    /// Splits the range into the parts falling into each week, with weeks
+    /// starting on the given day.
+    pub fn by_week_starting(self, first: Weekday) -> GroupBy<i32, impl FnMut(&Date) -> i32> {
+        let offset = first.num_days_from_monday();
+
+        // Weeks are identified by the day they start on.
+        self.group_by(move |date: &Date| {
+            date.num_days_from_ce() - ((weekday(*date) + 7 - offset) % 7) as i32
+        })
     }
 
-    fn group_by<K, F>(self, key: F) -> GroupBy<F>
+    /// Splits the range into runs of consecutive dates for which `key`
+    /// returns the same value, e.g. pay periods, sprints or school terms.
+    pub fn group_by<K, F>(self, key: F) -> GroupBy<K, F>
         where F: FnMut(&Date) -> K, K: PartialEq
     {
-        GroupBy{ dates: self, key: key }
+        GroupBy{ dates: self, key, marker: PhantomData }
     }
 }
 
@@ -59,19 +627,64 @@ impl Iterator for DateRange {
             result
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let days = self.days() as usize;
+        (days, Some(days))
+    }
+
+    fn count(self) -> usize {
+        self.days() as usize
+    }
+
+    fn last(mut self) -> Option<Date> {
+        self.next_back()
+    }
+
+    /// Skips to the date without going through the dates before it.
+    fn nth(&mut self, n: usize) -> Option<Date> {
+        match if n < self.len() { self.day(n as u32) } else { None } {
+            Some(date) => {
+                self.start = date.succ();
+                Some(date)
+            },
+            None => {
+                self.start = self.end;
+                None
+            }
+        }
+    }
 }
 
+impl DoubleEndedIterator for DateRange {
+    fn next_back(&mut self) -> Option<Date> {
+        if self.empty() {
+            None
+        } else {
+            self.end = self.end.pred();
+            Some(self.end)
+        }
+    }
+}
+
+impl ExactSizeIterator for DateRange {}
+
 //------------------------------------------------------------------------------
 
-struct GroupBy<F> {
+/// Iterator over consecutive subranges of a range whose dates share the same
+/// key.
+pub struct GroupBy<K, F> {
     dates: DateRange,
-    key: F
+    key: F,
+    marker: PhantomData<fn() -> K>
 }
 
-pub type ByMonth = GroupBy<fn(&Date) -> u32>;
-pub type ByWeek  = GroupBy<fn(&Date) -> u32>;
+pub type ByYear    = GroupBy<i32, fn(&Date) -> i32>;
+pub type ByQuarter = GroupBy<u32, fn(&Date) -> u32>;
+pub type ByMonth   = GroupBy<u32, fn(&Date) -> u32>;
+pub type ByWeek    = GroupBy<(i32, u32), fn(&Date) -> (i32, u32)>;
 
-impl<K, F> Iterator for GroupBy<F> where F: FnMut(&Date) -> K, K: PartialEq {
+impl<K, F> Iterator for GroupBy<K, F> where F: FnMut(&Date) -> K, K: PartialEq {
     type Item = DateRange;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -80,8 +693,8 @@ impl<K, F> Iterator for GroupBy<F> where F: FnMut(&Date) -> K, K: PartialEq {
         let start     = self.dates.start;
         let start_key = (self.key)(&start);
 
-        let end = self.dates.skip_while(|&d| (self.key)(&d) == start_key)
-                            .next()
+        let key = &mut self.key;
+        let end = self.dates.find(|d| key(d) != start_key)
                             .unwrap_or(self.dates.end);
 
         self.dates = DateRange::new(end, self.dates.end);
@@ -90,22 +703,91 @@ impl<K, F> Iterator for GroupBy<F> where F: FnMut(&Date) -> K, K: PartialEq {
     }
 }
 
-/// Returns a range of all dates in the given year.
-pub fn dates(year: i32) -> DateRange {
-    DateRange::new(UTC.ymd(year, 1, 1), UTC.ymd(year + 1, 1, 1))
+/// Returns a range of all dates in the given year, or an error if it is
+/// outside `MIN_YEAR` to `MAX_YEAR`.
+pub fn dates(year: i32) -> Result<DateRange, String> {
+    dates_starting(year, 1)
+}
+
+/// Returns a range of all dates in a year that starts with the given month
+/// of `year`, e.g. a fiscal year from April to March. Fails like `dates`.
+pub fn dates_starting(year: i32, month: u32) -> Result<DateRange, String> {
+    check_year(year)?;
+    Ok(DateRange::new(UTC.ymd(year, month, 1), UTC.ymd(year + 1, month, 1)))
+}
+
+/// Returns the range of all supported dates, from `MIN_YEAR` to `MAX_YEAR`.
+pub fn supported_dates() -> DateRange {
+    DateRange::new(UTC.ymd(MIN_YEAR, 1, 1), UTC.ymd(MAX_YEAR + 1, 1, 1))
+}
+
+/// Returns the year of the date, for years starting with the given month:
+/// the calendar year the date's year starts in.
+pub fn year_starting(date: Date, month: u32) -> i32 {
+    if date.month() >= month { date.year() } else { date.year() - 1 }
+}
+
+/// Returns the date with the given month and day in each of the given years,
+/// paired with its year. The date is `None` in years where it doesn't exist
+/// (February 29 in common years).
+pub fn anniversaries(month: u32, day: u32, years: RangeInclusive<i32>)
+    -> impl Iterator<Item = (i32, Option<Date>)>
+{
+    years.map(move |year| (year, UTC.ymd_opt(year, month, day).single()))
 }
 
 //------------------------------------------------------------------------------
 
 #[test]
-fn week_number_returns_week_number_of_the_date() {
-    assert_eq!(week_number(&UTC.ymd(2015, 1,  1)), 0);
-    assert_eq!(week_number(&UTC.ymd(2015, 1,  2)), 0);
-    assert_eq!(week_number(&UTC.ymd(2015, 1,  3)), 0);
-    assert_eq!(week_number(&UTC.ymd(2015, 1,  4)), 0);
+fn nth_and_last_weekday_find_days_of_the_week_in_a_month() {
+    assert_eq!(nth_weekday(2015, 11, Weekday::Thu, 4), Some(UTC.ymd(2015, 11, 26)));
+    assert_eq!(nth_weekday(2015, 6, Weekday::Mon, 1), Some(UTC.ymd(2015, 6, 1)));
+    assert_eq!(nth_weekday(2015, 6, Weekday::Mon, 5), Some(UTC.ymd(2015, 6, 29)));
+    assert_eq!(nth_weekday(2015, 6, Weekday::Wed, 5), None);
+    assert_eq!(nth_weekday(2015, 6, Weekday::Tue, 0), None);
+    assert_eq!(nth_weekday(2015, 13, Weekday::Tue, 1), None);
+    assert_eq!(last_weekday(2015, 5, Weekday::Mon), UTC.ymd(2015, 5, 25));
+    assert_eq!(last_weekday(2015, 12, Weekday::Thu), UTC.ymd(2015, 12, 31));
+}
+
+#[test]
+fn zone_parses_names_and_offsets() {
+    assert_eq!("utc".parse(), Ok(Zone::Utc));
+    assert_eq!("local".parse(), Ok(Zone::Local));
+    assert_eq!("+02:00".parse(), Ok(Zone::Fixed(7200)));
+    assert_eq!("-0530".parse(), Ok(Zone::Fixed(-19800)));
+    assert_eq!("+9".parse(), Ok(Zone::Fixed(32400)));
+    assert!("Europe/Prague".parse::<Zone>().is_err());
+    assert!("+25:00".parse::<Zone>().is_err());
+}
+
+#[test]
+fn day_at_depends_on_zone() {
+    let instant = UTC.ymd(2015, 3, 1).and_hms(23, 30, 0);
 
-    assert_eq!(week_number(&UTC.ymd(2015, 1,  5)), 1);
-    assert_eq!(week_number(&UTC.ymd(2015, 1, 13)), 2);
+    assert_eq!(day_at(instant, Zone::Utc), UTC.ymd(2015, 3, 1));
+    assert_eq!(day_at(instant, Zone::Fixed(3600)), UTC.ymd(2015, 3, 2));
+    assert_eq!(day_at(instant, Zone::Fixed(-3600)), UTC.ymd(2015, 3, 1));
+}
+
+#[test]
+fn months_and_days_between_counts_whole_months() {
+    assert_eq!(days_between(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 3, 15)), 73);
+    assert_eq!(months_and_days_between(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 3, 15)), (2, 14));
+    assert_eq!(months_and_days_between(UTC.ymd(2015, 1, 31), UTC.ymd(2015, 3, 1)), (1, 1));
+    assert_eq!(months_and_days_between(UTC.ymd(2015, 3, 15), UTC.ymd(2015, 1, 1)), (-2, -14));
+    assert_eq!(months_and_days_between(UTC.ymd(2015, 1, 5), UTC.ymd(2015, 1, 5)), (0, 0));
+}
+
+#[test]
+fn iso_week_returns_iso_year_and_week_of_the_date() {
+    assert_eq!(iso_week(&UTC.ymd(2014, 12, 29)), (2015, 1));
+    assert_eq!(iso_week(&UTC.ymd(2015, 1,  1)), (2015, 1));
+    assert_eq!(iso_week(&UTC.ymd(2015, 1,  4)), (2015, 1));
+
+    assert_eq!(iso_week(&UTC.ymd(2015, 1,  5)), (2015, 2));
+    assert_eq!(iso_week(&UTC.ymd(2015, 1, 13)), (2015, 3));
+    assert_eq!(iso_week(&UTC.ymd(2016, 1,  1)), (2015, 53));
 }
 
 #[test]
@@ -120,6 +802,71 @@ fn date_range_can_be_iterated() {
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn date_ranges_combine_like_sets() {
+    let range  = |from: u32, to: u32| DateRange::new(UTC.ymd(2015, 1, from), UTC.ymd(2015, 1, to));
+    let week   = range(5, 12);
+
+    assert!(week.contains(UTC.ymd(2015, 1, 5)));
+    assert!(!week.contains(UTC.ymd(2015, 1, 12)));
+    assert!(week.overlaps(range(11, 20)));
+    assert!(!week.overlaps(range(12, 20)));
+
+    assert_eq!(week.intersect(range(1, 8)), Some(range(5, 8)));
+    assert_eq!(week.intersect(range(6, 8)), Some(range(6, 8)));
+    assert_eq!(week.intersect(range(12, 20)), None);
+
+    assert_eq!(week.union(range(12, 20)), Some(range(5, 20)));
+    assert_eq!(week.union(range(1, 8)), Some(range(1, 12)));
+    assert_eq!(week.union(range(13, 20)), None);
+    assert_eq!(week.union(range(20, 20)), Some(week));
+
+    assert_eq!(week.split_at(UTC.ymd(2015, 1, 7)), (range(5, 7), range(7, 12)));
+    assert_eq!(week.split_at(UTC.ymd(2015, 1, 1)), (range(5, 5), week));
+    assert_eq!(week.split_at(UTC.ymd(2015, 1, 20)), (week, range(12, 12)));
+}
+
+#[test]
+fn every_and_weekdays_step_through_the_range() {
+    let january = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 1));
+
+    assert_eq!(january.every(14).collect::<Vec<_>>(),
+               [UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 15), UTC.ymd(2015, 1, 29)]);
+    assert_eq!(january.weekdays(Weekday::Fri).collect::<Vec<_>>(),
+               [UTC.ymd(2015, 1, 2), UTC.ymd(2015, 1, 9), UTC.ymd(2015, 1, 16),
+                UTC.ymd(2015, 1, 23), UTC.ymd(2015, 1, 30)]);
+    assert_eq!(january.weekdays(Weekday::Thu).next(), Some(UTC.ymd(2015, 1, 1)));
+    assert_eq!(DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 2))
+                   .weekdays(Weekday::Fri).count(), 0);
+}
+
+#[test]
+fn date_range_iterates_from_both_ends() {
+    let mut range = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 5));
+
+    assert_eq!(range.len(), 4);
+    assert_eq!(range.next_back(), Some(UTC.ymd(2015, 1, 4)));
+    assert_eq!(range.next(), Some(UTC.ymd(2015, 1, 1)));
+    assert_eq!(range.rev().collect::<Vec<_>>(), [UTC.ymd(2015, 1, 3), UTC.ymd(2015, 1, 2)]);
+
+    assert_eq!(dates(2015).unwrap().last(), Some(UTC.ymd(2015, 12, 31)));
+    assert_eq!(dates(2015).unwrap().nth(59), Some(UTC.ymd(2015, 3, 1)));
+
+    let mut range = dates(2015).unwrap();
+    assert_eq!(range.nth(365), None);
+    assert_eq!(range.len(), 0);
+}
+
+#[test]
+fn days_counts_days_in_range() {
+    let range = DateRange::new(UTC.ymd(2015, 12, 30), UTC.ymd(2016, 3, 1));
+
+    assert_eq!(range.days(), 62);
+    assert_eq!(range.day(2), Some(UTC.ymd(2016, 1, 1)));
+    assert_eq!(range.day(62), None);
+    assert_eq!(DateRange::new(range.end, range.start).days(), 0);
+}
+
 #[test]
 fn by_month_groups_date_range_by_months() {
     let     range  = DateRange::new(UTC.ymd(2015, 1, 10), UTC.ymd(2015, 3, 10));
@@ -137,6 +884,19 @@ fn by_month_groups_date_range_by_months() {
     assert_eq!(months.next(), None);
 }
 
+#[test]
+fn by_quarter_groups_date_range_by_quarters() {
+    let range = DateRange::new(UTC.ymd(2015, 2, 10), UTC.ymd(2016, 1, 10));
+
+    assert_eq!(range.by_quarter().collect::<Vec<_>>(), vec![
+        DateRange::new(UTC.ymd(2015,  2, 10), UTC.ymd(2015,  4, 1)),
+        DateRange::new(UTC.ymd(2015,  4,  1), UTC.ymd(2015,  7, 1)),
+        DateRange::new(UTC.ymd(2015,  7,  1), UTC.ymd(2015, 10, 1)),
+        DateRange::new(UTC.ymd(2015, 10,  1), UTC.ymd(2016,  1, 1)),
+        DateRange::new(UTC.ymd(2016,  1,  1), UTC.ymd(2016,  1, 10))
+    ]);
+}
+
 #[test]
 fn by_week_groups_date_range_by_weeks() {
     let range     = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 17));
@@ -154,9 +914,88 @@ fn by_week_groups_date_range_by_weeks() {
     assert_eq!(weeks.next(), None);
 }
 
+#[test]
+fn by_week_keeps_weeks_across_new_year_whole() {
+    let range = DateRange::new(UTC.ymd(2014, 12, 22), UTC.ymd(2016, 1, 12));
+    let weeks = range.by_week().collect::<Vec<_>>();
+
+    assert_eq!(weeks.len(), 56);
+    assert_eq!(weeks[1], DateRange::new(UTC.ymd(2014, 12, 29), UTC.ymd(2015, 1, 5)));
+    assert_eq!(weeks[55], DateRange::new(UTC.ymd(2016, 1, 11), UTC.ymd(2016, 1, 12)));
+}
+
+#[test]
+fn week_number_follows_the_numbering() {
+    use self::WeekNumbering::*;
+
+    // 2015 starts on a Thursday, 2017 on a Sunday.
+    let numbers = |numbering, year, month, day| week_number(&UTC.ymd(year, month, day), numbering);
+
+    assert_eq!(numbers(Iso, 2015, 1, 1), (2015, 1));
+    assert_eq!(numbers(Us, 2015, 1, 1), (2015, 1));
+    assert_eq!(numbers(Us, 2015, 1, 3), (2015, 1));
+    assert_eq!(numbers(Us, 2015, 1, 4), (2015, 2));
+    assert_eq!(numbers(Us, 2015, 12, 31), (2015, 53));
+    assert_eq!(numbers(Simple, 2015, 1, 4), (2015, 0));
+    assert_eq!(numbers(Simple, 2015, 1, 5), (2015, 1));
+
+    assert_eq!(numbers(Iso, 2017, 1, 1), (2016, 52));
+    assert_eq!(numbers(Us, 2017, 1, 1), (2017, 1));
+    assert_eq!(numbers(Simple, 2017, 1, 1), (2017, 0));
+    assert_eq!(numbers(Simple, 2017, 1, 2), (2017, 1));
+}
+
+#[test]
+fn by_numbered_week_splits_weeks_at_new_year() {
+    let range = DateRange::new(UTC.ymd(2015, 12, 27), UTC.ymd(2016, 1, 4));
+
+    assert_eq!(range.by_numbered_week(WeekNumbering::Us).collect::<Vec<_>>(), vec![
+        (2015, 53, DateRange::new(UTC.ymd(2015, 12, 27), UTC.ymd(2016, 1, 1))),
+        (2016, 1, DateRange::new(UTC.ymd(2016, 1, 1), UTC.ymd(2016, 1, 3))),
+        (2016, 2, DateRange::new(UTC.ymd(2016, 1, 3), UTC.ymd(2016, 1, 4)))
+    ]);
+}
+
+#[test]
+fn by_iso_week_numbers_weeks() {
+    let range = DateRange::new(UTC.ymd(2015, 12, 28), UTC.ymd(2016, 1, 5));
+
+    assert_eq!(range.by_iso_week().collect::<Vec<_>>(), vec![
+        (2015, 53, DateRange::new(UTC.ymd(2015, 12, 28), UTC.ymd(2016, 1, 4))),
+        (2016, 1, DateRange::new(UTC.ymd(2016, 1, 4), UTC.ymd(2016, 1, 5)))
+    ]);
+}
+
+#[test]
+fn by_week_starting_groups_date_range_by_weeks_starting_on_given_day() {
+    let range = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 12));
+
+    assert_eq!(range.by_week_starting(Weekday::Sun).collect::<Vec<_>>(), vec![
+        DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 4)),
+        DateRange::new(UTC.ymd(2015, 1, 4), UTC.ymd(2015, 1, 11)),
+        DateRange::new(UTC.ymd(2015, 1, 11), UTC.ymd(2015, 1, 12))
+    ]);
+    assert_eq!(range.by_week_starting(Weekday::Mon).collect::<Vec<_>>(),
+               range.by_week().collect::<Vec<_>>());
+}
+
+#[test]
+fn group_by_groups_date_range_by_custom_key() {
+    // Two-week sprints starting on Monday 2015-01-05.
+    let start = UTC.ymd(2015, 1, 5).num_days_from_ce();
+    let range = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 1));
+    let sprints = range.group_by(|date| (date.num_days_from_ce() - start).div_euclid(14));
+
+    assert_eq!(sprints.collect::<Vec<_>>(), vec![
+        DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 5)),
+        DateRange::new(UTC.ymd(2015, 1, 5), UTC.ymd(2015, 1, 19)),
+        DateRange::new(UTC.ymd(2015, 1, 19), UTC.ymd(2015, 2, 1))
+    ]);
+}
+
 #[test]
 fn dates_returns_all_dates_in_a_year() {
-    let range = dates(2015);
+    let range = dates(2015).unwrap();
 
     let actual = range.take(4).collect::<Vec<_>>();
     let expected = vec![ UTC.ymd(2015, 1, 1)
@@ -169,3 +1008,154 @@ fn dates_returns_all_dates_in_a_year() {
     let expected = UTC.ymd(2015, 12, 31);
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn dates_follow_proleptic_gregorian_leap_rules() {
+    assert_eq!(dates(1).unwrap().start.weekday(), Weekday::Mon);
+    assert_eq!(dates(0).unwrap().end, UTC.ymd(1, 1, 1));
+    assert_eq!(dates(1).unwrap().days(), 365);
+    assert_eq!(dates(4).unwrap().days(), 366);
+    assert_eq!(dates(0).unwrap().days(), 366);
+    assert_eq!(dates(-4).unwrap().days(), 366);
+    assert_eq!(dates(-1).unwrap().days(), 365);
+    assert_eq!(dates(100).unwrap().days(), 365);
+    assert_eq!(dates(-100).unwrap().days(), 365);
+    assert_eq!(dates(1600).unwrap().days(), 366);
+}
+
+#[test]
+fn julian_ymd_converts_to_and_from_the_julian_calendar() {
+    assert_eq!(julian_ymd(UTC.ymd(1582, 10, 15)), (1582, 10, 5));
+    assert_eq!(julian_ymd(UTC.ymd(1752, 9, 14)), (1752, 9, 3));
+    assert_eq!(julian_ymd(UTC.ymd(2000, 1, 14)), (2000, 1, 1));
+    assert_eq!(julian_ymd(UTC.ymd(-9999, 1, 1)), (-9999, 3, 19));
+    assert_eq!(from_julian_ymd(1752, 9, 2), Some(UTC.ymd(1752, 9, 13)));
+    assert_eq!(from_julian_ymd(-9999, 3, 19), Some(UTC.ymd(-9999, 1, 1)));
+    // Every fourth year is a leap year in the Julian calendar.
+    assert_eq!(from_julian_ymd(1900, 2, 29), Some(UTC.ymd(1900, 3, 13)));
+    assert_eq!(from_julian_ymd(1901, 2, 29), None);
+}
+
+#[test]
+fn reckoning_names_days_across_a_reform() {
+    let reform = Reckoning::reform_in(1752).unwrap();
+
+    assert_eq!(reform.ymd(UTC.ymd(1752, 9, 13)), (1752, 9, 2));
+    assert_eq!(reform.ymd(UTC.ymd(1752, 9, 14)), (1752, 9, 14));
+    assert_eq!(reform.date(1752, 9, 2), Some(UTC.ymd(1752, 9, 13)));
+    assert_eq!(reform.date(1752, 9, 3), None);
+    assert_eq!(reform.rename(UTC.ymd(1752, 9, 10)), UTC.ymd(1752, 9, 14));
+    assert_eq!(reform.rename(UTC.ymd(1752, 1, 1)), UTC.ymd(1752, 1, 12));
+    assert_eq!(reform.dates(1752), DateRange::new(UTC.ymd(1752, 1, 12), UTC.ymd(1753, 1, 1)));
+    assert_eq!(reform.ordinal(UTC.ymd(1752, 9, 13)), 246);
+    assert_eq!(reform.ordinal(UTC.ymd(1752, 9, 14)), 258);
+    assert_eq!(reform.ordinal(UTC.ymd(1752, 12, 31)), 366);
+    assert_eq!(Reckoning::reform_in(1582).unwrap().ordinal(UTC.ymd(1582, 10, 14)), 277);
+    assert_eq!(Reckoning::reform_in(1582).unwrap().ordinal(UTC.ymd(1582, 10, 15)), 288);
+    assert_eq!(Reckoning::Julian.ordinal(UTC.ymd(2000, 1, 14)), 1);
+    assert_eq!(Reckoning::Gregorian.ymd(UTC.ymd(1752, 9, 13)), (1752, 9, 13));
+    assert_eq!("julian".parse::<Reckoning>().unwrap().ymd(UTC.ymd(2000, 1, 14)), (2000, 1, 1));
+    assert!("1700".parse::<Reckoning>().is_err());
+}
+
+#[test]
+fn check_year_rejects_unsupported_years() {
+    assert_eq!(check_year(MIN_YEAR), Ok(MIN_YEAR));
+    assert_eq!(check_year(MAX_YEAR), Ok(MAX_YEAR));
+    assert!(check_year(MAX_YEAR + 1).is_err());
+    assert!(check_year(-1_000_000).is_err());
+}
+
+#[test]
+fn dates_rejects_unsupported_years() {
+    assert_eq!(dates(MAX_YEAR).map(|range| range.end), Ok(supported_dates().end));
+    assert_eq!(dates(MIN_YEAR).map(|range| range.start), Ok(supported_dates().start));
+    assert!(dates(1_000_000).unwrap_err().contains("year 1000000 is out of range"));
+    assert!(dates_starting(MIN_YEAR - 1, 4).is_err());
+}
+
+#[test]
+fn dates_starting_spans_year_from_given_month() {
+    let range = dates_starting(2025, 4).unwrap();
+
+    assert_eq!(range, DateRange::new(UTC.ymd(2025, 4, 1), UTC.ymd(2026, 4, 1)));
+    assert_eq!(year_starting(UTC.ymd(2026, 3, 31), 4), 2025);
+    assert_eq!(year_starting(UTC.ymd(2026, 4, 1), 4), 2026);
+
+    let years = DateRange::new(UTC.ymd(2025, 1, 1), UTC.ymd(2026, 1, 1)).by_year_starting(4)
+                                                                         .collect::<Vec<_>>();
+    assert_eq!(years, [DateRange::new(UTC.ymd(2025, 1, 1), UTC.ymd(2025, 4, 1)),
+                       DateRange::new(UTC.ymd(2025, 4, 1), UTC.ymd(2026, 1, 1))]);
+}
+
+#[test]
+fn anniversaries_returns_the_date_in_each_year() {
+    let actual = anniversaries(2, 29, 2015..=2016).collect::<Vec<_>>();
+    let expected = vec![(2015, None), (2016, Some(UTC.ymd(2016, 2, 29)))];
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn julian_day_counts_days_since_4713_bc() {
+    assert_eq!(julian_day(UTC.ymd(2000, 1, 1)), 2_451_545);
+    assert_eq!(julian_day(UTC.ymd(2015, 3, 14)), 2_457_096);
+}
+
+#[test]
+fn business_days_between_skips_weekends_and_holidays() {
+    let friday  = UTC.ymd(2015, 1, 2);
+    let holiday = UTC.ymd(2015, 1, 6);
+    let is_holiday = |date| date == holiday;
+
+    assert_eq!(business_days_between(friday, UTC.ymd(2015, 1, 9), is_holiday), 4);
+    assert_eq!(business_days_between(UTC.ymd(2015, 1, 9), friday, is_holiday), -4);
+    assert_eq!(business_days_between(friday, UTC.ymd(2015, 1, 4), is_holiday), 0);
+    assert_eq!(business_days_between(friday, friday, is_holiday), 0);
+}
+
+#[test]
+fn add_business_days_skips_weekends_and_holidays() {
+    let friday  = UTC.ymd(2015, 1, 2);
+    let holiday = UTC.ymd(2015, 1, 6);
+    let is_holiday = |date| date == holiday;
+
+    assert_eq!(add_business_days(friday, 3, is_holiday), Ok(UTC.ymd(2015, 1, 8)));
+    assert_eq!(add_business_days(UTC.ymd(2015, 1, 8), -3, is_holiday), Ok(friday));
+    assert_eq!(add_business_days(UTC.ymd(2015, 1, 3), 0, is_holiday), Ok(UTC.ymd(2015, 1, 3)));
+    assert_eq!(add_business_days(UTC.ymd(2015, 1, 3), 1, is_holiday), Ok(UTC.ymd(2015, 1, 5)));
+    assert!(add_business_days(UTC.ymd(MAX_YEAR, 12, 31), 1, is_holiday).is_err());
+    assert!(add_business_days(friday, 1_000_000_000, is_holiday).is_err());
+    assert!(add_business_days(friday, i64::MIN, is_holiday).is_err());
+}
+
+#[test]
+fn add_days_stays_within_supported_years() {
+    assert_eq!(add_days(UTC.ymd(2015, 1, 2), -3), Ok(UTC.ymd(2014, 12, 30)));
+    assert_eq!(add_days(UTC.ymd(MAX_YEAR, 12, 30), 1), Ok(UTC.ymd(MAX_YEAR, 12, 31)));
+    assert!(add_days(UTC.ymd(MAX_YEAR, 12, 31), 1).is_err());
+    assert!(add_days(UTC.ymd(2015, 1, 2), i64::MAX).is_err());
+    assert!(add_days(UTC.ymd(2015, 1, 2), i64::MIN).is_err());
+}
+
+#[test]
+fn add_months_returns_first_day_of_month() {
+    assert_eq!(add_months(UTC.ymd(2015, 3, 14),   0), UTC.ymd(2015,  3, 1));
+    assert_eq!(add_months(UTC.ymd(2015, 3, 14),  10), UTC.ymd(2016,  1, 1));
+    assert_eq!(add_months(UTC.ymd(2015, 3, 14), -3),  UTC.ymd(2014, 12, 1));
+}
+
+#[test]
+fn months_from_starts_with_month_of_date() {
+    assert_eq!(months_from(UTC.ymd(2015, 11, 14), 3),
+               DateRange::new(UTC.ymd(2015, 11, 1), UTC.ymd(2016, 2, 1)));
+}
+
+#[test]
+fn months_around_spans_whole_months() {
+    assert_eq!(months_around(UTC.ymd(2015, 1, 14), 2, 2),
+               Ok(DateRange::new(UTC.ymd(2014, 11, 1), UTC.ymd(2015, 4, 1))));
+    assert_eq!(months_around(UTC.ymd(9999, 11, 1), 0, 1),
+               Ok(DateRange::new(UTC.ymd(9999, 11, 1), UTC.ymd(10000, 1, 1))));
+    assert!(months_around(UTC.ymd(9999, 12, 1), 0, 1).is_err());
+    assert!(months_around(UTC.ymd(2015, 1, 1), u32::MAX, u32::MAX).is_err());
+}
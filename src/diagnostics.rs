@@ -0,0 +1,62 @@
+//! Warnings for the caller to surface.
+//!
+//! The library doesn't print. Where it can go on despite a problem, like an
+//! event it can't read and leaves out, it records a warning in a
+//! `Diagnostics` collector instead, which is returned alongside the result
+//! for the caller to report however it reports everything else.
+
+use std::fmt;
+
+/// What a warning is about.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// An event couldn't be read and was left out.
+    SkippedEvent,
+    /// A date could be read in more than one way.
+    AmbiguousDate,
+    /// The locale isn't recognized, so a default was assumed.
+    UnknownLocale,
+    /// A date given for the calendar lies outside its range and isn't shown.
+    OutOfRange
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub kind:    Kind,
+    pub message: String
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Warnings in the order they were recorded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    warnings: Vec<Warning>
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics::default()
+    }
+
+    pub fn warn<S: Into<String>>(&mut self, kind: Kind, message: S) {
+        self.warnings.push(Warning { kind, message: message.into() });
+    }
+
+    /// Adds the warnings of `other` after these.
+    pub fn append(&mut self, other: Diagnostics) {
+        self.warnings.extend(other.warnings);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
@@ -0,0 +1,194 @@
+//! Editing of events files.
+//!
+//! Events are added, changed and removed a line at a time, so that comments,
+//! blank lines and the order of the other events are kept. Before a file is
+//! overwritten, its previous contents are copied next to it under a name
+//! with the time, e.g. `events.txt.20250314T091500.bak`, so that a change
+//! can be undone by copying the backup back.
+
+use calendar::date::Date;
+use calendar::events::{ is_ignored, parse_event, Event };
+use chrono::UTC;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Lines of an events file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventsFile {
+    path:  PathBuf,
+    lines: Vec<String>
+}
+
+/// Change of a line of an events file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    /// Number of the line, starting at 1.
+    pub line: usize,
+    /// The line before the change, None if it was added.
+    pub old:  Option<String>,
+    /// The line after the change, None if it was removed.
+    pub new:  Option<String>
+}
+
+impl Change {
+    /// Formats the change like a hunk of a unified diff.
+    pub fn diff(&self) -> String {
+        let mut result = format!("@@ line {} @@\n", self.line);
+
+        if let Some(ref old) = self.old {
+            result.push_str(&format!("-{}\n", old));
+        }
+        if let Some(ref new) = self.new {
+            result.push_str(&format!("+{}\n", new));
+        }
+
+        result
+    }
+}
+
+impl EventsFile {
+    /// Reads the file. A missing file means there are no events yet.
+    pub fn load(path: PathBuf) -> Result<EventsFile, String> {
+        let lines = match fs::read_to_string(&path) {
+            Ok(input) => input.lines().map(String::from).collect(),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e))
+        };
+
+        Ok(EventsFile { path, lines })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Appends the event.
+    pub fn add(&mut self, event: &Event) -> Change {
+        self.lines.push(event.to_string());
+        Change { line: self.lines.len(), old: None, new: Some(event.to_string()) }
+    }
+
+    /// Changes the event starting on the date with the description.
+    pub fn edit<F>(&mut self, date: Date, description: &str, change: F) -> Result<Change, String>
+        where F: FnOnce(&mut Event)
+    {
+        let (index, mut event) = self.find(date, description)?;
+        change(&mut event);
+
+        let old = ::std::mem::replace(&mut self.lines[index], event.to_string());
+        Ok(Change { line: index + 1, old: Some(old), new: Some(event.to_string()) })
+    }
+
+    /// Removes the event starting on the date with the description.
+    pub fn remove(&mut self, date: Date, description: &str) -> Result<Change, String> {
+        let (index, _) = self.find(date, description)?;
+        let old = self.lines.remove(index);
+
+        Ok(Change { line: index + 1, old: Some(old), new: None })
+    }
+
+    /// Returns the index of the line of the only event starting on the date
+    /// with the description, and the event.
+    fn find(&self, date: Date, description: &str) -> Result<(usize, Event), String> {
+        let mut found = Vec::new();
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if is_ignored(trimmed) { continue; }
+
+            let event = parse_event(trimmed).map_err(|e| format!("{}: line {}: {}",
+                                                                 self.path.display(),
+                                                                 index + 1, e))?;
+            if event.dates.start == date && event.description == description {
+                found.push((index, event));
+            }
+        }
+
+        let name = || format!("'{}' on {}", description, date.format("%Y-%m-%d"));
+
+        match found.len() {
+            0 => Err(format!("no event {}", name())),
+            1 => Ok(found.remove(0)),
+            _ => Err(format!("several events {} (lines {})", name(),
+                             found.iter()
+                                  .map(|&(index, _)| (index + 1).to_string())
+                                  .collect::<Vec<_>>()
+                                  .join(", ")))
+        }
+    }
+
+    /// Writes the lines back to the file, after backing up the previous
+    /// contents. Returns the path of the backup, if there was a file.
+    pub fn save(&self) -> Result<Option<PathBuf>, String> {
+        let error = |e: io::Error| format!("{}: {}", self.path.display(), e);
+
+        let backup = if self.path.exists() {
+            let backup = self.backup_path();
+            fs::copy(&self.path, &backup).map_err(error)?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        let mut output = self.lines.join("\n");
+        if !output.is_empty() {
+            output.push('\n');
+        }
+
+        fs::write(&self.path, output).map_err(error)?;
+        Ok(backup)
+    }
+
+    /// Path of a new backup: the file name followed by the time, and a
+    /// number if there already is a backup from the same second.
+    fn backup_path(&self) -> PathBuf {
+        let name = self.path.file_name().map(|name| name.to_string_lossy().into_owned())
+                                        .unwrap_or_default();
+        let time = UTC::now().format("%Y%m%dT%H%M%S");
+
+        (1..).map(|number| match number {
+                 1      => format!("{}.{}.bak", name, time),
+                 number => format!("{}.{}-{}.bak", name, time, number)
+             })
+             .map(|backup| self.path.with_file_name(backup))
+             .find(|backup| !backup.exists())
+             .unwrap_or_default()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn events_file_edits_lines_and_keeps_backups() {
+    use calendar::date::DateRange;
+    use chrono::TimeZone;
+    use std::env;
+
+    let dir  = env::temp_dir().join(format!("calendar-edit-test-{}", ::std::process::id()));
+    let path = dir.join("events.txt");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(&path, "# Holidays\n2015-03-14 = Pi day\n2015-03-14 = Pie\n").unwrap();
+
+    let mut file = EventsFile::load(path.clone()).unwrap();
+    let pi_day   = UTC.ymd(2015, 3, 14);
+
+    let change = file.edit(pi_day, "Pi day", |event| {
+        event.dates = DateRange::new(pi_day.succ(), pi_day.succ().succ());
+    });
+    assert_eq!(change.unwrap().diff(),
+               "@@ line 2 @@\n-2015-03-14 = Pi day\n+2015-03-15 = Pi day\n");
+    assert_eq!(file.remove(pi_day, "Pie").unwrap().line, 3);
+    assert!(file.remove(pi_day, "Pie").is_err());
+
+    let trip = Event::new(DateRange::new(UTC.ymd(2015, 7, 1), UTC.ymd(2015, 7, 2)), "Trip");
+    assert_eq!(file.add(&trip).line, 3);
+
+    let backup = file.save().unwrap().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(),
+               "# Holidays\n2015-03-15 = Pi day\n2015-07-01 = Trip\n");
+    assert_eq!(fs::read_to_string(backup).unwrap(),
+               "# Holidays\n2015-03-14 = Pi day\n2015-03-14 = Pie\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
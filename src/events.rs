@@ -0,0 +1,425 @@
+//! Events read from an events file.
+//!
+//! The file contains one event per line in the form `date = description`,
+//! where the date is in the `YYYY-MM-DD` format, or the first and last day
+//! of an event taking several days, e.g. `2015-07-20..2015-07-24`, and the
+//! description may be enclosed in double quotes. Recurring events have a recurrence rule between
+//! the date and the equals sign, e.g.
+//! `2015-01-05 FREQ=WEEKLY;BYDAY=MO = Team meeting`, or a named rule, e.g.
+//! `2015-01-01 last-business-day-of-month = Payroll`. Business days of named
+//! rules only skip weekends here, as events don't know about holidays.
+//! Birthdays and anniversaries, e.g. `birthday 1983-06-04 "Alice"` or
+//! `anniversary 2010-09-12 "Wedding"`, recur every year, on February 28 in
+//! common years if they fall on February 29. Blank lines and
+//! lines starting with `#` are ignored. Events written for remind or when
+//! are read as well, see the `remind` module.
+
+use chrono::{ Datelike, TimeZone, UTC };
+
+use date::{ anniversaries, DateRange };
+use diagnostics::{ Diagnostics, Kind };
+use parse::parse_date;
+use recurrence::Recurrence;
+use remind;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// An event taking place on one or more consecutive days, possibly
+/// repeatedly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Event {
+    /// Days of the (first occurrence of the) event.
+    pub dates:       DateRange,
+    pub description: String,
+    pub recurrence:  Option<Recurrence>,
+    /// Set for birthdays and anniversaries, which recur every year.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none", default))]
+    pub yearly:      Option<Yearly>
+}
+
+/// An event recurring every year, with the year it first took place in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Yearly {
+    Birthday(i32),
+    Anniversary(i32)
+}
+
+impl Yearly {
+    fn keyword(self) -> &'static str {
+        match self {
+            Yearly::Birthday(_)    => "birthday",
+            Yearly::Anniversary(_) => "anniversary"
+        }
+    }
+
+    fn since(self) -> i32 {
+        match self {
+            Yearly::Birthday(year) | Yearly::Anniversary(year) => year
+        }
+    }
+}
+
+impl Event {
+    pub fn new(dates: DateRange, description: &str) -> Event {
+        Event { dates, description: description.to_string(), recurrence: None, yearly: None }
+    }
+
+    /// Returns the event with the age of a birthday, or the number of an
+    /// anniversary, in the year of its dates after the description, e.g.
+    /// `Alice (32)`. Other events are returned as they are.
+    pub fn counted(self) -> Event {
+        match self.yearly {
+            Some(yearly) => Event {
+                description: format!("{} ({})", self.description,
+                                     self.dates.start.year() - yearly.since()),
+                ..self
+            },
+            None => self
+        }
+    }
+
+    /// Returns the days of all occurrences of the event that take place at
+    /// least partially in the range.
+    pub fn occurrences(&self, range: DateRange) -> Vec<DateRange> {
+        let first  = self.dates.start;
+        let length = self.dates.end - first;
+        let starts = match self.recurrence {
+            // Birthdays on February 29 are celebrated on February 28 in
+            // common years, which the rule would skip.
+            Some(_) if self.yearly.is_some() => {
+                let years = first.year().max(range.start.year() - 1)..=range.end.year();
+                anniversaries(first.month(), first.day(), years)
+                    .map(|(year, date)| date.unwrap_or_else(|| UTC.ymd(year, 2, 28)))
+                    .collect()
+            },
+            // Occurrences starting before the range may still last into it.
+            Some(ref rule) => rule.dates_in(first, DateRange::new(range.start - length, range.end),
+                                            |_| false),
+            None           => vec![first]
+        };
+
+        starts.into_iter()
+              .map(|start| DateRange::new(start, start + length))
+              .filter(|dates| dates.overlaps(range))
+              .collect()
+    }
+}
+
+impl fmt::Display for Event {
+    /// Writes the event as a line of an events file.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(yearly) = self.yearly {
+            return write!(f, "{} {} \"{}\"", yearly.keyword(),
+                          self.dates.start.format("%Y-%m-%d"), self.description);
+        }
+
+        write!(f, "{}", self.dates.start.format("%Y-%m-%d"))?;
+        if self.dates.days() > 1 {
+            write!(f, "..{}", self.dates.end.pred().format("%Y-%m-%d"))?;
+        }
+
+        if let Some(ref rule) = self.recurrence {
+            write!(f, " {}", rule)?;
+        }
+
+        write!(f, " = {}", self.description)
+    }
+}
+
+/// Returns every occurrence of the events taking place in the range as a
+/// separate, non-recurring event.
+pub fn expand(events: &[Event], range: DateRange) -> Vec<Event> {
+    events.iter()
+          .flat_map(|event| event.occurrences(range)
+                                 .into_iter()
+                                 .map(move |dates| Event {
+                                     yearly: event.yearly,
+                                     ..Event::new(dates, &event.description)
+                                 }))
+          .collect()
+}
+
+/// Parses the contents of an events file.
+pub fn parse(input: &str) -> Result<Vec<Event>, String> {
+    parse_events(input, None)
+}
+
+/// Parses the events like `parse`, but leaves out the reminders of remind
+/// and events of when that can't be read, like `REM Fri 13` or reminders
+/// with `UNTIL`, with a warning. Files written for those programs often use
+/// more of them than is supported.
+pub fn parse_lenient(input: &str, diagnostics: &mut Diagnostics) -> Result<Vec<Event>, String> {
+    parse_events(input, Some(diagnostics))
+}
+
+/// Parses the events, failing on the first line that can't be read unless
+/// it's one of remind or when and there are `diagnostics` to record it in.
+fn parse_events(input: &str, mut diagnostics: Option<&mut Diagnostics>)
+    -> Result<Vec<Event>, String>
+{
+    let mut result = Vec::new();
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if is_ignored(line) { continue; }
+
+        let foreign = remind::is_reminder(line) || remind::is_when_event(line);
+
+        match (parse_event(line), diagnostics.as_mut()) {
+            (Ok(event), _) => result.push(event),
+            (Err(e), Some(diagnostics)) if foreign => {
+                diagnostics.warn(Kind::SkippedEvent,
+                                 format!("line {}: skipped '{}': {}", number + 1, line, e));
+            },
+            (Err(e), _) => return Err(format!("line {}: {}", number + 1, e))
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads and parses an events file.
+pub fn load(path: &Path) -> Result<Vec<Event>, String> {
+    let mut input = String::new();
+
+    File::open(path).and_then(|mut file| file.read_to_string(&mut input))
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    parse(&input).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Reads and parses an events file like `load`, but leaves out the
+/// reminders that can't be read, with a warning.
+pub fn load_lenient(path: &Path, diagnostics: &mut Diagnostics) -> Result<Vec<Event>, String> {
+    let mut input = String::new();
+
+    File::open(path).and_then(|mut file| file.read_to_string(&mut input))
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut found  = Diagnostics::new();
+    let     events = parse_lenient(&input, &mut found)
+                         .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    for warning in found.warnings() {
+        diagnostics.warn(warning.kind, format!("{}: {}", path.display(), warning));
+    }
+
+    Ok(events)
+}
+
+/// Whether the trimmed line of an events file is no event: blank, a comment,
+/// or a command of remind other than a reminder.
+pub fn is_ignored(line: &str) -> bool {
+    line.is_empty() || line.starts_with('#') || remind::is_command(line)
+}
+
+/// Parses a line of an events file that isn't ignored.
+pub fn parse_event(line: &str) -> Result<Event, String> {
+    const EXPECTED: &str = "expected 'date = description'";
+
+    if remind::is_reminder(line) {
+        return remind::parse_reminder(line);
+    }
+    if remind::is_when_event(line) {
+        return remind::parse_when_event(line);
+    }
+
+    match line.split_once(char::is_whitespace) {
+        Some(("birthday", rest))    => return parse_yearly(rest, Yearly::Birthday),
+        Some(("anniversary", rest)) => return parse_yearly(rest, Yearly::Anniversary),
+        _                           => ()
+    }
+
+    let end  = line.find(|c: char| c == '=' || c.is_whitespace())
+                   .ok_or(EXPECTED)?;
+    let dates = parse_days(&line[..end])?;
+    let rest  = line[end..].trim_start();
+
+    // Anything but an RRULE only is a rule if the equals sign follows it.
+    let rule_end = rest.find(char::is_whitespace)
+                       .filter(|&end| !rest.starts_with('=')
+                                      && rest[end..].trim_start().starts_with('='));
+
+    let (recurrence, rest) = if rest.starts_with("FREQ=") || rest.starts_with("RRULE:") {
+        let end = rest.find(char::is_whitespace).ok_or(EXPECTED)?;
+        (Some(rest[..end].parse()?), rest[end..].trim_start())
+    } else if let Some(end) = rule_end {
+        (Some(rest[..end].parse()?), rest[end..].trim_start())
+    } else {
+        (None, rest)
+    };
+
+    let description = unquote(rest.strip_prefix('=').ok_or(EXPECTED)?);
+
+    Ok(Event { recurrence, ..Event::new(dates, description) })
+}
+
+/// Parses the day of an event, or its first and last day, e.g.
+/// `2015-07-20..2015-07-24`.
+fn parse_days(input: &str) -> Result<DateRange, String> {
+    let (first, last) = match input.split_once("..") {
+        Some((first, last)) => (parse_date(first)?, parse_date(last)?),
+        None                => { let date = parse_date(input)?; (date, date) }
+    };
+
+    if last < first {
+        return Err(format!("invalid days: '{}'", input));
+    }
+
+    Ok(DateRange::new(first, last.succ()))
+}
+
+/// Parses the date and description of a birthday or anniversary, given the
+/// kind of it for the year of the date.
+fn parse_yearly(input: &str, kind: fn(i32) -> Yearly) -> Result<Event, String> {
+    let input = input.trim_start();
+    let end   = input.find(char::is_whitespace).unwrap_or(input.len());
+    let date  = parse_date(&input[..end])?;
+    let rest  = input[end..].trim_start();
+
+    let description = unquote(rest.strip_prefix('=').unwrap_or(rest));
+    if description.is_empty() {
+        return Err(format!("expected '{} date \"description\"'", kind(0).keyword()));
+    }
+
+    Ok(Event {
+        recurrence: Some("FREQ=YEARLY".parse()?),
+        yearly:     Some(kind(date.year())),
+        ..Event::new(DateRange::new(date, date.succ()), description)
+    })
+}
+
+/// Trims the description and removes the double quotes around it.
+fn unquote(description: &str) -> &str {
+    let description = description.trim();
+
+    if description.len() >= 2 && description.starts_with('"') && description.ends_with('"') {
+        &description[1..description.len() - 1]
+    } else {
+        description
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn parse_reads_events() {
+    let input = "
+        # Some comment
+        2015-03-14 = \"Pi day\"
+        2015-12-24 = Christmas Eve
+    ";
+
+    let expected = vec![
+        Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)), "Pi day"),
+        Event::new(DateRange::new(UTC.ymd(2015, 12, 24), UTC.ymd(2015, 12, 25)), "Christmas Eve")
+    ];
+
+    assert_eq!(parse(input), Ok(expected));
+}
+
+#[test]
+fn parse_reads_events_taking_several_days() {
+    let events = parse("2015-07-20..2015-07-24 = Holiday").unwrap();
+
+    assert_eq!(events[0].dates, DateRange::new(UTC.ymd(2015, 7, 20), UTC.ymd(2015, 7, 25)));
+    assert_eq!(events[0].to_string(), "2015-07-20..2015-07-24 = Holiday");
+    assert_eq!(parse("2015-07-24..2015-07-20 = Holiday"),
+               Err("line 1: invalid days: '2015-07-24..2015-07-20'".to_string()));
+}
+
+#[test]
+fn parse_reports_line_of_invalid_events() {
+    assert_eq!(parse("2015-03-14 = Pi day\n\n2015-03-14 Pi day"),
+               Err("line 3: expected 'date = description'".to_string()));
+    assert_eq!(parse("2015-02-30 = Nope"),
+               Err("line 1: invalid date: '2015-02-30'".to_string()));
+}
+
+#[test]
+fn parse_reads_recurring_events() {
+    let events = parse("2015-01-05 FREQ=WEEKLY;BYDAY=MO = Team meeting").unwrap();
+
+    assert_eq!(events[0].recurrence, Some("FREQ=WEEKLY;BYDAY=MO".parse().unwrap()));
+    assert_eq!(events[0].description, "Team meeting");
+    assert_eq!(events[0].to_string(), "2015-01-05 FREQ=WEEKLY;BYDAY=MO = Team meeting");
+}
+
+#[test]
+fn parse_reads_events_recurring_by_named_rules() {
+    let events = parse("2015-01-01 last-business-day-of-month = Payroll").unwrap();
+    let range  = DateRange::new(UTC.ymd(2015, 5, 1), UTC.ymd(2015, 6, 1));
+
+    assert_eq!(events[0].recurrence, Some("last-business-day-of-month".parse().unwrap()));
+    assert_eq!(events[0].occurrences(range),
+               vec![DateRange::new(UTC.ymd(2015, 5, 29), UTC.ymd(2015, 5, 30))]);
+    assert_eq!(events[0].to_string(), "2015-01-01 last-business-day-of-month = Payroll");
+    assert_eq!(parse("2015-01-01 payday = Payroll"),
+               Err("line 1: unknown rule: 'payday' (expected e.g. last-business-day-of-month \
+                    or first-monday-of-quarter)".to_string()));
+}
+
+#[test]
+fn occurrences_returns_days_of_occurrences_in_range() {
+    let event = Event {
+        recurrence: Some("FREQ=WEEKLY;INTERVAL=2".parse().unwrap()),
+        ..Event::new(DateRange::new(UTC.ymd(2015, 1, 3), UTC.ymd(2015, 1, 5)), "Trip")
+    };
+    let range = DateRange::new(UTC.ymd(2015, 1, 18), UTC.ymd(2015, 2, 1));
+
+    assert_eq!(event.occurrences(range), vec![
+        DateRange::new(UTC.ymd(2015, 1, 17), UTC.ymd(2015, 1, 19)),
+        DateRange::new(UTC.ymd(2015, 1, 31), UTC.ymd(2015, 2, 2))
+    ]);
+}
+
+#[test]
+fn parse_reads_birthdays_and_anniversaries() {
+    let events = parse("birthday 1983-06-04 \"Alice\"\nanniversary 2010-09-12 = Wedding").unwrap();
+    let range  = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1));
+
+    assert_eq!(events[0].yearly, Some(Yearly::Birthday(1983)));
+    assert_eq!(events[0].to_string(), "birthday 1983-06-04 \"Alice\"");
+    assert_eq!(events[1].description, "Wedding");
+
+    let counted = expand(&events, range).into_iter()
+                                        .map(|event| event.counted().description)
+                                        .collect::<Vec<_>>();
+    assert_eq!(counted, ["Alice (32)", "Wedding (5)"]);
+    assert_eq!(parse("birthday 1983-06-04"),
+               Err("line 1: expected 'birthday date \"description\"'".to_string()));
+}
+
+#[test]
+fn occurrences_moves_birthdays_on_february_29_in_common_years() {
+    let events = parse("birthday 2000-02-29 \"Leap\"").unwrap();
+    let range  = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2017, 1, 1));
+
+    assert_eq!(events[0].occurrences(range),
+               [DateRange::new(UTC.ymd(2015, 2, 28), UTC.ymd(2015, 3, 1)),
+                DateRange::new(UTC.ymd(2016, 2, 29), UTC.ymd(2016, 3, 1))]);
+    assert_eq!(expand(&events, range)[0].clone().counted().description, "Leap (15)");
+}
+
+#[test]
+fn parse_lenient_skips_unsupported_reminders() {
+    let input = "REM Fri 13 MSG Unlucky\nREM 14 Mar 2015 MSG Pi day\nREM 1 *7 MSG Weekly\n\
+                 REM 1 UNTIL 2016-01-01 MSG Monthly";
+    let mut diagnostics = Diagnostics::new();
+    let events = parse_lenient(input, &mut diagnostics).unwrap();
+
+    assert_eq!(events.iter().map(|event| &*event.description).collect::<Vec<_>>(), ["Pi day"]);
+    assert_eq!(diagnostics.warnings().len(), 3);
+    assert_eq!(diagnostics.warnings()[0].kind, Kind::SkippedEvent);
+    assert!(diagnostics.warnings()[0].message.starts_with("line 1: skipped 'REM Fri 13"));
+
+    // Lines of the events file itself still have to be read.
+    assert!(parse_lenient("2015-03-14 = Pi day\n2015-13-01 = Oops", &mut diagnostics).is_err());
+    assert!(parse(input).is_err());
+}
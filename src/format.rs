@@ -1,56 +1,1235 @@
 //! Calendar formatting utilities.
 
-use date::{ weekday, ByWeek, Date, DateRange };
-use chrono::Datelike;
-use std::iter::{ once, repeat, Chain, Map, Once, Repeat, Take };
+use advanced_iterator::AdvancedIterator;
+use calendars::System;
+use date::{ utc_offset, weekday, Date, DateRange, Reckoning, WeekNumbering, Zone };
+use events::Event;
+use moon::{ self, Phase };
+use parse::parse_title_format;
+use chrono::{ Datelike, TimeZone, UTC, Weekday };
+use style::{ Scale, Style };
+use sun::{ daylight, Daylight, Location };
+use std::collections::{ HashMap, HashSet };
+use std::io::{ self, Write };
+use std::iter::{ once, repeat };
+use std::panic;
+use std::str::FromStr;
+use std::thread;
+use width::Width;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
-type FnFormatWeek = fn(DateRange) -> String;
-pub type MonthLayout =
-    Chain<
-        Once<String>,
-        Chain<
-            Map<ByWeek, FnFormatWeek>,
-            Take<Repeat<String>>>>;
+/// Output format of the calendar.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "lowercase"))]
+pub enum Format {
+    Text,
+    Html,
+    Markdown,
+    Svg,
+    /// One line per day, for terminals too narrow for a month.
+    Linear,
+    /// One line per day with its events, see the `agenda` module.
+    Agenda,
+    /// One plain line per day with its holidays and events, see the `list`
+    /// module.
+    List,
+    /// Output of POSIX `cal`. Not parsed from a name, as it is a drop-in
+    /// replacement rather than a format of its own.
+    Cal
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text"     => Ok(Format::Text),
+            "html"     => Ok(Format::Html),
+            "markdown" => Ok(Format::Markdown),
+            "svg"      => Ok(Format::Svg),
+            "linear"   => Ok(Format::Linear),
+            "agenda"   => Ok(Format::Agenda),
+            "list"     => Ok(Format::List),
+            _          => Err(format!("unknown format: '{}'", s))
+        }
+    }
+}
+
+/// `strftime` format of month titles, like `%b %Y` for `Mar 2015`. Only
+/// formats of dates are accepted, as formatting the time of day of a date
+/// fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TitleFormat(String);
+
+impl Default for TitleFormat {
+    /// The name of the month.
+    fn default() -> TitleFormat {
+        TitleFormat("%B".to_string())
+    }
+}
+
+impl FromStr for TitleFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TitleFormat, String> {
+        parse_title_format(s).map(TitleFormat)
+    }
+}
+
+/// Renders the calendar in some output format.
+pub trait Renderer {
+    /// Renders the months in the range, followed by a legend listing the
+    /// given events unless there are none.
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String;
 
-pub fn layout_month(month: DateRange) -> MonthLayout {
-    let week_count   = month.by_week().count();
-    let title        = once(month_title(month.start));
-    let padding_item = repeat(" ").take(22).collect::<String>();
-    let padding      = repeat(padding_item).take(6 - week_count);
+    /// Writes the calendar `render` returns to `out`. Formats whose output
+    /// can grow long write it piece by piece, so that it appears as it is
+    /// rendered and rendering stops once writing fails, e.g. when piped
+    /// into `head`.
+    fn render_to(&self, out: &mut dyn Write, range: DateRange, options: &Options,
+                 legend: &[Event]) -> io::Result<()>
+    {
+        out.write_all(self.render(range, options, legend).as_bytes())
+    }
+}
 
-    title.chain(month.by_week().map(format_week as FnFormatWeek).chain(padding))
+/// Renders into a string with the renderer's `render_to`, for the formats
+/// that stream their output.
+pub fn render_to_string<R: Renderer>(renderer: &R, range: DateRange, options: &Options,
+                                 legend: &[Event]) -> String
+{
+    let mut result = Vec::new();
+    renderer.render_to(&mut result, range, options, legend)
+            .expect("writing to a vector can't fail");
+    String::from_utf8(result).expect("rendered calendars are UTF-8")
 }
 
-fn format_day(date: Date) -> String {
-    format!("{: >3}", date.day())
+/// Writes the events of the legend, one per line after a blank line.
+pub fn write_legend(out: &mut dyn Write, legend: &[Event]) -> io::Result<()> {
+    if !legend.is_empty() {
+        out.write_all(b"\n")?;
+    }
+
+    for event in legend {
+        writeln!(out, "{}  {}", format_dates(event.dates), event.description)?;
+    }
+
+    Ok(())
+}
+
+/// Plain-text calendar for the terminal, with the months laid out side by
+/// side.
+#[derive(Copy, Clone, Debug)]
+pub struct Text {
+    /// Number of months side by side, at least one.
+    pub months_per_line: usize,
+    /// Whether whole years get a centered banner with the year.
+    pub banner:          bool
+}
+
+impl Text {
+    /// Lays out the months of the range, `months_per_line` side by side, as
+    /// lines of months produced one after the other. The month of a break
+    /// starts a new line, under a divider with the label of the break. The
+    /// `footnotes` taking place in the months are laid out below them.
+    fn render_months<'a>(&self, range: DateRange, options: &'a Options,
+                         footnotes: &'a [Event]) -> impl Iterator<Item = String> + 'a
+    {
+        let months_per_line = self.months_per_line.max(1);
+        let layout = if options.vertical { layout_month_vertical } else { layout_month };
+        let width  = months_per_line * month_width(options);
+
+        let mut sections: Vec<(Vec<&str>, Vec<DateRange>)> = vec![(Vec::new(), Vec::new())];
+        for month in months(range, options) {
+            let labels = options.breaks.iter()
+                                       .filter(|(date, _)| month.contains(*date))
+                                       .map(|(_, label)| label.as_str())
+                                       .collect::<Vec<_>>();
+            if !labels.is_empty() {
+                sections.push((labels, Vec::new()));
+            }
+            sections.last_mut().unwrap().1.push(month);
+        }
+
+        // Compact months are only as high as they need to be, so the months
+        // side by side are aligned at the top.
+        sections.into_iter()
+                .filter(|(_, months)| !months.is_empty())
+                .flat_map(move |(labels, months)| {
+                    let dividers = labels.into_iter()
+                                         .map(move |label| divider(label, width, &options.width));
+                    let cells = months.into_iter().map(move |month| {
+                        layout(month, options).chain(footnote_lines(month, footnotes, options))
+                    });
+                    let blank = " ".repeat(month_width(options));
+                    dividers.chain(layout_grid(cells, months_per_line, blank)
+                                       .map(|line| line.concat()))
+                })
+    }
+}
+
+impl Renderer for Text {
+    /// Ranges spanning several years are split into years, each starting on
+    /// a new line. With `banner`, every year is preceded by a centered line
+    /// with the year, and so is a single whole year. Years start with the
+    /// month `year_start`.
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        render_to_string(self, range, options, legend)
+    }
+
+    /// Writes a line of months at a time.
+    fn render_to(&self, out: &mut dyn Write, range: DateRange, options: &Options,
+                 legend: &[Event]) -> io::Result<()>
+    {
+        self.render_years_parallel(out, range, options, legend, 1)
+    }
 }
 
-fn format_week(week: DateRange) -> String {
-    let pad_left  = weekday(week.start) * 3;
-    let pad_right = (6 - weekday(week.end.pred())) * 3;
+impl Text {
+    /// Writes the calendar like `render_to`, rendering up to `jobs` years
+    /// at a time in threads of their own. The years are written in order
+    /// as soon as all of a batch are rendered, so the output is the same
+    /// as with a single job.
+    pub fn render_years_parallel(&self, out: &mut dyn Write, range: DateRange,
+                                 options: &Options, legend: &[Event], jobs: usize)
+        -> io::Result<()>
+    {
+        let year        = |date| year_of(date, options);
+        let single_year = year(range.start) == year(range.end.pred());
+        let whole_year  = range == year_dates(year(range.start), options);
+        let (footnotes, legend) = if options.footnotes {
+            (legend, &[][..])
+        } else {
+            (&[][..], legend)
+        };
+
+        if single_year && !(whole_year && self.banner) {
+            self.render_months(range, options, footnotes).write_join(out, "\n")?;
+        } else if jobs <= 1 {
+            for (index, dates) in range.group_by(|&date| year_of(date, options)).enumerate() {
+                if index > 0 {
+                    out.write_all(b"\n\n")?;
+                }
+                self.render_year(dates, options, footnotes).write_join(out, "\n")?;
+            }
+        } else {
+            let years = range.group_by(|&date| year_of(date, options)).collect::<Vec<_>>();
+
+            for (index, batch) in years.chunks(jobs).enumerate() {
+                let rendered = thread::scope(|scope| {
+                    let threads = batch.iter()
+                                       .map(|&dates| scope.spawn(move || {
+                                           self.render_year(dates, options, footnotes)
+                                               .join("\n")
+                                       }))
+                                       .collect::<Vec<_>>();
+                    threads.into_iter()
+                           .map(|thread| thread.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                           .collect::<Vec<_>>()
+                });
+
+                if index > 0 {
+                    out.write_all(b"\n\n")?;
+                }
+                rendered.iter().write_join(out, "\n\n")?;
+            }
+        }
+        out.write_all(b"\n")?;
+
+        write_legend(out, legend)
+    }
+
+    /// Lines of the months of a year, preceded by the year with `banner`.
+    fn render_year<'a>(&self, dates: DateRange, options: &'a Options, footnotes: &'a [Event])
+        -> impl Iterator<Item = String> + 'a
+    {
+        let width  = self.months_per_line.max(1) * month_width(options);
+        let banner = Some(year_title(year_of(dates.start, options), options))
+                         .filter(|_| self.banner)
+                         .map(|title| options.width.center(&title, width));
+
+        banner.into_iter().chain(self.render_months(dates, options, footnotes))
+    }
+}
+
+/// Arranges the items, each a column of lines, in rows of `columns` items:
+/// the lines of the items of a row side by side, items shorter than others
+/// in their row padded with `blank` lines, and the rows one below the
+/// other. Every line of the grid is given as the lines of the items side
+/// by side, for the formats to join their own way, and a last row of fewer
+/// items has fewer of them. The rows are arranged as the lines are taken,
+/// so that long grids can be written as they are laid out. There is at least
+/// one column.
+pub fn layout_grid<I, C, T>(items: I, columns: usize, blank: T) -> impl Iterator<Item = Vec<T>>
+    where I: IntoIterator<Item = C>, C: IntoIterator<Item = T>, T: Clone
+{
+    items.into_iter()
+         .chunk(columns.max(1))
+         .flat_map(move |row| {
+             row.map(IntoIterator::into_iter)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .transpose_longest(blank.clone())
+                .map(|line| line.collect::<Vec<_>>())
+         })
+}
+
+/// Arranges the months in rows of `columns` months like `layout_grid`, for
+/// the formats that lay out a row of months at a time.
+pub fn month_rows(months: Vec<DateRange>, columns: usize) -> impl Iterator<Item = Vec<DateRange>> {
+    layout_grid(months.into_iter().map(|month| once(Some(month))), columns, None)
+        .map(|row| row.into_iter().flatten().collect())
+}
+
+/// Lays out the events taking place in the month below it, a line for each
+/// with its days in the month, e.g. `20-26 Trip`, wrapped to the width of
+/// the month. Empty if there are none.
+fn footnote_lines(month: DateRange, events: &[Event], options: &Options) -> Vec<String> {
+    let width = month_width(options);
+    let text  = width.saturating_sub(options.layout.month_gap).max(1);
+
+    events.iter()
+          .filter_map(|event| event.dates.intersect(month).map(|days| (days, event)))
+          .flat_map(|(days, event)| {
+              let first = day_number(days.start, options);
+              let last  = day_number(days.end.pred(), options);
+              let label = if first == last {
+                  format!("{:>2}", first)
+              } else {
+                  format!("{:>2}-{}", first, last)
+              };
+
+              wrap(&format!("{} {}", label, event.description), text,
+                   label.len() + 1, &options.width)
+          })
+          .map(|line| fit(&line, width, &options.width))
+          .collect()
+}
+
+/// Breaks the text into lines at most `width` columns wide at spaces, or
+/// within words longer than a line. Lines after the first are indented by
+/// `indent` columns, unless that leaves no room. Spaces the text starts
+/// with are kept.
+fn wrap(text: &str, width: usize, indent: usize, measure: &Width) -> Vec<String> {
+    let indent    = if indent * 2 > width { 0 } else { indent };
+    let lead      = &text[..text.len() - text.trim_start().len()];
+    let mut lines = Vec::new();
+    let mut line  = String::new();
+
+    for (index, word) in text.split_whitespace().enumerate() {
+        let word = if index == 0 { format!("{}{}", lead, word) } else { word.to_string() };
+        let word = &*word;
+        let start = if lines.is_empty() { 0 } else { indent };
+        let used  = measure.of(&line);
+
+        if used > start && used + 1 + measure.of(word) <= width {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+        if used > start {
+            lines.push(line);
+            line = " ".repeat(indent);
+        }
+
+        for c in word.chars() {
+            let start = if lines.is_empty() { 0 } else { indent };
+            let used  = measure.of(&line);
+
+            if used > start && used + measure.of_char(c) > width {
+                lines.push(line);
+                line = " ".repeat(indent);
+            }
+            line.push(c);
+        }
+    }
+
+    lines.push(line);
+    lines
+}
 
-    let mut result = String::with_capacity(22);
-    result.extend(repeat(" ").take(pad_left as usize));
-    result.extend(week.map(format_day));
-    result.extend(repeat(" ").take(pad_right as usize));
-    result.push_str(" ");
+/// Line across `width` columns with the label of a break.
+fn divider(label: &str, width: usize, measure: &Width) -> String {
+    let text = format!("── {} ", label);
+    let rest = width.saturating_sub(measure.of(&text));
+
+    text + &"─".repeat(rest)
+}
+
+/// Geometry of text output. More fields may be added, so start from
+/// `default()` or `compact()` and set the fields to change.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LayoutConfig {
+    /// Width of the cell of a day, in columns. At least 2, the width of the
+    /// day number. Days with events are only marked in cells of 3 or more,
+    /// and cells of 2 are separated by a column so that numbers don't touch.
+    pub day_width: usize,
+    /// Columns between months laid out side by side.
+    pub month_gap: usize,
+    /// Whether blank weeks at the end of months are left out, rather than
+    /// padding every month to six weeks.
+    pub trim_weeks: bool
+}
+
+impl LayoutConfig {
+    /// Narrow layout for small panes.
+    pub fn compact() -> LayoutConfig {
+        LayoutConfig { day_width: 2, month_gap: 1, trim_weeks: true }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> LayoutConfig {
+        LayoutConfig { day_width: 3, month_gap: 1, trim_weeks: false }
+    }
+}
+
+/// Options controlling how the days of a month are formatted. More fields
+/// may be added, so start from `default()` and set the fields to change.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Options {
+    /// First day of the week. Ignored if `workweek` is set.
+    pub week_start: Weekday,
+    /// How weeks are numbered. Weeks starting on the first day of the
+    /// numbering are split like its weeks, e.g. at New Year in US weeks.
+    pub week_numbering: Option<WeekNumbering>,
+    /// Whether only Monday to Friday are shown, with weeks starting on
+    /// Monday.
+    pub workweek: bool,
+    /// Whether the weeks of a month are listed latest first.
+    pub flip_weeks: bool,
+    /// Whether the weeks of a month are columns rather than rows, like
+    /// `ncal` shows them. Only affects text output.
+    pub vertical: bool,
+    /// Whether days are numbered by the day of the year (1–366) rather than
+    /// of the month, like `cal -j` shows them.
+    pub julian: bool,
+    /// Whether days with a new, full or quarter moon are marked with its
+    /// symbol after the day number. Only affects text output.
+    pub moon: bool,
+    /// First month of the year, e.g. 4 for fiscal years from April to
+    /// March. Ranges are split into years and whole years are titled
+    /// accordingly.
+    pub year_start: u32,
+    /// Geometry of text output.
+    pub layout: LayoutConfig,
+    /// Measurement of text width, for padding text output.
+    pub width: Width,
+    /// Style of Saturdays and Sundays.
+    pub weekend: Style,
+    /// Style of holidays. Takes precedence over `weekend`.
+    pub holiday: Style,
+    /// Dates of holidays.
+    pub holidays: HashSet<Date>,
+    /// Names of the holidays on each date. Only affects list output.
+    pub holiday_names: HashMap<Date, Vec<String>>,
+    /// Style of highlighted dates. Takes precedence over `holiday`.
+    pub highlight: Style,
+    /// Dates to highlight.
+    pub highlights: HashSet<Date>,
+    /// How often dates occur. Days that do are colored by the frequency in
+    /// `heat_scale`, like a contribution graph, taking precedence over
+    /// `holiday`. Only affects text output.
+    pub heat: Frequencies,
+    pub heat_scale: Scale,
+    /// Styles of single dates, like the days off of the people in an
+    /// overlay. Take precedence over `heat`.
+    pub marks: HashMap<Date, Style>,
+    /// Dates with events. These are marked with an asterisk.
+    pub events: HashSet<Date>,
+    /// Whether the legend is laid out below every month, wrapped to its
+    /// width, rather than listed after the calendar. Only affects text
+    /// output.
+    pub footnotes: bool,
+    /// Days of events taking several days. In text output, they're
+    /// underlined like a bar from the first to the last of them in every
+    /// week, and only marked on the first.
+    pub bars: Bars,
+    /// Labeled breaks, like the start of a term. The month of each starts a
+    /// new line under a divider with the label. Only affects text output.
+    pub breaks: Vec<(Date, String)>,
+    /// Calendar the days are named in, e.g. the Julian calendar up to the
+    /// reform of 1752, which leaves out the skipped days of its month.
+    pub reckoning: Reckoning,
+    /// Calendar whose day of every date is shown on a line below each week,
+    /// or beside the day in linear output. Only affects text output, and
+    /// not that of vertical months.
+    pub overlay: Option<System>,
+    /// Where to show when the sun is up: on every day in list and agenda
+    /// output, and for the first day of every week on a line below it in
+    /// text output, except in vertical months. Days on which the clocks
+    /// change in `zone` are marked as well.
+    pub location: Option<Location>,
+    /// Time zone of the sunrise and sunset.
+    pub zone: Zone,
+    /// Format of the title of every month in text output, in the calendar
+    /// of `reckoning`.
+    pub title_format: TitleFormat,
+    /// Whether months in text output have a row of the weekday names below
+    /// the title, abbreviated to fit the day cells. Vertical months always
+    /// name the weekdays.
+    pub weekday_header: bool,
+    /// Today's date. Only highlighted in HTML output.
+    pub today: Option<Date>
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            week_start: Weekday::Mon,
+            week_numbering: None,
+            workweek:   false,
+            flip_weeks: false,
+            vertical:   false,
+            julian:     false,
+            moon:       false,
+            year_start: 1,
+            layout:     LayoutConfig::default(),
+            width:      Width::default(),
+            weekend:    Style::default(),
+            holiday:    Style::default(),
+            holidays:   HashSet::new(),
+            holiday_names: HashMap::new(),
+            highlight:  Style::default(),
+            highlights: HashSet::new(),
+            heat:       Frequencies::default(),
+            heat_scale: Scale::default(),
+            marks:      HashMap::new(),
+            events:     HashSet::new(),
+            footnotes:  false,
+            bars:       Bars::default(),
+            breaks:     Vec::new(),
+            reckoning:  Reckoning::Gregorian,
+            overlay:    None,
+            location:   None,
+            zone:       Zone::Utc,
+            title_format:   TitleFormat::default(),
+            weekday_header: false,
+            today:      None
+        }
+    }
+}
+
+/// Number of times each date occurs in a list of dates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Frequencies {
+    counts: HashMap<Date, u32>,
+    max:    u32
+}
+
+impl Frequencies {
+    pub fn count<I: IntoIterator<Item = Date>>(dates: I) -> Frequencies {
+        let mut counts = HashMap::new();
+        for date in dates {
+            *counts.entry(date).or_insert(0) += 1;
+        }
+
+        let max = counts.values().cloned().max().unwrap_or(0);
+        Frequencies { counts, max }
+    }
+
+    pub fn get(&self, date: Date) -> u32 {
+        self.counts.get(&date).cloned().unwrap_or(0)
+    }
+
+    /// The highest frequency.
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+}
+
+/// Days of events taking several days, indexed by date.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bars {
+    days:      HashSet<Date>,
+    /// Days of events that take the day before as well.
+    continued: HashSet<Date>
+}
+
+impl Bars {
+    pub fn new<I: IntoIterator<Item = DateRange>>(events: I) -> Bars {
+        let mut bars = Bars::default();
+        for dates in events {
+            bars.add(dates);
+        }
+        bars
+    }
+
+    pub fn add(&mut self, dates: DateRange) {
+        self.days.extend(dates);
+        self.continued.extend(dates.skip(1));
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.days.contains(&date)
+    }
+
+    /// Whether an event takes both the day and the day before.
+    pub fn continues(&self, date: Date) -> bool {
+        self.continued.contains(&date)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+}
+
+/// First day of the displayed weeks.
+pub fn first_day(options: &Options) -> Weekday {
+    if options.workweek { Weekday::Mon } else { options.week_start }
+}
+
+/// Number of days shown per week, i.e. the number of columns.
+pub fn days_per_week(options: &Options) -> u32 {
+    if options.workweek { 5 } else { 7 }
+}
+
+/// Splits the range into weeks starting on `first_day`, numbered weeks of
+/// `options.week_numbering` if they start on that day.
+pub fn week_ranges(range: DateRange, options: &Options) -> Vec<DateRange> {
+    match options.week_numbering {
+        Some(numbering) if numbering.first_day() == first_day(options) => {
+            range.by_numbered_week(numbering).map(|(_, _, week)| week).collect()
+        },
+        _ => range.by_week_starting(first_day(options)).collect()
+    }
+}
+
+/// Returns the weeks of the month, starting on `options.week_start`, in the
+/// order they should be displayed. In a workweek layout, the weeks are
+/// trimmed to Monday to Friday and weeks without such days are left out.
+pub fn weeks(month: DateRange, options: &Options) -> ::std::vec::IntoIter<DateRange> {
+    let days = days_per_week(options);
+    let mut result = week_ranges(month, options)
+        .into_iter()
+        .filter_map(|week| {
+            let mut shown = week.filter(|&date| weekday(date) < days);
+            let first = shown.next()?;
+            let last  = shown.next_back().unwrap_or(first);
+
+            Some(DateRange::new(first, last.succ()))
+        })
+        .collect::<Vec<_>>();
+
+    if options.flip_weeks {
+        result.reverse();
+    }
+
+    result.into_iter()
+}
+
+/// Whether there are events on the date, taking one day or several.
+pub fn has_events(date: Date, options: &Options) -> bool {
+    options.events.contains(&date) || options.bars.contains(date)
+}
+
+/// Column of the date in its week, starting at 0.
+pub fn column(date: Date, options: &Options) -> u32 {
+    (weekday(date) + 7 - first_day(options).num_days_from_monday()) % 7
+}
+
+/// Abbreviated weekday names in the order of the columns, for the formats
+/// that have a header row.
+pub fn weekday_names(options: &Options) -> Vec<&'static str> {
+    const NAMES: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+    let start = first_day(options).num_days_from_monday() as usize;
+
+    NAMES[start..].iter()
+                  .chain(&NAMES[..start])
+                  .take(days_per_week(options) as usize)
+                  .cloned()
+                  .collect()
+}
+
+/// Number shown for the day: the day of the month, or of the year with
+/// `julian`, in the calendar of `reckoning`.
+pub fn day_number(date: Date, options: &Options) -> u32 {
+    if options.julian { options.reckoning.ordinal(date) } else { options.reckoning.ymd(date).2 }
+}
+
+/// The months of the range in the calendar of `reckoning`. The month of a
+/// reform is shorter by the days it skipped.
+pub fn months(range: DateRange, options: &Options) -> Vec<DateRange> {
+    range.group_by(|&date| {
+             let (year, month, _) = options.reckoning.ymd(date);
+             (year, month)
+         })
+         .collect()
+}
+
+/// Name of the month of the date in the calendar of `reckoning`.
+pub fn month_name(date: Date, options: &Options) -> String {
+    UTC.ymd(2000, options.reckoning.ymd(date).1, 1).format("%B").to_string()
+}
+
+/// Year of the date in the calendar of `reckoning`, for years starting with
+/// the month `year_start`: the year it starts in.
+fn year_of(date: Date, options: &Options) -> i32 {
+    let (year, month, _) = options.reckoning.ymd(date);
+    if month >= options.year_start { year } else { year - 1 }
+}
+
+/// The days of the year starting in the given one, as `year_of` counts
+/// years.
+pub fn year_dates(year: i32, options: &Options) -> DateRange {
+    let first = |year| options.reckoning.rename(UTC.ymd(year, options.year_start, 1));
+    DateRange::new(first(year), first(year + 1))
+}
+
+/// Number of digits of the longest day number.
+fn day_digits(options: &Options) -> usize {
+    if options.julian { 3 } else { 2 }
+}
+
+/// Width of the cell of a day: the configured one, widened if needed to fit
+/// the longest day number, plus the column of the moon symbol.
+fn day_width(options: &Options) -> usize {
+    options.layout.day_width.max(day_digits(options)) + moon_width(options)
+}
+
+/// Columns between the cells of days next to each other: one if the cells
+/// are only as wide as the longest day number, which would otherwise run
+/// into the number before it, and none otherwise.
+fn day_gap(options: &Options) -> usize {
+    if day_width(options) > day_digits(options) { 0 } else { 1 }
+}
+
+/// Width of the moon symbol following the day number, which terminals
+/// don't agree on. None without `moon`.
+fn moon_width(options: &Options) -> usize {
+    if options.moon {
+        Phase::ALL.iter().map(|phase| options.width.of_char(phase.symbol())).max().unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+/// Width of a formatted month, in columns, including the gap separating it
+/// from the next month: a cell per day, or for vertical months the weekday
+/// names, a space and a cell per week, with the `day_gap` between cells.
+pub fn month_width(options: &Options) -> usize {
+    let day_width = day_width(options);
+    let day_gap   = day_gap(options);
+    let month_gap = options.layout.month_gap;
+
+    if options.vertical {
+        2 + 6 * (day_gap + day_width) + 1 + month_gap
+    } else {
+        let days = days_per_week(options) as usize;
+        days * day_width + (days - 1) * day_gap + month_gap
+    }
+}
+
+/// Height of a formatted month: the title and the `weekday_header` plus six
+/// weeks, so that all months have the same height, or just the weeks needed
+/// with `trim_weeks`. Weeks take another line with an `overlay`, and
+/// another with a `location`.
+fn month_height(month: DateRange, options: &Options) -> usize {
+    let weeks  = if options.layout.trim_weeks { weeks(month, options).len() } else { 6 };
+    let header = if options.weekday_header { 1 } else { 0 };
+    1 + header + weeks * (1 + options.overlay.iter().count() + options.location.iter().count())
+}
+
+/// Lines of a formatted month.
+pub type MonthLayout = ::std::vec::IntoIter<String>;
+
+/// Formats the month as lines of text of equal width: the month name
+/// followed by one line per week, padded to six weeks unless compact.
+/// Months whose days all look plain are laid out by `layout_month_plain`.
+pub fn layout_month(month: DateRange, options: &Options) -> MonthLayout {
+    if is_plain(options) {
+        layout_month_plain(month, options)
+    } else {
+        layout_month_by_days(month, options)
+    }
+}
+
+/// Formats the month like `layout_month`, going through its weeks and
+/// days one by one, which every option is supported by.
+pub fn layout_month_by_days(month: DateRange, options: &Options) -> MonthLayout {
+    let mut grid = vec![String::new(); month_height(month, options)];
+    render_month_into(&mut grid, month_width(options), month, options);
+    grid.into_iter()
+}
+
+/// Whether the days are shown as nothing but their day of the month, no
+/// matter their date: no styles, symbols, markers or other calendars.
+fn is_plain(options: &Options) -> bool {
+    !options.julian && !options.moon && options.overlay.is_none() && options.location.is_none()
+        && options.reckoning == Reckoning::Gregorian
+        && options.weekend == Style::Plain
+        && options.holidays.is_empty() && options.highlights.is_empty()
+        && options.marks.is_empty() && options.events.is_empty() && options.bars.is_empty()
+        && options.heat.max() == 0
+}
+
+/// Formats the month like `layout_month`, but computes its weeks from the
+/// column of its first day and its number of days rather than going
+/// through the days, which is several times faster for long ranges. Only
+/// for `is_plain` options.
+pub fn layout_month_plain(month: DateRange, options: &Options) -> MonthLayout {
+    let cell  = day_width(options);
+    let gap   = " ".repeat(day_gap(options));
+    let days  = days_per_week(options) as usize;
+    let first = column(month.start, options) as usize;
+    let count = month.days() as usize;
+    let start = month.start.day() as usize;
+
+    // Columns of weekend days, if left out, come last in a workweek.
+    let mut rows = (0..(first + count).div_ceil(7))
+                       .filter_map(|row| {
+                           let index = |col: usize| (row * 7 + col).checked_sub(first)
+                                                                  .filter(|&i| i < count);
+                           (0..days).any(|col| index(col).is_some()).then(|| {
+                               let mut line = String::with_capacity(days * cell
+                                                                    + options.layout.month_gap);
+                               for col in 0..days {
+                                   if col > 0 { line.push_str(&gap); }
+                                   match index(col) {
+                                       Some(i) => line.push_str(&format!("{:>1$}", start + i,
+                                                                         cell)),
+                                       None    => line.push_str(&" ".repeat(cell))
+                                   }
+                               }
+                               line.push_str(&" ".repeat(options.layout.month_gap));
+                               line
+                           })
+                       })
+                       .collect::<Vec<_>>();
+
+    if options.flip_weeks {
+        rows.reverse();
+    }
+
+    let header = Some(weekday_header(options)).filter(|_| options.weekday_header);
+    let height = month_height(month, options);
+    let blank  = " ".repeat(month_width(options));
+
+    once(month_title(month.start, &options.title_format, options))
+        .chain(header)
+        .chain(rows)
+        .chain(repeat(blank))
+        .take(height)
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Formats the month as lines of text of equal width like `layout_month`,
+/// but with a line per weekday and a column per week.
+pub fn layout_month_vertical(month: DateRange, options: &Options) -> MonthLayout {
+    let days  = days_per_week(options) as usize;
+    let weeks = weeks(month, options).map(|week| {
+                                         let mut cells = vec![" ".repeat(day_width(options));
+                                                              days];
+                                         for date in week {
+                                             cells[column(date, options) as usize] =
+                                                 format_day(date, options);
+                                         }
+                                         cells.into_iter()
+                                     })
+                                     .collect::<Vec<_>>();
+
+    // Transposing needs at least one week, which a range of only weekend
+    // days in a workweek layout doesn't have.
+    let gap  = " ".repeat(day_gap(options));
+    let rows: Box<dyn Iterator<Item = String>> = if weeks.is_empty() {
+        Box::new(repeat(String::new()))
+    } else {
+        Box::new(weeks.into_iter().transpose().map(|row| {
+            row.map(|cell| format!("{}{}", gap, cell)).collect::<String>()
+        }))
+    };
+
+    let width = month_width(options);
+    let lines = weekday_names(options).into_iter()
+                                      .zip(rows)
+                                      .map(|(name, row)| format!("{}{}", name, row));
+
+    once(month_title(month.start, &options.title_format, options))
+        .chain(lines)
+        .map(|line| fit(&line, width, &options.width))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Renders the month into `grid`, one line per element, each line clipped or
+/// padded to exactly `width` columns. Lines that don't fit into the grid are
+/// dropped and grid lines below the last week are left blank.
+pub fn render_month_into(grid:    &mut [String],
+                         width:   usize,
+                         month:   DateRange,
+                         options: &Options)
+{
+    let mut lines = month_spans(month, options).into_iter()
+                                               .map(|spans| paint(&spans, options));
+
+    for line in grid.iter_mut() {
+        *line = fit(&lines.next().unwrap_or_default(), width, &options.width);
+    }
+}
+
+/// Clips or pads the text with spaces so that it is exactly `width` columns
+/// wide, as measured by `measure`. Escape sequences are kept intact and
+/// styles are reset if the text was clipped.
+fn fit(text: &str, width: usize, measure: &Width) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars  = text.chars();
+    let mut used   = 0;
+    let mut styled = false;
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            result.push(c);
+            result.extend(chars.by_ref().take_while(|&c| c != 'm'));
+            result.push('m');
+            styled = true;
+        } else if used + measure.of_char(c) <= width {
+            result.push(c);
+            used += measure.of_char(c);
+        } else {
+            if styled { result.push_str("\x1b[0m"); }
+            break;
+        }
+    }
+
+    debug_assert_eq!(measure.of(&result), used);
+    result.push_str(&" ".repeat(width - used));
+    result
+}
+
+/// Title of a calendar of the range: the year if it is a whole year,
+/// otherwise the first and last month.
+pub fn range_title(range: DateRange, options: &Options) -> String {
+    let first = range.start;
+    let last  = range.end.pred();
+    let year  = year_of(first, options);
+    let month = |date| format!("{} {}", month_name(date, options), options.reckoning.ymd(date).0);
+
+    if range == year_dates(year, options) {
+        year_title(year, options)
+    } else if month(first) == month(last) {
+        month(first)
+    } else {
+        format!("{} – {}", month(first), month(last))
+    }
+}
+
+/// Title of the year starting in the given one: the year itself, or with
+/// years not starting in January, both years it spans, e.g. `2025/26`.
+/// The second year is written in full before year 1, e.g. `-6/-5`.
+pub fn year_title(year: i32, options: &Options) -> String {
+    if options.year_start == 1 {
+        year.to_string()
+    } else if year < 0 {
+        format!("{}/{}", year, year + 1)
+    } else {
+        format!("{}/{:02}", year, (year + 1).rem_euclid(100))
+    }
+}
+
+/// Formats the days of an event as `YYYY-MM-DD`, or `YYYY-MM-DD..YYYY-MM-DD`
+/// if it takes more than one day.
+pub fn format_dates(dates: DateRange) -> String {
+    let first = dates.start;
+    let last  = dates.end.pred();
+
+    if first == last {
+        first.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{}..{}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d"))
+    }
+}
+
+/// What a piece of a layout shows, so that every output format can style
+/// it its own way: text output in the styles of the options, HTML and SVG
+/// with CSS classes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StyleRole {
+    /// Padding, titles and days without anything special about them.
+    Plain,
+    Weekend,
+    Holiday,
+    /// A day colored by how often it occurs, at the given level of
+    /// `heat_scale`.
+    Heat(usize),
+    /// A day given a style of its own, in `marks`.
+    Marked(Style),
+    Highlight,
+    /// A day of an event in `bars`, or the space before it if the event took
+    /// place the day before too, in the style it has otherwise.
+    Bar(Style)
+}
+
+impl StyleRole {
+    /// Style of the role in text output.
+    pub fn style(self, options: &Options) -> Style {
+        match self {
+            StyleRole::Plain         => Style::Plain,
+            StyleRole::Weekend       => options.weekend,
+            StyleRole::Holiday       => options.holiday,
+            StyleRole::Heat(level)   => options.heat_scale.0.get(level)
+                                                            .cloned()
+                                                            .unwrap_or(Style::Plain),
+            StyleRole::Marked(style) => style,
+            StyleRole::Highlight     => options.highlight,
+            StyleRole::Bar(style)    => style
+        }
+    }
+
+    /// Paints the text in the style of the role, underlined in a bar.
+    pub fn paint(self, text: &str, options: &Options) -> String {
+        match self {
+            StyleRole::Bar(_) if text.is_empty() => String::new(),
+            StyleRole::Bar(style)                => Style::Underline.paint(&style.paint(text)),
+            _                                    => self.style(options).paint(text)
+        }
+    }
+
+    /// CSS class of the role: `weekend`, `holiday`, `heat-1` and up,
+    /// `marked`, `highlight` or `bar`. None for plain text.
+    pub fn class(self) -> Option<String> {
+        match self {
+            StyleRole::Plain       => None,
+            StyleRole::Weekend     => Some("weekend".to_string()),
+            StyleRole::Holiday     => Some("holiday".to_string()),
+            StyleRole::Heat(level) => Some(format!("heat-{}", level + 1)),
+            StyleRole::Marked(_)   => Some("marked".to_string()),
+            StyleRole::Highlight   => Some("highlight".to_string()),
+            StyleRole::Bar(_)      => Some("bar".to_string())
+        }
+    }
+}
+
+/// Piece of a line of a layout, all of it in the same role.
+pub type Span = (String, StyleRole);
+
+/// Joins the spans into a line of text, painting them in the styles of
+/// their roles.
+pub fn paint(spans: &[Span], options: &Options) -> String {
+    spans.iter().map(|&(ref text, role)| role.paint(text, options)).collect()
+}
+
+/// Returns the role of the day, by precedence: highlighted, marked, heat,
+/// holiday and weekend.
+pub fn day_role(date: Date, options: &Options) -> StyleRole {
+    let heat = options.heat_scale.level(options.heat.get(date), options.heat.max());
+
+    if options.highlights.contains(&date) {
+        StyleRole::Highlight
+    } else if let Some(&style) = options.marks.get(&date) {
+        StyleRole::Marked(style)
+    } else if let Some(level) = heat {
+        StyleRole::Heat(level)
+    } else if options.holidays.contains(&date) {
+        StyleRole::Holiday
+    } else if weekday(date) >= 5 {
+        StyleRole::Weekend
+    } else {
+        StyleRole::Plain
+    }
+}
+
+/// Formats the day like `day_spans`, painted for the terminal.
+fn format_day(date: Date, options: &Options) -> String {
+    paint(&day_spans(date, options), options)
+}
+
+/// Lays out the day right-aligned in its cell, preceded by an asterisk if
+/// it has events and the cell is wider than the longest day number, and
+/// followed by the symbol of the moon phase with `moon`.
+pub fn day_spans(date: Date, options: &Options) -> Vec<Span> {
+    let day    = day_number(date, options).to_string();
+    let width  = day_width(options) - moon_width(options);
+    let marker = match (width > day_digits(options), has_events(date, options)) {
+        (false, _)     => "",
+        (true, true)   => "*",
+        (true, false)  => " "
+    };
+    let moon   = match moon::phase(date).filter(|_| options.moon) {
+        Some(phase) => options.width.center(&phase.symbol().to_string(), moon_width(options)),
+        None        => " ".repeat(moon_width(options))
+    };
+
+    vec![(format!("{}{}", " ".repeat(width - marker.len() - day.len()), marker),
+          StyleRole::Plain),
+         (day, day_role(date, options)),
+         (moon, StyleRole::Plain)]
+}
+
+/// Lays out the week in cells of `day_width` separated by the `day_gap`,
+/// padded to a whole week and followed by the gap to the next month.
+pub fn week_spans(week: DateRange, options: &Options) -> Vec<Span> {
+    let gap       = day_gap(options);
+    let cell      = (day_width(options) + gap) as u32;
+    let pad_left  = column(week.start, options) * cell;
+    let pad_right = (days_per_week(options) - 1 - column(week.end.pred(), options)) * cell;
+
+    let mut result = vec![(" ".repeat(pad_left as usize), StyleRole::Plain)];
+    for date in week {
+        let mut spans = day_spans(date, options);
+        if date != week.start {
+            spans[0].0.insert_str(0, &" ".repeat(gap));
+        }
+
+        // Bars start at the day number, unless they continue from the day
+        // before in the same week.
+        if options.bars.contains(date) {
+            let continues = options.bars.continues(date);
+            let continued = continues && date != week.start;
+            for span in &mut spans[if continued { 0 } else { 1 }..] {
+                span.1 = StyleRole::Bar(span.1.style(options));
+            }
+
+            // The asterisk stays on the first day of the events, so that they
+            // are marked without styles too, and the bar marks the others,
+            // unless they have events of their own.
+            if continues && !options.events.contains(&date) {
+                spans[0].0 = spans[0].0.replace('*', " ");
+            }
+        }
+
+        result.extend(spans);
+    }
+    result.push((" ".repeat(pad_right as usize + options.layout.month_gap), StyleRole::Plain));
 
     result
 }
 
-fn month_title(date: Date) -> String {
-    format!("{: ^22}", format!("{}", date.format("%B")))
+/// Lays out the days of the week in the calendar of the `overlay` below
+/// `week_spans`, each label right-aligned under the day number. Empty
+/// without an overlay.
+pub fn overlay_spans(week: DateRange, options: &Options) -> Vec<Span> {
+    let system = match options.overlay {
+        Some(system) => system,
+        None         => return Vec::new()
+    };
+    let cell  = day_width(options);
+    let gap   = day_gap(options);
+    let width = cell - moon_width(options);
+
+    let mut result = vec![(" ".repeat(column(week.start, options) as usize * (cell + gap)),
+                           StyleRole::Plain)];
+    for date in week {
+        let label = system.label(date);
+        let pad   = width.saturating_sub(options.width.of(&label));
+        let gap   = if date == week.start { 0 } else { gap };
+        result.push((format!("{}{}{}", " ".repeat(gap + pad), label, " ".repeat(cell - width)),
+                     StyleRole::Plain));
+    }
+
+    result
+}
+
+/// Lays out when the sun is up on the first day of the week, below
+/// `week_spans`, followed by the change of the clocks on a day of the week
+/// they change, like `☀ 07:12–16:31 -1h Sun`. Empty without a location.
+pub fn daylight_spans(week: DateRange, options: &Options) -> Vec<Span> {
+    let sun = match daylight_of(week.start, options) {
+        Some(daylight) => format!("☀ {}", daylight),
+        None           => return Vec::new()
+    };
+    let changes = week.filter_map(|date| {
+                          clock_change(date, options).map(|change| {
+                              format!(" {} {}", change_label(change), date.format("%a"))
+                          })
+                      })
+                      .collect::<String>();
+
+    vec![(sun + &changes, StyleRole::Plain)]
+}
+
+/// When the sun is up on the day at the `location`, in the time of `zone`,
+/// if there is a location.
+pub fn daylight_of(date: Date, options: &Options) -> Option<Daylight> {
+    options.location.map(|location| daylight(date, location, utc_offset(date, options.zone)))
+}
+
+/// By how many seconds the clocks of `zone` change on the day, if they do
+/// and there is a `location`.
+pub fn clock_change(date: Date, options: &Options) -> Option<i32> {
+    options.location?;
+
+    match utc_offset(date, options.zone) - utc_offset(date.pred(), options.zone) {
+        0      => None,
+        change => Some(change)
+    }
+}
+
+/// Label of a change of the clocks, like `+1h` or `-30m`.
+pub fn change_label(change: i32) -> String {
+    let sign = if change < 0 { '-' } else { '+' };
+
+    match change.abs() {
+        change if change % 3600 == 0 => format!("{}{}h", sign, change / 3600),
+        change                       => format!("{}{}m", sign, change / 60)
+    }
+}
+
+/// Lays out the month like `layout_month`, as spans rather than painted
+/// text: the title followed by a line per week, with an `overlay` a line
+/// of its days below each, and with a `location` a line of when the sun is
+/// up, neither padded to the height of six weeks nor to the width of the
+/// month.
+pub fn month_spans(month: DateRange, options: &Options) -> Vec<Vec<Span>> {
+    once(vec![(month_title(month.start, &options.title_format, options), StyleRole::Plain)])
+        .chain(Some(vec![(weekday_header(options), StyleRole::Plain)])
+                   .filter(|_| options.weekday_header))
+        .chain(weeks(month, options).flat_map(|week| {
+            once(week_spans(week, options))
+                .chain(Some(overlay_spans(week, options)).filter(|_| options.overlay.is_some()))
+                .chain(Some(daylight_spans(week, options)).filter(|_| options.location.is_some()))
+        }))
+        .collect()
+}
+
+/// Title of the month of the date in the calendar of `reckoning`,
+/// formatted with the `strftime` format, like `%B` or `%b %Y`, and centered
+/// over the month by its width as `options.width` measures it. Titles wider
+/// than the days of the month are cut off, so that they don't run into the
+/// next month.
+pub fn month_title(date: Date, format: &TitleFormat, options: &Options) -> String {
+    let (year, month, _) = options.reckoning.ymd(date);
+    let title = UTC.ymd(year, month, 1).format(&format.0).to_string();
+    let width = month_width(options);
+    let title = fit(&title, width - options.layout.month_gap, &options.width);
+
+    options.width.center(title.trim_end(), width)
+}
+
+/// Row of the weekday names in the order of the columns, each right-aligned
+/// over the day numbers and abbreviated to fit: to two letters in cells of
+/// up to three columns, like ` Mo Tu`, and to three in wider ones.
+fn weekday_header(options: &Options) -> String {
+    let cell   = day_width(options);
+    let width  = cell - moon_width(options);
+    let length = if width > 3 { 3 } else { 2 };
+    let start  = first_day(options).num_days_from_monday();
+
+    let names = (0..days_per_week(options))
+                    .map(|column| {
+                        // January 5, 2015 is a Monday.
+                        let day  = UTC.ymd(2015, 1, 5 + (start + column) % 7);
+                        let name = day.format("%a").to_string();
+                        format!("{:>2$}{}", &name[..length], " ".repeat(cell - width), width)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&" ".repeat(day_gap(options)));
+
+    names + &" ".repeat(options.layout.month_gap)
 }
 
 //------------------------------------------------------------------------------
 
 #[cfg(test)]
-use chrono::{ TimeZone, UTC };
+use date::dates;
 
 #[test]
 fn layout_month_returns_an_iterator_of_formatted_weeks() {
     let month = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 1));
-    let mut layout = layout_month(month);
+    let mut layout = layout_month(month, &Options::default());
 
     assert_eq!(layout.next().unwrap(), "       January        ");
     assert_eq!(layout.next().unwrap(), "           1  2  3  4 ");
@@ -62,7 +1241,7 @@ fn layout_month_returns_an_iterator_of_formatted_weeks() {
     assert_eq!(layout.next(), None);
 
     let month = DateRange::new(UTC.ymd(2010, 2, 1), UTC.ymd(2010, 3, 1));
-    let mut layout = layout_month(month);
+    let mut layout = layout_month(month, &Options::default());
 
     assert_eq!(layout.next().unwrap(), "       February       ");
     assert_eq!(layout.next().unwrap(), "  1  2  3  4  5  6  7 ");
@@ -74,24 +1253,574 @@ fn layout_month_returns_an_iterator_of_formatted_weeks() {
     assert_eq!(layout.next(), None);
 }
 
+#[test]
+fn render_month_into_clips_and_pads_to_the_grid() {
+    let month = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 1));
+
+    let mut grid = vec![String::new(); 3];
+    render_month_into(&mut grid, 10, month, &Options::default());
+    assert_eq!(grid, vec!["       Jan", "          ", "  5  6  7 "]);
+
+    let mut grid = vec![String::new(); 8];
+    render_month_into(&mut grid, 24, month, &Options::default());
+    assert_eq!(grid[5], " 26 27 28 29 30 31      ");
+    assert_eq!(grid[6], " ".repeat(24));
+    assert_eq!(grid[7], " ".repeat(24));
+}
+
+#[test]
+fn fit_keeps_escape_sequences_intact() {
+    let text = "  \x1b[31m12\x1b[0m  1";
+
+    let width = Width::default();
+
+    assert_eq!(fit(text, 8, &width), "  \x1b[31m12\x1b[0m  1 ");
+    assert_eq!(fit(text, 3, &width), "  \x1b[31m1\x1b[0m");
+}
+
+#[test]
+fn fit_measures_wide_characters() {
+    let width = Width::default();
+
+    assert_eq!(fit("一月", 5, &width), "一月 ");
+    assert_eq!(fit("一月", 3, &width), "一 ");
+}
+
+#[test]
+fn text_renders_each_year_under_a_banner() {
+    let text  = Text { months_per_line: 6, banner: true };
+    let range = DateRange::new(UTC.ymd(2014, 1, 1), UTC.ymd(2016, 1, 1));
+    let lines = text.render(range, &Options::default(), &[]).lines()
+                                                          .map(String::from)
+                                                          .collect::<Vec<_>>();
+
+    // Banner, two rows of six months and a blank line between the years.
+    assert_eq!(lines.len(), 2 * (1 + 2 * 7) + 1);
+    assert_eq!(lines[0].trim(), "2014");
+    assert_eq!(lines[0].find('2'), Some(64));
+    assert_eq!(lines[15], "");
+    assert_eq!(lines[16].trim(), "2015");
+    assert_eq!(lines[17].trim(), "January               February               \
+                                  March                 April                  \
+                                  May                   June");
+}
+
+#[test]
+fn text_starts_a_line_of_months_under_a_divider_at_breaks() {
+    let text    = Text { months_per_line: 3, banner: false };
+    let options = Options {
+        breaks: vec![(UTC.ymd(2015, 9, 1), "Autumn term".to_string()),
+                     (UTC.ymd(2015, 2, 10), "Spring term".to_string())],
+        ..Options::default()
+    };
+    let output  = text.render(dates(2015).unwrap(), &options, &[]);
+    let lines   = output.lines().collect::<Vec<_>>();
+    let titles  = lines.iter()
+                       .filter(|line| line.contains("January") || line.contains("term")
+                                      || line.contains("May") || line.contains("December"))
+                       .map(|line| line.trim_end_matches('─')
+                                       .split_whitespace()
+                                       .collect::<Vec<_>>()
+                                       .join(" "))
+                       .collect::<Vec<_>>();
+
+    assert_eq!(titles, vec!["January", "── Spring term", "May June July", "── Autumn term",
+                            "December"]);
+    let divider = lines.iter().find(|line| line.contains("Spring")).unwrap();
+    assert_eq!(options.width.of(divider), 3 * month_width(&options));
+}
+
 #[test]
 fn format_day_formats_day() {
-    assert_eq!(format_day(UTC.ymd(2015, 1,  1)), "  1");
-    assert_eq!(format_day(UTC.ymd(2015, 2, 11)), " 11");
+    let options = Options::default();
+
+    assert_eq!(format_day(UTC.ymd(2015, 1,  1), &options), "  1");
+    assert_eq!(format_day(UTC.ymd(2015, 2, 11), &options), " 11");
+
+    let options = Options {
+        events: vec![UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 11)].into_iter()
+                                                               .collect(),
+        ..Options::default()
+    };
+
+    assert_eq!(format_day(UTC.ymd(2015, 1,  1), &options), " *1");
+    assert_eq!(format_day(UTC.ymd(2015, 2, 11), &options), "*11");
 }
 
 #[test]
-fn format_week_formats_week() {
+fn week_spans_lay_out_week() {
     let week0 = DateRange::new(UTC.ymd(2015, 1, 1),  UTC.ymd(2015, 1,  5));
     let week1 = DateRange::new(UTC.ymd(2015, 1, 5),  UTC.ymd(2015, 1, 12));
     let week4 = DateRange::new(UTC.ymd(2015, 1, 26), UTC.ymd(2015, 2, 1));
 
-    assert_eq!(format_week(week0), "           1  2  3  4 ");
-    assert_eq!(format_week(week1), "  5  6  7  8  9 10 11 ");
-    assert_eq!(format_week(week4), " 26 27 28 29 30 31    ");
+    let options = Options::default();
+
+    assert_eq!(paint(&week_spans(week0, &options), &options), "           1  2  3  4 ");
+    assert_eq!(paint(&week_spans(week1, &options), &options), "  5  6  7  8  9 10 11 ");
+    assert_eq!(paint(&week_spans(week4, &options), &options), " 26 27 28 29 30 31    ");
+}
+
+#[test]
+fn month_spans_leave_styling_to_the_renderer() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        weekend:    Style::Dim,
+        heat:       Frequencies::count(vec![UTC.ymd(2015, 2, 2)]),
+        heat_scale: "dim,bold".parse().unwrap(),
+        ..Options::default()
+    };
+    let lines = month_spans(month, &options);
+
+    assert_eq!(lines.len(), 1 + 5);
+    assert_eq!(lines[0], [(month_title(month.start, &TitleFormat::default(), &options),
+                           StyleRole::Plain)]);
+    assert_eq!(lines[1][1..3], [("  ".to_string(), StyleRole::Plain),
+                                ("1".to_string(),  StyleRole::Weekend)]);
+    assert_eq!(lines[2][2], ("2".to_string(), StyleRole::Heat(1)));
+    assert_eq!(StyleRole::Heat(1).class(), Some("heat-2".to_string()));
+    assert_eq!(paint(&lines[1], &options), format!("{}\x1b[2m1\x1b[0m ", " ".repeat(20)));
+}
+
+#[test]
+fn wrap_breaks_lines_at_spaces_or_within_long_words() {
+    let wrap = |text, width, indent| wrap(text, width, indent, &Width::default());
+
+    assert_eq!(wrap(" 4 Independence Day", 16, 3), [" 4 Independence", "   Day"]);
+    assert_eq!(wrap("20-26 Trip to the mountains", 15, 6),
+               ["20-26 Trip to", "      the", "      mountains"]);
+    assert_eq!(wrap("12 Supercalifragilistic", 10, 3),
+               ["12", "   Superca", "   lifragi", "   listic"]);
+}
+
+#[test]
+fn footnotes_are_laid_out_below_their_months() {
+    let options = Options { footnotes: true, ..Options::default() };
+    let legend  = vec![
+        Event::new(DateRange::new(UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 3)), "Trip"),
+        Event::new(DateRange::new(UTC.ymd(2015, 2, 14), UTC.ymd(2015, 2, 15)),
+                   "Valentine's Day dinner")
+    ];
+    let text   = Text { months_per_line: 2, banner: false };
+    let output = text.render(DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 3, 1)),
+                             &options, &legend);
+    let lines  = output.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[7], "30-31 Trip             1-2 Trip             ");
+    assert_eq!(lines[8], "                      14 Valentine's Day    ");
+    assert_eq!(lines[9], "                         dinner             ");
+}
+
+#[test]
+fn footnotes_stay_below_their_month_beside_months_without_any() {
+    let options = Options { footnotes: true, ..Options::default() };
+    let legend  = vec![Event::new(DateRange::new(UTC.ymd(2015, 2, 14), UTC.ymd(2015, 2, 15)),
+                                  "Dinner")];
+    let text   = Text { months_per_line: 2, banner: false };
+    let output = text.render(DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 3, 1)),
+                             &options, &legend);
+
+    assert_eq!(output.lines().last(), Some("                      14 Dinner             "));
+}
+
+#[test]
+fn footnotes_label_days_as_the_calendar_numbers_them() {
+    let options = Options { footnotes: true, julian: true, ..Options::default() };
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let legend  = vec![Event::new(DateRange::new(UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 3)),
+                                  "Trip")];
+
+    assert_eq!(footnote_lines(month, &legend, &options)[0].trim_end(), "32-33 Trip");
+}
+
+#[test]
+fn layout_grid_arranges_items_in_rows() {
+    let item  = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+    let items = vec![item(&["a1", "a2"]), item(&["b1"]), item(&["c1", "c2", "c3"])];
+
+    assert_eq!(layout_grid(items, 2, "  ".to_string()).map(|line| line.concat())
+                                                       .collect::<Vec<_>>(),
+               ["a1b1", "a2  ", "c1", "c2", "c3"]);
+    assert_eq!(layout_grid(vec![item(&["a1"]), item(&["b1"])], 0, String::new()).count(), 2);
+
+    let text = Text { months_per_line: 0, banner: true };
+    let one  = Text { months_per_line: 1, banner: true };
+    let year = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1));
+    assert_eq!(text.render(year, &Options::default(), &[]),
+               one.render(year, &Options::default(), &[]));
+}
+
+#[test]
+fn month_rows_arranges_months_in_rows() {
+    let months = months(DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 6, 1)),
+                        &Options::default());
+    let rows   = month_rows(months.clone(), 2).collect::<Vec<_>>();
+
+    assert_eq!(rows, [&months[0..2], &months[2..4], &months[4..]]);
 }
 
 #[test]
 fn month_title_formats_month_name() {
-    assert_eq!(month_title(UTC.ymd(2015, 1, 1)), "       January        ");
+    let title = |date: Date, format: &str, options: &Options| {
+        month_title(date, &format.parse().unwrap(), options)
+    };
+
+    assert_eq!(title(UTC.ymd(2015, 1, 1), "%B", &Options::default()),
+               "       January        ");
+    assert_eq!(title(UTC.ymd(2015, 3, 1), "%b %Y", &Options::default()),
+               "       Mar 2015       ");
+
+    // Cut off to the width of the days, leaving the gap to the next month.
+    assert_eq!(title(UTC.ymd(2015, 9, 1), "%B %Y, week %V of the year", &Options::default()),
+               " September 2015, week ");
+    let options = Options { workweek: true, ..Options::default() };
+    assert_eq!(title(UTC.ymd(2015, 9, 1), "%B %-d, %Y", &options), "September 1, 20 ");
+    assert!("%H".parse::<TitleFormat>().is_err());
+}
+
+#[test]
+fn layout_month_names_weekdays_below_the_title() {
+    let month   = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let options = Options { weekday_header: true, ..Options::default() };
+
+    for layout in &[layout_month_plain(month, &options), layout_month_by_days(month, &options)] {
+        let layout = layout.clone().collect::<Vec<_>>();
+
+        assert_eq!(layout.len(), 8);
+        assert_eq!(layout[1], " Mo Tu We Th Fr Sa Su ");
+        assert_eq!(layout[2], "                    1 ");
+    }
+
+    let options = Options { weekday_header: true, week_start: Weekday::Sun,
+                            layout: LayoutConfig { day_width: 4, ..LayoutConfig::default() },
+                            ..Options::default() };
+    assert_eq!(weekday_header(&options), " Sun Mon Tue Wed Thu Fri Sat ");
+}
+
+#[test]
+fn week_spans_style_weekends() {
+    let options = Options { weekend: Style::Dim, ..Options::default() };
+    let week    = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 5));
+
+    assert_eq!(paint(&week_spans(week, &options), &options),
+               "           1  2  \x1b[2m3\x1b[0m  \x1b[2m4\x1b[0m ");
+}
+
+#[test]
+fn week_spans_underline_events_taking_several_days() {
+    let options = Options {
+        holiday:  Style::Red,
+        holidays: vec![UTC.ymd(2015, 1, 2)].into_iter().collect(),
+        bars:     Bars::new(vec![DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 3)),
+                                 DateRange::new(UTC.ymd(2015, 1, 4), UTC.ymd(2015, 1, 7))]),
+        ..Options::default()
+    };
+    let week = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 5));
+
+    // Only the first day of an event keeps its asterisk.
+    assert_eq!(paint(&week_spans(week, &options), &options),
+               "          *\x1b[4m1\x1b[0m\x1b[4m  \x1b[0m\
+                \x1b[4m\x1b[31m2\x1b[0m\x1b[0m  3 *\x1b[4m4\x1b[0m ");
+
+    let week = DateRange::new(UTC.ymd(2015, 1, 5), UTC.ymd(2015, 1, 12));
+    assert_eq!(paint(&week_spans(week, &options), &options),
+               "  \x1b[4m5\x1b[0m\x1b[4m  \x1b[0m\x1b[4m6\x1b[0m  7  8  9 10 11 ");
+}
+
+#[test]
+fn week_spans_style_holidays() {
+    let options = Options {
+        weekend:  Style::Dim,
+        holiday:  Style::Red,
+        holidays: vec![UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 4)].into_iter()
+                                                                .collect(),
+        ..Options::default()
+    };
+    let week = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 5));
+
+    assert_eq!(paint(&week_spans(week, &options), &options),
+               "           \x1b[31m1\x1b[0m  2  \x1b[2m3\x1b[0m  \x1b[31m4\x1b[0m ");
+}
+
+#[test]
+fn text_renderer_appends_legend() {
+    let legend = vec![
+        Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)), "Pi day"),
+        Event::new(DateRange::new(UTC.ymd(2015, 7, 20), UTC.ymd(2015, 7, 25)), "Holiday")
+    ];
+
+    let text   = Text { months_per_line: 3, banner: false };
+    let output = text.render(dates(2015).unwrap(), &Options::default(), &legend);
+    let lines  = output.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 4 * 7 + 3);
+    assert_eq!(lines[0], format!("{: ^22}{: ^22}{: ^22}", "January", "February", "March"));
+    assert_eq!(&lines[28..], &["", "2015-03-14  Pi day", "2015-07-20..2015-07-24  Holiday"]);
+
+    let text   = Text { months_per_line: 3, banner: true };
+    let output = text.render(dates(2015).unwrap(), &Options::default(), &[]);
+
+    assert_eq!(output.lines().next(), Some(&*format!("{: ^66}", 2015)));
+
+    let march = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let output = text.render(march, &Options::default(), &[]);
+
+    assert_eq!(output.lines().next().map(str::trim), Some("March"));
+}
+
+#[test]
+fn format_can_be_parsed() {
+    assert_eq!("html".parse(), Ok(Format::Html));
+    assert!("pdf".parse::<Format>().is_err());
+}
+
+#[test]
+fn format_day_highlights_dates() {
+    let options = Options {
+        holiday:    Style::Red,
+        holidays:   vec![UTC.ymd(2015, 1, 1)].into_iter().collect(),
+        highlight:  Style::Reverse,
+        highlights: vec![UTC.ymd(2015, 1, 1)].into_iter().collect(),
+        ..Options::default()
+    };
+
+    assert_eq!(format_day(UTC.ymd(2015, 1, 1), &options), "  \x1b[7m1\x1b[0m");
+}
+
+#[test]
+fn range_title_names_year_or_months() {
+    let options = Options::default();
+
+    assert_eq!(range_title(dates(2015).unwrap(), &options), "2015");
+    assert_eq!(range_title(DateRange::new(UTC.ymd(2014, 11, 1), UTC.ymd(2015, 4, 1)), &options),
+               "November 2014 – March 2015");
+    assert_eq!(range_title(DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1)), &options),
+               "March 2015");
+}
+
+#[test]
+fn text_splits_years_starting_with_year_start() {
+    let options = Options { year_start: 4, ..Options::default() };
+    let text    = Text { months_per_line: 12, banner: true };
+    let range   = DateRange::new(UTC.ymd(2025, 1, 1), UTC.ymd(2026, 1, 1));
+    let output  = text.render(range, &options, &[]);
+    let banners = output.lines()
+                        .map(str::trim)
+                        .filter(|line| line.contains('/'))
+                        .collect::<Vec<_>>();
+
+    assert_eq!(banners, ["2024/25", "2025/26"]);
+    assert_eq!(range_title(DateRange::new(UTC.ymd(2099, 4, 1), UTC.ymd(2100, 4, 1)), &options),
+               "2099/00");
+    assert_eq!(year_title(-6, &options), "-6/-5");
+    assert_eq!(year_title(-1, &options), "-1/0");
+}
+
+#[test]
+fn layout_month_starts_weeks_on_week_start() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options { week_start: Weekday::Sun, ..Options::default() };
+    let mut layout = layout_month(month, &options);
+
+    assert_eq!(layout.nth(1).unwrap(), "  1  2  3  4  5  6  7 ");
+    assert_eq!(layout.nth(2).unwrap(), " 22 23 24 25 26 27 28 ");
+    assert_eq!(weekday_names(&options)[..2], ["Su", "Mo"]);
+}
+
+#[test]
+fn week_ranges_splits_weeks_like_their_numbering() {
+    let range = DateRange::new(UTC.ymd(2025, 12, 28), UTC.ymd(2026, 1, 4));
+    let us    = Options { week_start: Weekday::Sun, week_numbering: Some(WeekNumbering::Us),
+                          ..Options::default() };
+    let sunday_start = Options { week_start: Weekday::Sun, ..Options::default() };
+
+    assert_eq!(week_ranges(range, &us),
+               [DateRange::new(UTC.ymd(2025, 12, 28), UTC.ymd(2026, 1, 1)),
+                DateRange::new(UTC.ymd(2026, 1, 1), UTC.ymd(2026, 1, 4))]);
+    assert_eq!(week_ranges(range, &sunday_start), [range]);
+}
+
+#[test]
+fn layout_month_shows_only_workdays_in_workweek_layout() {
+    // February 2015 starts on a Sunday.
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options { workweek: true, week_start: Weekday::Sun, ..Options::default() };
+    let layout  = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[0], "    February    ");
+    assert_eq!(layout[1], "  2  3  4  5  6 ");
+    assert_eq!(layout[4], " 23 24 25 26 27 ");
+    assert_eq!(layout[5], " ".repeat(16));
+    assert_eq!(weekday_names(&options), ["Mo", "Tu", "We", "Th", "Fr"]);
+}
+
+#[test]
+fn layout_month_vertical_has_a_line_per_weekday() {
+    let month   = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let options = Options { vertical: true, ..Options::default() };
+    let layout  = layout_month_vertical(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout, ["        March         ",
+                        "Mo     2  9 16 23 30  ",
+                        "Tu     3 10 17 24 31  ",
+                        "We     4 11 18 25     ",
+                        "Th     5 12 19 26     ",
+                        "Fr     6 13 20 27     ",
+                        "Sa     7 14 21 28     ",
+                        "Su  1  8 15 22 29     "]);
+}
+
+#[test]
+fn layout_month_compact_has_narrow_cells_and_no_blank_weeks() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options { layout: LayoutConfig::compact(), ..Options::default() };
+
+    // Only the columns between days separate the numbers.
+    assert_eq!(layout_month(month, &options).collect::<Vec<_>>(),
+               ["      February       ",
+                "                   1 ",
+                " 2  3  4  5  6  7  8 ",
+                " 9 10 11 12 13 14 15 ",
+                "16 17 18 19 20 21 22 ",
+                "23 24 25 26 27 28    "]);
+
+    let options = Options { events: once(UTC.ymd(2015, 2, 10)).collect(), ..options };
+    assert_eq!(layout_month(month, &options).nth(3).unwrap(), " 9 10 11 12 13 14 15 ");
+}
+
+#[test]
+fn layout_month_uses_configured_day_width_and_month_gap() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        layout: LayoutConfig { day_width: 4, month_gap: 3, trim_weeks: false },
+        events: vec![UTC.ymd(2015, 2, 3)].into_iter().collect(),
+        ..Options::default()
+    };
+    let layout = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[1], format!("{}   1   ", " ".repeat(24)));
+    assert_eq!(layout[2], "   2  *3   4   5   6   7   8   ");
+    assert_eq!(layout[6], " ".repeat(31));
+}
+
+#[test]
+fn layout_month_numbers_days_of_year_with_julian() {
+    let month   = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let options = Options {
+        julian: true,
+        layout: LayoutConfig { day_width: 4, ..LayoutConfig::default() },
+        events: vec![UTC.ymd(2015, 3, 2)].into_iter().collect(),
+        ..Options::default()
+    };
+    let layout = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[1], format!("{}  60 ", " ".repeat(24)));
+    assert_eq!(layout[2], " *61  62  63  64  65  66  67 ");
+
+    // Cells too narrow for three digits are widened.
+    let options = Options { layout: LayoutConfig::compact(), ..options };
+    assert_eq!(layout_month(month, &options).nth(6).unwrap(),
+               format!(" 89  90{}", " ".repeat(21)));
+}
+
+#[test]
+fn layout_month_marks_moon_phases() {
+    let month   = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let options = Options { moon: true, ..Options::default() };
+    let layout  = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[2], "  2   3   4   5○  6   7   8  ");
+    assert_eq!(layout[0].chars().count(), 7 * 4 + 1);
+}
+
+#[test]
+fn layout_month_colors_days_by_frequency() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        heat:       Frequencies::count(vec![UTC.ymd(2015, 2, 2), UTC.ymd(2015, 2, 3),
+                                            UTC.ymd(2015, 2, 3)]),
+        heat_scale: "dim,bold".parse().unwrap(),
+        ..Options::default()
+    };
+    let layout = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[2], "  \x1b[2m2\x1b[0m  \x1b[1m3\x1b[0m  4  5  6  7  8 ");
+}
+
+#[test]
+fn layout_month_styles_marked_days() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        marks:      vec![(UTC.ymd(2015, 2, 2), Style::Green), (UTC.ymd(2015, 2, 3), Style::Blue)]
+                        .into_iter()
+                        .collect(),
+        highlight:  Style::Reverse,
+        highlights: vec![UTC.ymd(2015, 2, 3)].into_iter().collect(),
+        ..Options::default()
+    };
+    let layout = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[2], "  \x1b[32m2\x1b[0m  \x1b[7m3\x1b[0m  4  5  6  7  8 ");
+}
+
+#[test]
+fn layout_month_lists_flipped_weeks_latest_first() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options { flip_weeks: true, ..Options::default() };
+    let layout  = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout[1], " 23 24 25 26 27 28    ");
+    assert_eq!(layout[5], "                    1 ");
+}
+
+#[test]
+fn layout_month_shows_overlay_below_each_week() {
+    let month   = DateRange::new(UTC.ymd(2015, 9, 1), UTC.ymd(2015, 10, 1));
+    let options = Options { overlay: Some(System::Hebrew), ..Options::default() };
+    let layout  = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout.len(), 13);
+    assert_eq!(layout[1], "     1  2  3  4  5  6 ");
+    assert_eq!(layout[2], "    17 18 19 20 21 22 ");
+    assert_eq!(layout[5], " 14 15 16 17 18 19 20 ");
+    assert_eq!(layout[6], " Ti  2  3  4  5  6  7 ");
+}
+
+#[test]
+fn layout_month_shows_daylight_below_each_week() {
+    let month   = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let berlin  = Location { latitude: 52.52, longitude: 13.40 };
+    let options = Options { location: Some(berlin), zone: Zone::Fixed(3600),
+                            ..Options::default() };
+    let layout  = layout_month(month, &options).collect::<Vec<_>>();
+
+    assert_eq!(layout.len(), 13);
+    assert_eq!(layout[1], "                    1 ");
+    assert_eq!(layout[2], "☀ 06:54–17:44         ");
+    assert_eq!(layout[3], "  2  3  4  5  6  7  8 ");
+    assert_eq!(layout[4], "☀ 06:51–17:46         ");
+
+    assert_eq!(change_label(3600), "+1h");
+    assert_eq!(change_label(-1800), "-30m");
+}
+
+#[test]
+fn layout_month_plain_matches_layout_by_days() {
+    let configs = [
+        Options::default(),
+        Options { week_start: Weekday::Sun, flip_weeks: true, ..Options::default() },
+        Options { workweek: true, layout: LayoutConfig::compact(), ..Options::default() },
+        Options { layout: LayoutConfig { day_width: 4, month_gap: 3, trim_weeks: true },
+                  ..Options::default() }
+    ];
+    let range = DateRange::new(UTC.ymd(2015, 1, 7), UTC.ymd(2016, 11, 19));
+
+    for options in &configs {
+        assert!(is_plain(options));
+
+        for month in months(range, options) {
+            assert_eq!(layout_month_plain(month, options).collect::<Vec<_>>(),
+                       layout_month_by_days(month, options).collect::<Vec<_>>());
+        }
+    }
 }
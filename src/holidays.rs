@@ -0,0 +1,227 @@
+//! Public holidays.
+
+use chrono::{ Duration, TimeZone, UTC, Weekday };
+use date::{ last_weekday, nth_weekday, Date };
+#[cfg(feature = "serde")]
+use serde::{ Deserialize, Serialize };
+
+/// A public holiday.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Holiday {
+    #[cfg_attr(feature = "serde", serde(with = "::serialization::date"))]
+    pub date: Date,
+    pub name: String
+}
+
+/// Source of public holidays. Implement this to plug in custom rule sets.
+pub trait HolidayProvider {
+    /// Returns the holidays in the given year, ordered by date.
+    fn holidays(&self, year: i32) -> Vec<Holiday>;
+}
+
+/// Rule determining the date of a holiday in a given year.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Same month and day every year.
+    Fixed(u32, u32),
+    /// Given number of days after (or before, if negative) Easter Sunday.
+    Easter(i64),
+    /// N-th (starting at 1) given weekday of the month.
+    NthWeekday(u32, Weekday, u32),
+    /// Last given weekday of the month.
+    LastWeekday(u32, Weekday)
+}
+
+impl Rule {
+    pub fn date(self, year: i32) -> Date {
+        match self {
+            Rule::Fixed(month, day)             => UTC.ymd(year, month, day),
+            Rule::Easter(offset)                => easter(year) + Duration::days(offset),
+            Rule::NthWeekday(month, weekday, n) => nth_weekday(year, month, weekday, n)
+                                                       .expect("the month has the n-th weekday"),
+            Rule::LastWeekday(month, weekday)   => last_weekday(year, month, weekday)
+        }
+    }
+}
+
+/// Holiday provider defined by a list of named rules.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<(Rule, String)>
+}
+
+impl RuleSet {
+    pub fn new() -> RuleSet {
+        RuleSet::default()
+    }
+
+    /// Adds a holiday called `name` whose date is determined by `rule`.
+    pub fn add(mut self, rule: Rule, name: &str) -> RuleSet {
+        self.rules.push((rule, name.to_string()));
+        self
+    }
+}
+
+impl HolidayProvider for RuleSet {
+    fn holidays(&self, year: i32) -> Vec<Holiday> {
+        let mut result = self.rules.iter()
+                                   .map(|&(rule, ref name)| Holiday {
+                                       date: rule.date(year),
+                                       name: name.clone()
+                                   })
+                                   .collect::<Vec<_>>();
+        result.sort_by_key(|holiday| holiday.date);
+        result
+    }
+}
+
+/// Formats the holidays as CSV with a `date,name` header line.
+pub fn to_csv(holidays: &[Holiday]) -> String {
+    let mut result = "date,name\n".to_string();
+
+    for holiday in holidays {
+        let name = if holiday.name.contains([',', '"']) {
+            format!("\"{}\"", holiday.name.replace('"', "\"\""))
+        } else {
+            holiday.name.clone()
+        };
+
+        result.push_str(&format!("{},{}\n", holiday.date.format("%Y-%m-%d"), name));
+    }
+
+    result
+}
+
+/// Codes of the countries with built-in holiday rules.
+pub const COUNTRIES: &[&str] = &["CZ", "DE", "GB", "US"];
+
+/// Returns the built-in holiday rules of the country with the given ISO 3166
+/// code (case insensitive), if there are any.
+pub fn country(code: &str) -> Option<RuleSet> {
+    use self::Rule::*;
+
+    let rules = RuleSet::new();
+
+    match &*code.to_uppercase() {
+        "CZ" => Some(rules.add(Fixed(1, 1),   "New Year's Day")
+                          .add(Easter(-2),    "Good Friday")
+                          .add(Easter(1),     "Easter Monday")
+                          .add(Fixed(5, 1),   "Labour Day")
+                          .add(Fixed(5, 8),   "Liberation Day")
+                          .add(Fixed(7, 5),   "Saints Cyril and Methodius Day")
+                          .add(Fixed(7, 6),   "Jan Hus Day")
+                          .add(Fixed(9, 28),  "St. Wenceslas Day")
+                          .add(Fixed(10, 28), "Independent Czechoslovak State Day")
+                          .add(Fixed(11, 17), "Struggle for Freedom and Democracy Day")
+                          .add(Fixed(12, 24), "Christmas Eve")
+                          .add(Fixed(12, 25), "Christmas Day")
+                          .add(Fixed(12, 26), "St. Stephen's Day")),
+        "DE" => Some(rules.add(Fixed(1, 1),   "Neujahr")
+                          .add(Easter(-2),    "Karfreitag")
+                          .add(Easter(1),     "Ostermontag")
+                          .add(Fixed(5, 1),   "Tag der Arbeit")
+                          .add(Easter(39),    "Christi Himmelfahrt")
+                          .add(Easter(50),    "Pfingstmontag")
+                          .add(Fixed(10, 3),  "Tag der Deutschen Einheit")
+                          .add(Fixed(12, 25), "1. Weihnachtstag")
+                          .add(Fixed(12, 26), "2. Weihnachtstag")),
+        "GB" => Some(rules.add(Fixed(1, 1),   "New Year's Day")
+                          .add(Easter(-2),    "Good Friday")
+                          .add(Easter(1),     "Easter Monday")
+                          .add(NthWeekday(5, Weekday::Mon, 1), "Early May Bank Holiday")
+                          .add(LastWeekday(5, Weekday::Mon),   "Spring Bank Holiday")
+                          .add(LastWeekday(8, Weekday::Mon),   "Summer Bank Holiday")
+                          .add(Fixed(12, 25), "Christmas Day")
+                          .add(Fixed(12, 26), "Boxing Day")),
+        "US" => Some(rules.add(Fixed(1, 1),   "New Year's Day")
+                          .add(NthWeekday(1, Weekday::Mon, 3),  "Martin Luther King Jr. Day")
+                          .add(NthWeekday(2, Weekday::Mon, 3),  "Washington's Birthday")
+                          .add(LastWeekday(5, Weekday::Mon),    "Memorial Day")
+                          .add(Fixed(6, 19),  "Juneteenth")
+                          .add(Fixed(7, 4),   "Independence Day")
+                          .add(NthWeekday(9, Weekday::Mon, 1),  "Labor Day")
+                          .add(NthWeekday(10, Weekday::Mon, 2), "Columbus Day")
+                          .add(Fixed(11, 11), "Veterans Day")
+                          .add(NthWeekday(11, Weekday::Thu, 4), "Thanksgiving Day")
+                          .add(Fixed(12, 25), "Christmas Day")),
+        _    => None
+    }
+}
+
+/// Returns the date of Easter Sunday in the given year (Gregorian calendar).
+pub fn easter(year: i32) -> Date {
+    // Anonymous Gregorian algorithm (Meeus/Jones/Butcher), with divisions
+    // rounding down so that it holds for years before 1 too.
+    let a = year.rem_euclid(19);
+    let b = year.div_euclid(100);
+    let c = year.rem_euclid(100);
+    let d = b.div_euclid(4);
+    let e = b.rem_euclid(4);
+    let f = (b + 8).div_euclid(25);
+    let g = (b - f + 1).div_euclid(3);
+    let h = (19 * a + b - d - g + 15).rem_euclid(30);
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k).rem_euclid(7);
+    let m = (a + 11 * h + 22 * l) / 451;
+
+    let month = (h + l - 7 * m + 114) / 31;
+    let day   = (h + l - 7 * m + 114) % 31 + 1;
+
+    UTC.ymd(year, month as u32, day as u32)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn easter_returns_date_of_easter_sunday() {
+    assert_eq!(easter(2015), UTC.ymd(2015, 4,  5));
+    assert_eq!(easter(2016), UTC.ymd(2016, 3, 27));
+    assert_eq!(easter(2024), UTC.ymd(2024, 3, 31));
+    assert_eq!(easter(2025), UTC.ymd(2025, 4, 20));
+
+    // Years before 1 are counted astronomically, year 0 being 1 BC.
+    assert_eq!(easter(1),    UTC.ymd(1, 4, 1));
+    assert_eq!(easter(0),    UTC.ymd(0, 4, 9));
+    assert_eq!(easter(-1),   UTC.ymd(-1, 4, 18));
+    assert_eq!(easter(-100), UTC.ymd(-100, 4, 8));
+}
+
+#[test]
+fn rules_compute_dates_in_the_given_year() {
+    assert_eq!(Rule::Fixed(7, 4).date(2015),   UTC.ymd(2015, 7, 4));
+    assert_eq!(Rule::Easter(-2).date(2015),    UTC.ymd(2015, 4, 3));
+    assert_eq!(Rule::NthWeekday(11, Weekday::Thu, 4).date(2015),
+               UTC.ymd(2015, 11, 26));
+    assert_eq!(Rule::LastWeekday(5, Weekday::Mon).date(2015),
+               UTC.ymd(2015, 5, 25));
+    assert_eq!(Rule::LastWeekday(12, Weekday::Thu).date(2015),
+               UTC.ymd(2015, 12, 31));
+}
+
+#[test]
+fn country_returns_holidays_ordered_by_date() {
+    let holidays = country("de").unwrap().holidays(2015);
+
+    assert_eq!(holidays.len(), 9);
+    assert_eq!(holidays[1], Holiday { date: UTC.ymd(2015, 4, 3),
+                                      name: "Karfreitag".to_string() });
+    assert!(holidays.windows(2).all(|w| w[0].date <= w[1].date));
+
+    assert!(country("XX").is_none());
+}
+
+#[test]
+fn holidays_can_be_formatted_as_csv() {
+    let holidays = vec![
+        Holiday { date: UTC.ymd(2015, 1, 1),   name: "New Year's Day".to_string() },
+        Holiday { date: UTC.ymd(2015, 12, 26),
+                  name: "St. Stephen's Day, \"Boxing Day\"".to_string() }
+    ];
+
+    assert_eq!(to_csv(&holidays), "date,name
+2015-01-01,New Year's Day
+2015-12-26,\"St. Stephen's Day, \"\"Boxing Day\"\"\"
+");
+}
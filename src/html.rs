@@ -0,0 +1,144 @@
+//! HTML output.
+//!
+//! The year is rendered as a standalone HTML document with one `<table>` per
+//! month, in rows of `months_per_line` months. Days are marked with the CSS
+//! class of their role (`weekend`, `holiday`, `heat-1` and up, `marked` or
+//! `highlight`) and with `event` and `today`, so the look can be changed by
+//! overriding the embedded style sheet.
+
+use date::{ Date, DateRange };
+use events::Event;
+use format::{ column, day_number, day_role, days_per_week, format_dates, has_events, month_name,
+              month_rows, months, range_title, weekday_names, weeks, Options, Renderer };
+
+const STYLE: &str = "
+body     { font-family: sans-serif; }
+.row     { white-space: nowrap; }
+.month   { display: inline-block; vertical-align: top; margin: 1em; }
+caption  { font-weight: bold; }
+td, th   { text-align: right; padding: 0.1em 0.3em; }
+.weekend { color: #777; }
+.holiday { color: #c00; }
+.event   { text-decoration: underline; }
+.today     { outline: 1px solid; }
+.highlight { background: #ff0; }
+";
+
+#[derive(Copy, Clone, Debug)]
+pub struct Html {
+    pub months_per_line: usize
+}
+
+impl Renderer for Html {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        let mut result = String::new();
+
+        result.push_str("<!DOCTYPE html>\n<html>\n<head>\n");
+        result.push_str("<meta charset=\"utf-8\">\n");
+        result.push_str(&format!("<title>{}</title>\n", range_title(range, options)));
+        result.push_str(&format!("<style>{}</style>\n", STYLE));
+        result.push_str("</head>\n<body>\n");
+        result.push_str(&format!("<h1>{}</h1>\n", range_title(range, options)));
+
+        for row in month_rows(months(range, options), self.months_per_line) {
+            result.push_str("<div class=\"row\">\n");
+            for month in row {
+                result.push_str(&render_month(month, options));
+            }
+            result.push_str("</div>\n");
+        }
+
+        if !legend.is_empty() {
+            result.push_str("<ul class=\"legend\">\n");
+
+            for event in legend {
+                result.push_str(&format!("<li>{} {}</li>\n", format_dates(event.dates),
+                                                            escape(&event.description)));
+            }
+
+            result.push_str("</ul>\n");
+        }
+
+        result.push_str("</body>\n</html>\n");
+        result
+    }
+}
+
+fn render_month(month: DateRange, options: &Options) -> String {
+    let mut result = String::new();
+
+    result.push_str("<table class=\"month\">\n");
+    result.push_str(&format!("<caption>{}</caption>\n", month_name(month.start, options)));
+    result.push_str("<tr>");
+    for name in weekday_names(options) {
+        result.push_str(&format!("<th>{}</th>", name));
+    }
+    result.push_str("</tr>\n");
+
+    for week in weeks(month, options) {
+        result.push_str("<tr>");
+        result.push_str(&"<td></td>".repeat(column(week.start, options) as usize));
+        result.extend(week.map(|date| render_day(date, options)));
+        let pad_right = days_per_week(options) - 1 - column(week.end.pred(), options);
+        result.push_str(&"<td></td>".repeat(pad_right as usize));
+        result.push_str("</tr>\n");
+    }
+
+    result.push_str("</table>\n");
+    result
+}
+
+fn render_day(date: Date, options: &Options) -> String {
+    let mut classes = day_role(date, options).class().into_iter().collect::<Vec<_>>();
+    if has_events(date, options) {
+        classes.push("event".to_string());
+    }
+    if options.today == Some(date) {
+        classes.push("today".to_string());
+    }
+
+    if classes.is_empty() {
+        format!("<td>{}</td>", day_number(date, options))
+    } else {
+        format!("<td class=\"{}\">{}</td>", classes.join(" "), day_number(date, options))
+    }
+}
+
+/// Escapes the characters with a special meaning in HTML and XML.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+
+#[test]
+fn render_month_renders_table_with_classes() {
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        holidays: vec![UTC.ymd(2015, 2, 2)].into_iter().collect(),
+        events:   vec![UTC.ymd(2015, 2, 2)].into_iter().collect(),
+        today:    Some(UTC.ymd(2015, 2, 28)),
+        ..Options::default()
+    };
+
+    let html  = render_month(month, &options);
+    let lines = html.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[1], "<caption>February</caption>");
+    assert_eq!(lines[3], "<tr><td></td><td></td><td></td><td></td><td></td><td></td>\
+                          <td class=\"weekend\">1</td></tr>");
+    assert!(lines[4].starts_with("<tr><td class=\"holiday event\">2</td><td>3</td>"));
+    assert!(lines[7].ends_with("<td class=\"weekend today\">28</td><td></td></tr>"));
+}
+
+#[test]
+fn escape_escapes_special_characters() {
+    assert_eq!(escape("Tom & Jerry <3"), "Tom &amp; Jerry &lt;3");
+}
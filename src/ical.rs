@@ -0,0 +1,346 @@
+//! Import of events from iCalendar (.ics) files.
+//!
+//! Only the parts needed to place events into a calendar are supported:
+//! the `DTSTART`, `DTEND`, `SUMMARY` and `RRULE` properties of `VEVENT`
+//! components. Times and time zones are ignored, only the dates matter.
+
+use chrono::{ TimeZone, UTC };
+use date::{ Date, DateRange };
+use diagnostics::{ Diagnostics, Kind };
+use events::Event;
+use recurrence::{ Recurrence, Rule };
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Parses the events in an iCalendar file. Their descriptions are the
+/// summaries.
+pub fn parse(input: &str) -> Result<Vec<Event>, String> {
+    parse_events(input, None)
+}
+
+/// Parses the events like `parse`, but leaves out the events that can't be
+/// read, like those with unsupported recurrence rules, with a warning.
+pub fn parse_lenient(input: &str, diagnostics: &mut Diagnostics) -> Vec<Event> {
+    parse_events(input, Some(diagnostics)).unwrap_or_default()
+}
+
+/// Parses the events, failing on the first one that can't be read unless
+/// there are `diagnostics` to record it in.
+fn parse_events(input: &str, mut diagnostics: Option<&mut Diagnostics>)
+    -> Result<Vec<Event>, String>
+{
+    let mut result = Vec::new();
+    let mut event: Option<EventBuilder> = None;
+
+    for line in unfold(input) {
+        let (name, value) = match split_property(&line) {
+            Some(property) => property,
+            None           => continue
+        };
+
+        match (name, value) {
+            ("BEGIN", "VEVENT") => event = Some(EventBuilder::default()),
+            ("END",   "VEVENT") => {
+                if let Some(event) = event.take() {
+                    let summary = event.summary.clone();
+
+                    match (event.build(), diagnostics.as_mut()) {
+                        (Ok(event), _)              => result.push(event),
+                        (Err(e), Some(diagnostics)) => {
+                            diagnostics.warn(Kind::SkippedEvent,
+                                             format!("skipped event '{}': {}", summary, e));
+                        },
+                        (Err(e), None)              => return Err(e)
+                    }
+                }
+            },
+            (name, value) => if let Some(ref mut event) = event {
+                if let Err(e) = event.set(name, value) {
+                    event.error.get_or_insert(e);
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads and parses an iCalendar file.
+pub fn load(path: &Path) -> Result<Vec<Event>, String> {
+    let mut input = String::new();
+
+    File::open(path).and_then(|mut file| file.read_to_string(&mut input))
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    parse(&input).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Reads and parses an iCalendar file like `load`, but leaves out the
+/// events that can't be read, with a warning.
+pub fn load_lenient(path: &Path, diagnostics: &mut Diagnostics) -> Result<Vec<Event>, String> {
+    let mut input = String::new();
+
+    File::open(path).and_then(|mut file| file.read_to_string(&mut input))
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut found  = Diagnostics::new();
+    let     events = parse_lenient(&input, &mut found);
+
+    for warning in found.warnings() {
+        diagnostics.warn(warning.kind, format!("{}: {}", path.display(), warning));
+    }
+
+    Ok(events)
+}
+
+/// Writes the events as an iCalendar file of all-day events. Recurrence
+/// rules are not written, so recurring events should be expanded first.
+pub fn write(events: &[Event]) -> String {
+    let mut result = String::new();
+
+    result.push_str("BEGIN:VCALENDAR\r\n");
+    result.push_str("VERSION:2.0\r\n");
+    result.push_str("PRODID:-//calendar.rs//calendar//EN\r\n");
+
+    for (index, event) in events.iter().enumerate() {
+        let start = event.dates.start.format("%Y%m%d");
+
+        result.push_str("BEGIN:VEVENT\r\n");
+        result.push_str(&format!("UID:{}-{}@calendar.rs\r\n", start, index));
+        result.push_str(&format!("DTSTAMP:{}T000000Z\r\n", start));
+        result.push_str(&format!("DTSTART;VALUE=DATE:{}\r\n", start));
+        result.push_str(&format!("DTEND;VALUE=DATE:{}\r\n", event.dates.end.format("%Y%m%d")));
+        result.push_str(&format!("SUMMARY:{}\r\n", escape(&event.description)));
+        result.push_str("END:VEVENT\r\n");
+    }
+
+    result.push_str("END:VCALENDAR\r\n");
+    result
+}
+
+#[derive(Default)]
+struct EventBuilder {
+    start:   Option<DateValue>,
+    end:     Option<DateValue>,
+    summary: String,
+    rule:    Option<Rule>,
+    /// The first property that couldn't be read.
+    error:   Option<String>
+}
+
+impl EventBuilder {
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "DTSTART" => self.start   = Some(parse_date_value(value)?),
+            "DTEND"   => self.end     = Some(parse_date_value(value)?),
+            "SUMMARY" => self.summary = unescape(value),
+            "RRULE"   => self.rule    = Some(value.parse()?),
+            _         => {}
+        }
+
+        Ok(())
+    }
+
+    fn build(self) -> Result<Event, String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let start = self.start.ok_or("event without DTSTART")?.date;
+        let end   = match self.end {
+            // The end of an event is exclusive, so an event ending at midnight
+            // doesn't take place on that day.
+            Some(DateValue { date, midnight: true }) => date,
+            Some(DateValue { date, .. })             => date.succ(),
+            None                                     => start.succ()
+        };
+
+        // Events ending before they start are taken to last for a day.
+        let dates = DateRange::new(start, end.max(start.succ()));
+
+        Ok(Event { recurrence: self.rule.map(Recurrence::Rule),
+                   ..Event::new(dates, &self.summary) })
+    }
+}
+
+/// Value of a DTSTART or DTEND property.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct DateValue {
+    date: Date,
+    /// Whether this is either a date without time or a time at midnight.
+    midnight: bool
+}
+
+/// Joins lines folded according to RFC 5545 (continuation lines start with
+/// a space or a tab).
+fn unfold(input: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+
+    for line in input.lines() {
+        let line = line.trim_end_matches('\r');
+
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = result.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+
+        result.push(line.to_string());
+    }
+
+    result
+}
+
+/// Splits a content line into the property name (without parameters) and
+/// value.
+fn split_property(line: &str) -> Option<(&str, &str)> {
+    let colon = line.find(':')?;
+    let name  = &line[..colon];
+    let name  = name.split(';').next().unwrap_or(name);
+
+    Some((name, &line[colon + 1..]))
+}
+
+/// Parses a DATE (`20150314`) or DATE-TIME (`20150314T093000Z`) value.
+fn parse_date_value(value: &str) -> Result<DateValue, String> {
+    let error = || format!("invalid date: '{}'", value);
+    let digits = |range: ::std::ops::Range<usize>| {
+        value.get(range).and_then(|s| s.parse::<u32>().ok()).ok_or_else(error)
+    };
+
+    let year  = digits(0..4)? as i32;
+    let month = digits(4..6)?;
+    let day   = digits(6..8)?;
+    let date  = UTC.ymd_opt(year, month, day).single().ok_or_else(error)?;
+
+    let midnight = match value.get(8..) {
+        None | Some("") => true,
+        Some(time)      => time.trim_start_matches('T')
+                               .trim_end_matches('Z')
+                               .chars()
+                               .all(|c| c == '0')
+    };
+
+    Ok(DateValue { date, midnight })
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars  = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push(' '),
+                Some(c)               => result.push(c),
+                None                  => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' | ';' | ',' => { result.push('\\'); result.push(c); },
+            '\n'             => result.push_str("\\n"),
+            c                => result.push(c)
+        }
+    }
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn parse_reads_events() {
+    let input = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20150314\r
+SUMMARY:Pi day\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20150720\r
+DTEND;VALUE=DATE:20150725\r
+SUMMARY:Holiday in Prague\\, Czech\r
+  Republic\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;TZID=Europe/Prague:20150102T090000\r
+DTEND;TZID=Europe/Prague:20150102T100000\r
+RRULE:FREQ=WEEKLY;COUNT=4\r
+SUMMARY:Meeting\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    let expected = vec![
+        Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)),
+                   "Pi day"),
+        Event::new(DateRange::new(UTC.ymd(2015, 7, 20), UTC.ymd(2015, 7, 25)),
+                   "Holiday in Prague, Czech Republic"),
+        Event {
+            recurrence: Some("FREQ=WEEKLY;COUNT=4".parse().unwrap()),
+            ..Event::new(DateRange::new(UTC.ymd(2015, 1, 2), UTC.ymd(2015, 1, 3)),
+                         "Meeting")
+        }
+    ];
+
+    assert_eq!(parse(input), Ok(expected));
+}
+
+#[test]
+fn parse_lenient_skips_unreadable_events() {
+    let input = "BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20150314\r
+RRULE:FREQ=MONTHLY;BYSETPOS=-1\r
+SUMMARY:Review\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20150315\r
+SUMMARY:Pi day after\r
+END:VEVENT\r
+";
+    let mut diagnostics = Diagnostics::new();
+
+    assert!(parse(input).is_err());
+    assert_eq!(parse_lenient(input, &mut diagnostics),
+               vec![Event::new(DateRange::new(UTC.ymd(2015, 3, 15), UTC.ymd(2015, 3, 16)),
+                               "Pi day after")]);
+    assert_eq!(diagnostics.warnings().len(), 1);
+    assert_eq!(diagnostics.warnings()[0].kind, Kind::SkippedEvent);
+    assert!(diagnostics.warnings()[0].message.starts_with("skipped event 'Review': "));
+}
+
+#[test]
+fn parse_date_value_parses_dates_and_date_times() {
+    assert_eq!(parse_date_value("20150314"),
+               Ok(DateValue { date: UTC.ymd(2015, 3, 14), midnight: true }));
+    assert_eq!(parse_date_value("20150314T000000Z"),
+               Ok(DateValue { date: UTC.ymd(2015, 3, 14), midnight: true }));
+    assert_eq!(parse_date_value("20150314T093000"),
+               Ok(DateValue { date: UTC.ymd(2015, 3, 14), midnight: false }));
+    assert!(parse_date_value("2015031").is_err());
+    assert!(parse_date_value("20150230").is_err());
+}
+
+#[test]
+fn written_events_can_be_parsed_back() {
+    let events = vec![
+        Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)), "Pi day"),
+        Event::new(DateRange::new(UTC.ymd(2015, 7, 20), UTC.ymd(2015, 7, 25)),
+                   "Holiday in Prague, Czech Republic; summer")
+    ];
+
+    assert_eq!(parse(&write(&events)), Ok(events));
+}
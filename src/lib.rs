@@ -0,0 +1,94 @@
+//! Calendar generation.
+//!
+//! The library lays out the months of a year (or any other range of dates)
+//! and renders them as plain text, HTML, Markdown or SVG, optionally marking
+//! weekends, holidays and days with events.
+//!
+//! ```
+//! use calendar::format::Options;
+//!
+//! let calendar = calendar::render_year(2015, &Options::default()).unwrap();
+//! assert!(calendar.starts_with("       January       "));
+//! ```
+//!
+//! Other formats render into a string the same way, with `render_to_string`
+//! and their renderer, e.g. `render_to_string(&Agenda, range, &options, &[])`.
+//!
+//! The `Calendar` builder gives access to all options and output formats.
+//! The `prelude` module exports what is needed to use it; the rest of the
+//! public modules expose the building blocks for custom renderers.
+//!
+//! # Features
+//!
+//! - `providers` (default): built-in public holiday rules, the `holidays`
+//!   module.
+//! - `cli` (default): the `calendar` program. Depend on the library with
+//!   `default-features = false` to leave out the dependencies it needs.
+//! - `serde`: `Serialize` and `Deserialize` for date ranges, events and
+//!   holidays, see the `serialization` module, and `Serialize` for
+//!   calendars with the output they render. The `calendar` program uses it
+//!   for JSON output.
+
+extern crate chrono;
+extern crate unicode_width;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+mod builder;
+pub mod advanced_iterator;
+pub mod agenda;
+pub mod cal;
+pub mod calendars;
+pub mod chart;
+pub mod cron;
+pub mod date;
+pub mod diagnostics;
+pub mod events;
+pub mod format;
+#[cfg(feature = "providers")]
+pub mod holidays;
+pub mod html;
+pub mod ical;
+pub mod linear;
+pub mod list;
+pub mod load;
+pub mod markdown;
+pub mod moon;
+pub mod parse;
+pub mod payroll;
+pub mod random;
+pub mod recurrence;
+pub mod remind;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod skeleton;
+pub mod store;
+pub mod sun;
+pub mod style;
+pub mod svg;
+pub mod width;
+
+pub use builder::Calendar;
+pub use date::{ Date, DateRange };
+pub use format::{ layout_month, render_to_string, LayoutConfig, Options };
+
+/// The types needed to configure and render calendars.
+pub mod prelude {
+    pub use builder::Calendar;
+    pub use date::{ Date, DateRange };
+    pub use events::Event;
+    pub use format::{ Format, LayoutConfig, Options, Renderer };
+    pub use style::Style;
+    pub use svg::PageSize;
+    pub use width::Width;
+}
+
+use format::{ Renderer, Text };
+
+/// Renders the year as plain text, three months per line, or fails if the
+/// year isn't supported.
+pub fn render_year(year: i32, options: &Options) -> Result<String, String> {
+    Ok(Text { months_per_line: 3, banner: false }.render(date::dates(year)?, options, &[]))
+}
@@ -0,0 +1,74 @@
+//! Linear text output for very narrow terminals.
+//!
+//! Every month is its name and year followed by one line per day, with the
+//! abbreviated weekday before the day, so the output stays readable in a
+//! pane too narrow for even a compact month. Days are styled and marked as
+//! in the text output, and followed by the date in the calendar of the
+//! overlay, if any.
+
+use advanced_iterator::AdvancedIterator;
+use date::{ weekday, DateRange };
+use events::Event;
+use format::{ days_per_week, day_spans, format_dates, month_name, months, paint,
+              render_to_string, Options, Renderer };
+use std::io::{ self, Write };
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+#[derive(Copy, Clone, Debug)]
+pub struct Linear;
+
+impl Renderer for Linear {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        render_to_string(self, range, options, legend)
+    }
+
+    /// Writes a month at a time.
+    fn render_to(&self, out: &mut dyn Write, range: DateRange, options: &Options,
+                 legend: &[Event]) -> io::Result<()>
+    {
+        months(range, options).into_iter()
+                              .map(|month| render_month(month, options))
+                              .write_join(out, "\n")?;
+
+        if !legend.is_empty() {
+            out.write_all(b"\n")?;
+        }
+
+        for event in legend {
+            writeln!(out, "{}\n  {}", format_dates(event.dates), event.description)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn render_month(month: DateRange, options: &Options) -> String {
+    let mut result = format!("{} {}\n", &month_name(month.start, options)[..3],
+                                         options.reckoning.ymd(month.start).0);
+
+    for date in month.filter(|&date| weekday(date) < days_per_week(options)) {
+        let overlay = options.overlay.map(|system| format!("  {}", system.name(date)))
+                                     .unwrap_or_default();
+        result.push_str(&format!("{}{}{}\n", WEEKDAYS[weekday(date) as usize],
+                                             paint(&day_spans(date, options), options),
+                                             overlay));
+    }
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn render_lists_one_day_per_line() {
+    use chrono::{ TimeZone, UTC };
+
+    let range   = DateRange::new(UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 3));
+    let options = Options { events:   vec![UTC.ymd(2015, 2, 2)].into_iter().collect(),
+                            workweek: true,
+                            ..Options::default() };
+
+    assert_eq!(Linear.render(range, &options, &[]),
+               "Jan 2015\nFr 30\n\nFeb 2015\nMo *2\n");
+}
@@ -0,0 +1,115 @@
+//! Plain list output.
+//!
+//! Every day is a line with its date and weekday, followed by its holidays
+//! and events, and with a location when the sun is up and whether the
+//! clocks change, each tagged with what it is:
+//!
+//! ```text
+//! 2015-07-03 Friday  [holiday: Independence Day] [event: Trip]
+//! ```
+//!
+//! Nothing is styled or laid out in columns, so the output reads well in a
+//! screen reader and lines can be found with `grep`. The events given as
+//! the legend are listed on their days, like in agenda output.
+
+use date::{ weekday, Date, DateRange };
+use events::Event;
+use format::{ change_label, clock_change, daylight_of, days_per_week, render_to_string, Options,
+              Renderer };
+use std::collections::HashMap;
+use std::io::{ self, Write };
+
+#[derive(Copy, Clone, Debug)]
+pub struct List {
+    /// Whether days without holidays or events are left out.
+    pub annotated: bool
+}
+
+impl Renderer for List {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        render_to_string(self, range, options, legend)
+    }
+
+    /// Writes a month at a time.
+    fn render_to(&self, out: &mut dyn Write, range: DateRange, options: &Options,
+                 legend: &[Event]) -> io::Result<()>
+    {
+        for month in range.by_month() {
+            let events = events_by_date(legend, month);
+            let lines  = month.filter(|&date| weekday(date) < days_per_week(options))
+                              .filter_map(|date| {
+                                  render_day(date, options, &events, self.annotated)
+                              })
+                              .collect::<String>();
+            out.write_all(lines.as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The descriptions of the events on each of their days in the range, in
+/// the order of the events.
+fn events_by_date(events: &[Event], range: DateRange) -> HashMap<Date, Vec<&str>> {
+    let mut by_date: HashMap<Date, Vec<&str>> = HashMap::new();
+    for event in events {
+        for date in event.dates.intersect(range).into_iter().flatten() {
+            by_date.entry(date).or_default().push(&event.description[..]);
+        }
+    }
+    by_date
+}
+
+/// The line of the day, or `None` if it is left out for lacking holidays
+/// and events.
+fn render_day(date: Date, options: &Options, events: &HashMap<Date, Vec<&str>>,
+              annotated: bool) -> Option<String>
+{
+    let holidays = options.holiday_names.get(&date).into_iter().flatten()
+                          .map(|name| format!("[holiday: {}]", name));
+    let events   = events.get(&date).into_iter().flatten()
+                         .map(|description| format!("[event: {}]", description));
+    let notes    = holidays.chain(events).collect::<Vec<_>>();
+
+    // Every day has a sunrise, so it doesn't make the day worth listing.
+    if annotated && notes.is_empty() {
+        return None;
+    }
+
+    let sun     = daylight_of(date, options).map(|daylight| format!("[sun: {}]", daylight));
+    let clocks  = clock_change(date, options).map(|change| {
+                      format!("[clocks: {}]", change_label(change))
+                  });
+    let notes   = notes.into_iter().chain(sun).chain(clocks).collect::<Vec<_>>();
+
+    let day = date.format("%Y-%m-%d %A").to_string();
+
+    if notes.is_empty() {
+        Some(format!("{}\n", day))
+    } else {
+        Some(format!("{}  {}\n", day, notes.join(" ")))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn render_lists_holidays_and_events_on_their_days() {
+    use chrono::{ TimeZone, UTC };
+
+    let range   = DateRange::new(UTC.ymd(2015, 7, 3), UTC.ymd(2015, 7, 6));
+    let events  = [Event::new(DateRange::new(UTC.ymd(2015, 7, 3), UTC.ymd(2015, 7, 5)), "Trip")];
+    let mut options = Options::default();
+    options.holiday_names.insert(UTC.ymd(2015, 7, 3), vec!["Independence Day".to_string(),
+                                                          "Hot Dog Day".to_string()]);
+
+    assert_eq!(List { annotated: false }.render(range, &options, &events),
+               "2015-07-03 Friday  [holiday: Independence Day] [holiday: Hot Dog Day] \
+                [event: Trip]\n\
+                2015-07-04 Saturday  [event: Trip]\n\
+                2015-07-05 Sunday\n");
+    assert_eq!(List { annotated: true }.render(range, &options, &events),
+               "2015-07-03 Friday  [holiday: Independence Day] [holiday: Hot Dog Day] \
+                [event: Trip]\n\
+                2015-07-04 Saturday  [event: Trip]\n");
+}
@@ -0,0 +1,162 @@
+//! Capacity planning.
+//!
+//! Compares how many days are taken by events with the working time that is
+//! available, per week or month. Events have no times of day, so both are
+//! counted in days: every day an event takes place on counts as an
+//! event-day, and every business day as an available day.
+
+use chart::{ render, Bar, Scale };
+use chrono::Datelike;
+use date::{ is_business_day, week_number, Date, DateRange, WeekNumbering };
+use events::{ expand, Event };
+use std::str::FromStr;
+use width::Width;
+
+/// Periods the report is split into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Period {
+    /// Weeks of the numbering, which also gives the day they start on.
+    Week(WeekNumbering),
+    Month
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Period, String> {
+        match s {
+            "week"  => Ok(Period::Week(WeekNumbering::Iso)),
+            "month" => Ok(Period::Month),
+            _       => Err(format!("unknown period: '{}' (available: week, month)", s))
+        }
+    }
+}
+
+/// Load of a period.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Load {
+    pub period:    DateRange,
+    /// Event-days on business days. Overlapping events each count, so this
+    /// can exceed `available`.
+    pub busy:      u32,
+    /// Business days: Monday to Friday, except holidays.
+    pub available: u32
+}
+
+/// Returns the load of every week or month in the range. The first and last
+/// period may be partial.
+pub fn load<F>(events: &[Event], range: DateRange, period: Period, is_holiday: F) -> Vec<Load>
+    where F: Fn(Date) -> bool
+{
+    let business_day = |date: Date| is_business_day(date, &is_holiday);
+    let occurrences  = expand(events, range);
+
+    periods(range, period).into_iter()
+                          .map(|dates| {
+                              let busy = occurrences.iter()
+                                                    .flat_map(|event| event.dates)
+                                                    .filter(|&date| dates.contains(date)
+                                                                    && business_day(date))
+                                                    .count();
+                              let available = dates.filter(|&date| business_day(date)).count();
+
+                              Load { period: dates, busy: busy as u32, available: available as u32 }
+                          })
+                          .collect()
+}
+
+fn periods(range: DateRange, period: Period) -> Vec<DateRange> {
+    let starts_period = |date: Date| match period {
+        Period::Week(numbering) => date.weekday() == numbering.first_day()
+                                   || (numbering != WeekNumbering::Iso && date.ordinal() == 1),
+        Period::Month           => date.day() == 1
+    };
+
+    range.collect::<Vec<_>>()
+         .split_inclusive(|&date| starts_period(date.succ()))
+         .map(|days| DateRange::new(days[0], days[days.len() - 1].succ()))
+         .collect()
+}
+
+/// Label of a period: the week (`2015-W09`) or the month (`2015-03`).
+fn label(load: &Load, period: Period) -> String {
+    match period {
+        Period::Week(numbering) => {
+            let (year, week) = week_number(&load.period.start, numbering);
+            format!("{}-W{:02}", year, week)
+        },
+        Period::Month           => load.period.start.format("%Y-%m").to_string()
+    }
+}
+
+/// Renders the loads as a bar chart, a line per period. A bar of `width`
+/// columns stands for all available days, and is followed by `+` if the
+/// period is overbooked.
+pub fn chart(loads: &[Load], period: Period, width: usize) -> String {
+    let bars = loads.iter()
+                    .map(|load| {
+                        let (ratio, percent) = match load.available {
+                            0         => (0.0, "-".to_string()),
+                            available => (load.busy as f64 / available as f64,
+                                          format!("{}%", load.busy * 100 / available))
+                        };
+                        let annotation = format!("{:>2}/{:<2} days {:>5}", load.busy,
+                                                                          load.available,
+                                                                          percent);
+
+                        Bar::new(&label(load, period), ratio, &annotation)
+                    })
+                    .collect::<Vec<_>>();
+
+    render(&bars, width, Scale::Fixed(1.0), &Width::default())
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+
+#[test]
+fn load_counts_event_days_on_business_days() {
+    let range  = DateRange::new(UTC.ymd(2015, 3, 2), UTC.ymd(2015, 3, 16));
+    let events = vec![Event::new(DateRange::new(UTC.ymd(2015, 3, 5), UTC.ymd(2015, 3, 10)),
+                                 "Conference"),
+                      Event::new(DateRange::new(UTC.ymd(2015, 3, 9), UTC.ymd(2015, 3, 10)),
+                                 "Review")];
+    let week   = Period::Week(WeekNumbering::Iso);
+    let loads  = load(&events, range, week, |date| date == UTC.ymd(2015, 3, 13));
+
+    assert_eq!(loads.len(), 2);
+    assert_eq!((loads[0].busy, loads[0].available), (2, 5));
+    assert_eq!((loads[1].busy, loads[1].available), (2, 4));
+
+    assert_eq!(chart(&loads, week, 10), "\
+2015-W10  ████         2/5  days   40%
+2015-W11  █████        2/4  days   50%
+");
+}
+
+#[test]
+fn load_splits_range_into_weeks_of_the_numbering() {
+    let range = DateRange::new(UTC.ymd(2015, 12, 26), UTC.ymd(2016, 1, 5));
+    let week  = Period::Week(WeekNumbering::Us);
+    let loads = load(&[], range, week, |_| false);
+
+    assert_eq!(loads.iter().map(|load| load.period).collect::<Vec<_>>(),
+               [DateRange::new(UTC.ymd(2015, 12, 26), UTC.ymd(2015, 12, 27)),
+                DateRange::new(UTC.ymd(2015, 12, 27), UTC.ymd(2016, 1, 1)),
+                DateRange::new(UTC.ymd(2016, 1, 1), UTC.ymd(2016, 1, 3)),
+                DateRange::new(UTC.ymd(2016, 1, 3), UTC.ymd(2016, 1, 5))]);
+    assert!(chart(&loads, week, 10).starts_with("2015-W52"));
+}
+
+#[test]
+fn load_splits_range_into_months() {
+    let range = DateRange::new(UTC.ymd(2015, 1, 20), UTC.ymd(2015, 3, 1));
+    let loads = load(&[], range, Period::Month, |_| false);
+
+    assert_eq!(loads.iter().map(|load| load.period).collect::<Vec<_>>(),
+               [DateRange::new(UTC.ymd(2015, 1, 20), UTC.ymd(2015, 2, 1)),
+                DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1))]);
+    assert_eq!(loads[1].available, 20);
+}
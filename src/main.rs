@@ -1,46 +1,1552 @@
+extern crate calendar;
+extern crate clap;
+extern crate clap_complete;
 extern crate chrono;
-extern crate docopt;
-extern crate rustc_serialize;
+extern crate crossterm;
+extern crate notify;
+extern crate serde_json;
+#[cfg(unix)]
+extern crate signal_hook;
+extern crate terminal_size;
+extern crate toml;
+extern crate ureq;
 
-mod advanced_iterator;
-mod date;
-mod format;
+mod bench;
+mod bookmarks;
+mod cache;
+mod cli;
+mod config;
+mod edit;
+mod pager;
+mod query;
+mod remote;
+mod resolve;
+mod signals;
+mod terminal;
+mod tui;
+mod watch;
 
-use advanced_iterator::AdvancedIterator;
-use date::dates;
-use format::layout_month;
-use docopt::Docopt;
+use bookmarks::Bookmarks;
+use cache::{ format_age, format_size, Cache };
+use calendar::date::{ self, add_business_days, anniversaries, business_days_between,
+                      days_between, is_business_day, julian_day, months_and_days_between,
+                      months_around, months_from, week_number, weekday, Date, DateRange,
+                      Reckoning, WeekNumbering, Zone };
+use calendar::cron::Schedule;
+use calendar::diagnostics::Diagnostics;
+use calendar::events::Event;
+use calendar::format::{ self, Format, Frequencies, Options };
+use calendar::holidays::{ self, HolidayProvider, RuleSet };
+use calendar::ical;
+use calendar::load;
+use calendar::moon;
+use calendar::payroll;
+use calendar::random;
+use calendar::recurrence::{ NamedRule, Rule };
+use calendar::parse::{ normalize_dates, parse_date, parse_date_range, parse_month,
+                       parse_month_day, parse_quarter, parse_weekday, parse_year, parse_year_range,
+                       DateOrder };
+use calendar::skeleton::{ skeleton, Markup };
+use calendar::store::{ EventStore, Sources };
+use calendar::style::Style;
+use calendar::svg::PageSize;
+use calendar::{ Calendar, LayoutConfig };
+use chrono::{ Datelike, Weekday };
+use clap_complete::Shell;
+use crossterm::{ cursor, execute };
+use crossterm::terminal::{ Clear, ClearType };
+use cli::{ parse_context, parse_count, parse_day_width, parse_jobs, parse_month_count,
+           parse_days, parse_month_gap, parse_months_per_line, parse_seed, parse_year_count,
+           Args };
+use config::{ Config, Person, Settings };
+use edit::EventsFile;
+use pager::Pager;
+use resolve::RangeArgs;
+use signals::{ Signal, Signals };
+use std::cell::OnceCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{ HashMap, HashSet };
+use std::env;
+use std::fmt;
+use std::fs;
+use std::hash::{ Hash, Hasher };
+use std::io::{ self, BufWriter, IsTerminal, Read, Write };
+use std::iter::once;
+use std::path::PathBuf;
+use std::process;
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+use terminal::Displayable;
 
-const USAGE: &'static str = "
-Calendar.
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: bench::CountingAllocator = bench::CountingAllocator;
 
-Usage:
-  calendar <year> [--months-per-line=<num>]
-  calendar (-h | --help)
+/// Width of the bars of the load command, in columns.
+const LOAD_BAR_WIDTH: usize = 20;
 
-Options:
-  -h --help                 Show this screen
-  --months-per-line=<num>   Number of months per line [default: 3]
-";
+/// Styles of the days off of people without a color of their own, in the
+/// order they are given.
+const PERSON_STYLES: [Style; 6] = [Style::Green, Style::Blue, Style::Magenta, Style::Cyan,
+                                   Style::Yellow, Style::Red];
 
-#[derive(Debug, RustcDecodable)]
-struct Args {
-    arg_year: i32,
-    flag_months_per_line: usize
-}
+/// Style of days more than one person of an overlay is off.
+const OVERLAP_STYLE: Style = Style::Underline;
+
+/// Style of the days of rules given by `--mark-rule`.
+const RULE_STYLE: Style = Style::Bold;
 
 fn main() {
-    let args: Args = Docopt::new(USAGE).and_then(|d| d.decode())
-                                       .unwrap_or_else(|e| e.exit());
-
-    let calendar = dates(args.arg_year)
-                  .by_month()
-                  .map(layout_month)
-                  .chunk(args.flag_months_per_line)
-                  .map(|c| c.transpose())
-                  .chain_all()
-                  .map(|c| c.collect::<String>())
-                  .join("\n");
-
-    println!("{}", calendar);
+    let args = Args::parse(env::args()).unwrap_or_else(|e| e.exit());
+
+    let result = match args.command() {
+        Some("around")       => print_around(&args),
+        Some("day")          => print_day(&args),
+        Some("week")         => print_week(&args),
+        Some("holidays")     => print_holidays(&args),
+        Some("skeleton")     => print_skeleton(&args),
+        Some("random")       => print_random(&args),
+        Some("occurrences")  => print_occurrences(&args),
+        Some("query")        => print_query(&args),
+        Some("load")         => print_load(&args),
+        Some("periods")      => print_periods(&args),
+        Some("bookmark")     => manage_bookmarks(&args),
+        Some("event")        => edit_events(&args),
+        Some("until")        => print_until(&args),
+        Some("diff")         => print_diff(&args),
+        Some("workdays")     => print_workdays(&args),
+        Some("add-workdays") => print_add_workdays(&args),
+        Some("weekday-of")   => print_weekday_of(&args),
+        Some("cache")        => manage_cache(&args),
+        Some("completions")  => print_completions(&args),
+        Some("bench")        => print_bench(&args),
+        _                    => print_calendar(&args)
+    };
+
+    exit_on_error(result);
+}
+
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(error) = result {
+        eprintln!("calendar: {}", error);
+        process::exit(1);
+    }
+}
+
+fn print_bench(args: &Args) -> Result<(), String> {
+    let years  = get_opt(args, "--years").map(parse_year_count)
+                                          .transpose()?
+                                          .unwrap_or(bench::DEFAULT_YEARS);
+    let phases = bench::run(years);
+    let total  = bench::Phase {
+        name:        "total",
+        time:        phases.iter().map(|phase| phase.time).sum::<Duration>(),
+        allocations: phases.iter().map(|phase| phase.allocations).sum()
+    };
+
+    // Allocations are only counted in builds with the bench feature.
+    for phase in phases.iter().chain(once(&total)) {
+        let allocations = phase.allocations.map_or_else(|| "-".to_string(), |n| n.to_string());
+        println!("{:<16}{:>12.3} ms{:>12.3} µs/year{:>12} allocs", phase.name,
+                 phase.time.as_secs_f64() * 1e3,
+                 phase.time.as_secs_f64() * 1e6 / f64::from(years),
+                 allocations);
+    }
+
+    Ok(())
+}
+
+/// Shows the calendar of the range given, or browses it with
+/// `--interactive`.
+fn print_calendar(args: &Args) -> Result<(), String> {
+    let settings = settings(args)?;
+    let range    = resolve::resolve(&range_args(args, &settings)?, today(&settings))?;
+    let range    = reckoned(range, &settings);
+
+    if args.get_bool("--interactive") {
+        run_interactive(args, range)
+    } else {
+        print_range(args, range, HashSet::new())
+    }
+}
+
+fn print_around(args: &Args) -> Result<(), String> {
+    let date    = parse_date_arg(args)?;
+    let context = get_opt(args, "--context").map(parse_context)
+                                            .transpose()?
+                                            .unwrap_or(2);
+
+    let range = months_around(date, context, context)?;
+    print_range(args, range, once(date).collect())
+}
+
+/// Collects the arguments describing the range to show.
+fn range_args(args: &Args, settings: &Settings) -> Result<RangeArgs, String> {
+    let input  = DateInput::new(args)?;
+    let expand = |key| get_opt(args, key).map(|value| input.expand(value)).transpose();
+
+    Ok(RangeArgs {
+        range:      expand("<range>")?,
+        last:       expand("<last>")?,
+        from:       expand("--from")?,
+        to:         expand("--to")?,
+        months:     get_opt(args, "--months").map(parse_month_count).transpose()?,
+        around:     args.get_bool("-3"),
+        upcoming:   args.get_bool("--upcoming"),
+        cal:        compat_cal(args),
+        year_start: settings.year_start.filter(|&month| month != 1),
+        quarter:    get_opt(args, "--quarter").map(parse_quarter).transpose()?
+    })
+}
+
+/// Prints the calendar of the months in the range. In watch mode, SIGHUP
+/// starts over with the configuration and events read again, but keeps the
+/// dates given by `--highlight`, as the standard input can be read only once.
+fn print_range(args: &Args, range: DateRange, highlights: HashSet<Date>)
+    -> Result<(), String>
+{
+    let signals = if args.get_bool("--watch") { Some(Signals::listen()?) } else { None };
+    let given   = highlight_args(args)?;
+
+    while show_range(args, range, &highlights, &given, signals.as_ref())? == Signal::Reload {}
+    Ok(())
+}
+
+/// Prints the calendar with the dates given by `--highlight`, watching for
+/// changes given the signals to watch for. Returns the signal that ended
+/// watching, or `Signal::Shutdown` once printed when not watching.
+fn show_range(args: &Args, range: DateRange, highlights: &HashSet<Date>, given: &[Date],
+              signals: Option<&Signals>)
+    -> Result<Signal, String>
+{
+    let settings   = fit_terminal(settings(args)?);
+    let cache_key  = get_opt(args, "--cache-key");
+    let checksum   = cache_key.map(|_| render_checksum(args, &settings)).transpose()?
+                              .unwrap_or_default();
+
+    if let Some(key) = cache_key {
+        if let Some((output, warnings)) = read_cached(key, &checksum)? {
+            print!("{}", terminal::displayable(&output));
+            warnings.iter().for_each(warn);
+            return Ok(Signal::Shutdown);
+        }
+    }
+
+    let bookmarks  = user_bookmarks();
+    let heatmap    = settings.heatmap.unwrap_or(false);
+    let every      = highlight_every(args, range)?;
+    let heat       = if heatmap {
+        Frequencies::count(given.iter().cloned())
+    } else {
+        Frequencies::default()
+    };
+    let highlights = bookmarks.iter()
+                               .map(|(_, date)| date)
+                               .filter(|&date| range.contains(date))
+                               .chain(highlights.iter().cloned())
+                               .chain(every)
+                               .chain(given.iter().cloned().filter(|_| !heatmap))
+                               .collect::<HashSet<_>>();
+
+    let holidays = holiday_dates(&settings, range)?;
+    let quarter  = get_opt(args, "--quarter").is_some();
+
+    // Footnotes and lists name the holidays along with the events.
+    let footnotes = settings.footnotes.unwrap_or(false);
+    let list      = settings.format == Some(Format::List);
+    let named     = if footnotes || list { holiday_events(&settings, range)? } else { Vec::new() };
+
+    let input  = DateInput::new(args)?;
+    let breaks = args.get_vec("--break-at")
+                     .into_iter()
+                     .map(|value| parse_break(value, &input))
+                     .collect::<Result<Vec<_>, _>>()?;
+
+    let people = people(args)?;
+    let mut marks       = HashMap::new();
+    let mut diagnostics = Diagnostics::new();
+    for &(_, ref person, style) in &people {
+        for date in days_off(person, range, &mut diagnostics)? {
+            marks.entry(date).and_modify(|mark| *mark = OVERLAP_STYLE).or_insert(style);
+        }
+    }
+    report(&diagnostics);
+    for rule in args.get_vec("--mark-rule") {
+        let rule = rule.parse::<NamedRule>()?;
+        for date in rule.dates(range.start, range.end, |date| holidays.contains(&date)) {
+            marks.entry(date).or_insert(RULE_STYLE);
+        }
+    }
+    let summary = match settings.format.unwrap_or(Format::Text) {
+        Format::Text => people_summary(&people, &settings, range)?,
+        _            => String::new()
+    };
+
+    let jobs    = get_opt(args, "--jobs").map(parse_jobs).transpose()?.unwrap_or(1);
+    if jobs > 1 && settings.format.unwrap_or(Format::Text) != Format::Text {
+        return Err("--jobs only applies to text output".to_string());
+    }
+    let sources = sources(args, &settings)?;
+    let store   = EventStore::load(sources.clone())?;
+
+    // Built again on changes in watch mode, with the events as last loaded.
+    let build = || {
+        let mut events = store.query(range);
+        events.sort_by_key(|event| event.dates.start);
+
+        let calendar = Calendar::range(range)
+            .months_per_line(settings.months_per_line.unwrap_or_else(|| {
+                if quarter { 3 } else { fitting_months_per_line(&settings) }
+            }))
+            .banner(settings.banner.unwrap_or(true))
+            .week_start(week_start(&settings))
+            .week_numbering(settings.week_numbering)
+            .year_start(settings.year_start.unwrap_or(1))
+            .workweek(settings.workweek.unwrap_or(false))
+            .flip_weeks(settings.flip.unwrap_or(false))
+            .vertical(settings.vertical.unwrap_or(false))
+            .julian(settings.julian.unwrap_or(false))
+            .reckoning(settings.reform.unwrap_or_default())
+            .overlay(settings.overlay)
+            .moon(settings.moon.unwrap_or(false))
+            .daylight(settings.location, settings.timezone.unwrap_or(Zone::Local))
+            .title_format(settings.title_format.clone().unwrap_or_default())
+            .weekday_header(settings.weekday_header.unwrap_or(false))
+            .layout(layout(&settings))
+            .width(settings.width.clone().unwrap_or_default())
+            .weekend_style(settings.weekends.unwrap_or(Style::Plain))
+            .holiday_style(settings.holiday_style.unwrap_or(Style::Red))
+            .holidays(holidays.iter().cloned())
+            .holiday_names(named.iter().map(|holiday| (holiday.dates.start,
+                                                       holiday.description.clone())))
+            .annotated(args.get_bool("--annotated"))
+            .highlight_style(Style::Reverse)
+            .heat(heat.clone(), settings.heat_scale.clone().unwrap_or_default())
+            .today(today(&settings))
+            .events(&events)
+            .format(settings.format.unwrap_or(Format::Text))
+            .page_size(settings.page_size.unwrap_or(PageSize::A4))
+            .jobs(jobs);
+        let calendar = highlights.iter()
+                                 .fold(calendar, |calendar, &date| calendar.highlight(date));
+        let calendar = marks.iter()
+                            .fold(calendar, |calendar, (&date, &style)| calendar.mark(date, style));
+        let calendar = breaks.iter()
+                             .fold(calendar, |calendar, (date, label)| {
+                                 calendar.break_at(*date, label)
+                             });
+
+        // The agenda and lists show the events on their days rather than as
+        // a legend.
+        let on_days = list || settings.format == Some(Format::Agenda);
+
+        if footnotes && !on_days {
+            let mut events = named.iter().cloned().chain(events).collect::<Vec<_>>();
+            events.sort_by_key(|event| event.dates.start);
+            calendar.footnotes(true).legend(legend_events(events, &settings))
+        } else if settings.legend.unwrap_or(false) || on_days {
+            calendar.legend(legend_events(events, &settings))
+        } else {
+            calendar
+        }
+    };
+
+    // With the warnings of the events as last loaded.
+    let render = || {
+        let calendar = build();
+        let mut diagnostics = store.diagnostics();
+        diagnostics.append(calendar.diagnostics());
+        (calendar.render() + &summary, diagnostics)
+    };
+
+    build().check()?;
+
+    if let Some(key) = cache_key {
+        let (output, diagnostics) = render();
+        write_cached(key, &checksum, &output, &diagnostics)?;
+        print!("{}", terminal::displayable(&output));
+        report(&diagnostics);
+        return Ok(Signal::Shutdown);
+    }
+
+    let signals = match signals {
+        Some(signals) => signals,
+        None          => {
+            return write_calendar(args, &build(), &summary, &store).map(|_| Signal::Shutdown)
+        }
+    };
+
+    let files = sources.events.into_iter().chain(sources.ical).collect::<Vec<_>>();
+    if files.is_empty() {
+        return Err("--watch needs an events file (--events or --ical)".to_string());
+    }
+
+    // Clear the screen before every redraw.
+    watch::watch(&store, &files, signals, || {
+        let (output, diagnostics) = render();
+        let _ = execute!(io::stdout(), Clear(ClearType::All), cursor::MoveTo(0, 0));
+        print!("{}", terminal::displayable(&output));
+        report(&diagnostics);
+    })
+}
+
+/// Writes the calendar followed by the summary as the terminal shows it,
+/// through the pager if shown in one, and reports the warnings.
+fn write_calendar(args: &Args, calendar: &Calendar, summary: &str, store: &EventStore)
+    -> Result<(), String>
+{
+    let written = if io::stdout().is_terminal() && !args.get_bool("--no-pager") {
+        let mut pager = Pager::new();
+        calendar.render_to(&mut Displayable::new(&mut pager))
+                .and_then(|_| Displayable::new(&mut pager).write_all(summary.as_bytes()))
+                .and_then(|_| pager.finish())
+    } else {
+        let stdout  = io::stdout();
+        let mut out = Displayable::new(BufWriter::new(stdout.lock()));
+        calendar.render_to(&mut out)
+                .and_then(|_| out.write_all(summary.as_bytes()))
+                .and_then(|_| out.flush())
+    };
+
+    // A reader that went away, like head, has seen all it wanted.
+    match written {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+        Ok(()) => {}
+    }
+
+    let mut diagnostics = store.diagnostics();
+    diagnostics.append(calendar.diagnostics());
+    report(&diagnostics);
+    Ok(())
+}
+
+/// Browses the calendar three months at a time around the selected day,
+/// which is highlighted, with today underlined. SIGHUP carries on at the
+/// same day with the configuration and events read again.
+fn run_interactive(args: &Args, range: DateRange) -> Result<(), String> {
+    let signals   = Signals::listen()?;
+    let today     = today(&settings(args)?);
+    let selected  = if range.contains(today) { today } else { range.start };
+    let mut state = tui::State::new(selected, today);
+
+    while browse(args, &mut state, &signals)? == Signal::Reload {}
+    Ok(())
+}
+
+/// Browses the calendar from the state until quit or a signal, which is
+/// returned.
+fn browse(args: &Args, state: &mut tui::State, signals: &Signals) -> Result<Signal, String> {
+    let settings = settings(args)?;
+    let today    = state.today;
+    let provider = settings.holidays.as_ref().map(|code| holiday_provider(code)).transpose()?;
+    let store    = load_store(args, &settings)?;
+
+    let holidays = |range: DateRange| {
+        provider.iter()
+                .flat_map(|provider| (range.start.year()..=range.end.pred().year())
+                                         .flat_map(move |year| provider.holidays(year)))
+                .filter(move |holiday| range.contains(holiday.date))
+                .collect::<Vec<_>>()
+    };
+
+    // Only the month of the day in the first and last supported months.
+    let render = |selected| {
+        let range = months_around(selected, 1, 1).unwrap_or_else(|_| months_from(selected, 1));
+
+        Calendar::range(range)
+            .months_per_line(3)
+            .banner(false)
+            .week_start(week_start(&settings))
+            .week_numbering(settings.week_numbering)
+            .workweek(settings.workweek.unwrap_or(false))
+            .julian(settings.julian.unwrap_or(false))
+            .overlay(settings.overlay)
+            .moon(settings.moon.unwrap_or(false))
+            .layout(layout(&settings))
+            .width(settings.width.clone().unwrap_or_default())
+            .weekend_style(settings.weekends.unwrap_or(Style::Plain))
+            .holiday_style(settings.holiday_style.unwrap_or(Style::Red))
+            .holidays(holidays(range).into_iter().map(|holiday| holiday.date))
+            .highlight_style(Style::Reverse)
+            .highlight(selected)
+            .mark(today, Style::Underline)
+            .events(&store.query(range))
+            .render()
+    };
+
+    let events = |date: Date| {
+        let day = DateRange::new(date, date.succ());
+
+        holidays(day).into_iter()
+                     .map(|holiday| holiday.name)
+                     .chain(store.query(day).into_iter().map(|event| event.description))
+                     .collect()
+    };
+
+    tui::run(state, signals, render, events)
+}
+
+fn print_day(args: &Args) -> Result<(), String> {
+    let date     = parse_date_arg(args)?;
+    let settings = settings(args)?;
+    let today    = today(&settings);
+
+    let holidays = match settings.holidays {
+        Some(ref code) => {
+            let provider = holiday_provider(code)?;
+            (date.year().min(today.year())..=date.year().max(today.year()))
+                .flat_map(|year| provider.holidays(year))
+                .collect::<Vec<_>>()
+        },
+        None => Vec::new()
+    };
+
+    let store  = load_store(args, &settings)?;
+    let events = store.query(DateRange::new(date, date.succ()));
+
+    let numbering    = settings.week_numbering.unwrap_or(WeekNumbering::Iso);
+    let (year, week) = week_number(&date, numbering);
+    let between       = DateRange::new(today.min(date), today.max(date).succ());
+    let holiday_dates = holidays.iter()
+                                .map(|holiday| holiday.date)
+                                .filter(|&day| between.contains(day))
+                                .collect::<HashSet<_>>();
+    let business_days = business_days_between(today, date, |day| holiday_dates.contains(&day));
+
+    println!("{}  {}", date.format("%Y-%m-%d"), date.format("%A"));
+    match numbering {
+        WeekNumbering::Iso => println!("ISO week:       {}-W{:02}", year, week),
+        _                  => println!("Week:           {}-W{:02}", year, week)
+    }
+    println!("Day of year:    {}", date.ordinal());
+    println!("Julian day:     {}", julian_day(date));
+    println!("Moon:           {}", moon_phase(date));
+
+    for holiday in holidays.iter().filter(|holiday| holiday.date == date) {
+        println!("Holiday:        {}", holiday.name);
+    }
+
+    for event in events {
+        println!("Event:          {}", event.description);
+    }
+
+    for (name, _) in user_bookmarks().iter().filter(|&(_, day)| day == date) {
+        println!("Bookmark:       {}", name);
+    }
+
+    println!("Business days:  {} from today", business_days);
+    Ok(())
+}
+
+/// The principal phase of the moon on the day, e.g. `○ full moon`, or
+/// else the next one, e.g. `full moon on 2015-03-05`. Principal phases are
+/// less than eight days apart.
+fn moon_phase(date: Date) -> String {
+    if let Some(phase) = moon::phase(date) {
+        return format!("{} {}", phase.symbol(), phase.name());
+    }
+
+    (1..8).map(|days| date + chrono::Duration::days(days))
+          .find_map(|day| moon::phase(day).map(|phase| (day, phase)))
+          .map_or_else(String::new, |(day, phase)| {
+              format!("{} on {}", phase.name(), day.format("%Y-%m-%d"))
+          })
+}
+
+/// Prints the week of the date as an agenda, with the date highlighted and
+/// the names of holidays listed like events.
+fn print_week(args: &Args) -> Result<(), String> {
+    let settings = settings(args)?;
+    let date     = match get_opt(args, "<date>") {
+        Some(_) => parse_date_arg(args)?,
+        None    => today(&settings)
+    };
+
+    let workweek = settings.workweek.unwrap_or(false);
+    let first    = if workweek { Weekday::Mon } else { week_start(&settings) };
+    // The week of the last supported day ends with it.
+    let around   = DateRange::new(date - chrono::Duration::days(6),
+                                  date + chrono::Duration::days(7))
+                       .intersect(date::supported_dates())
+                       .ok_or_else(|| format!("{} is out of range", date.format("%Y-%m-%d")))?;
+
+    // Numbered weeks are split at New Year, except ISO weeks.
+    let week = match settings.week_numbering {
+        Some(numbering) if numbering.first_day() == first => {
+            around.by_numbered_week(numbering).map(|(_, _, week)| week).find(|week| {
+                week.contains(date)
+            })
+        },
+        _ => around.by_week_starting(first).find(|week| week.contains(date))
+    }.expect("the days around the date contain its week");
+
+    let holidays = match settings.holidays {
+        Some(ref code) => {
+            let provider = holiday_provider(code)?;
+            (week.start.year()..=week.end.pred().year())
+                .flat_map(|year| provider.holidays(year))
+                .filter(|holiday| week.contains(holiday.date))
+                .collect()
+        },
+        None => Vec::new()
+    };
+
+    let store      = load_store(args, &settings)?;
+    let mut events = holidays.iter()
+                             .map(|holiday| Event::new(DateRange::new(holiday.date,
+                                                                      holiday.date.succ()),
+                                                       &holiday.name))
+                             .chain(store.query(week))
+                             .collect::<Vec<_>>();
+    events.sort_by_key(|event| event.dates.start);
+
+    let calendar = Calendar::range(week)
+        .format(Format::Agenda)
+        .week_start(first)
+        .week_numbering(settings.week_numbering)
+        .workweek(workweek)
+        .daylight(settings.location, settings.timezone.unwrap_or(Zone::Local))
+        .weekend_style(settings.weekends.unwrap_or(Style::Plain))
+        .holiday_style(settings.holiday_style.unwrap_or(Style::Red))
+        .holidays(holidays.iter().map(|holiday| holiday.date))
+        .highlight_style(Style::Reverse)
+        .highlight(date)
+        .legend(legend_events(events, &settings));
+
+    print!("{}", terminal::displayable(&calendar.render()));
+    Ok(())
+}
+
+fn print_holidays(args: &Args) -> Result<(), String> {
+    let years    = parse_year_range(args.get_str("<years>"))?;
+    let settings = settings(args)?;
+    let country  = get_opt(args, "--country").map(String::from)
+                                            .or(settings.holidays)
+                                            .ok_or("no country given (use --country)")?;
+
+    let provider = holiday_provider(&country)?;
+    let holidays = years.flat_map(|year| provider.holidays(year)).collect::<Vec<_>>();
+
+    match get_opt(args, "--format").unwrap_or("text") {
+        "text" => for holiday in holidays {
+            println!("{}  {}", holiday.date.format("%Y-%m-%d"), holiday.name);
+        },
+        "json" => println!("{}", serde_json::to_string_pretty(&holidays)
+                                         .map_err(|e| e.to_string())?),
+        "csv"  => print!("{}", holidays::to_csv(&holidays)),
+        "ics"  => {
+            let events = holidays.iter()
+                                 .map(|holiday| {
+                                     Event::new(DateRange::new(holiday.date, holiday.date.succ()),
+                                                &holiday.name)
+                                 })
+                                 .collect::<Vec<_>>();
+            print!("{}", ical::write(&events));
+        },
+        format => return Err(format!("unknown format for holidays: '{}' \
+                                      (available: text, json, csv, ics)", format))
+    }
+
+    Ok(())
+}
+
+fn print_query(args: &Args) -> Result<(), String> {
+    let settings = settings(args)?;
+    let context  = query::Context { today:     today(&settings),
+                                    country:   settings.holidays.clone(),
+                                    numbering: settings.week_numbering
+                                                       .unwrap_or(WeekNumbering::Iso) };
+
+    println!("{}", query::evaluate(args.get_str("<expr>"), &context)?);
+    Ok(())
+}
+
+fn print_until(args: &Args) -> Result<(), String> {
+    let date  = parse_date_arg(args)?;
+    let today = today(&settings(args)?);
+    let span  = format_span(today, date, get_opt(args, "--in"))?;
+
+    match days_between(today, date) {
+        0                => println!("today"),
+        days if days > 0 => println!("{}", span),
+        _                => println!("{} ago", span)
+    }
+
+    Ok(())
+}
+
+fn print_diff(args: &Args) -> Result<(), String> {
+    let input = DateInput::new(args)?;
+    let from  = parse_date(&input.expand(args.get_str("<date>"))?)?;
+    let to    = parse_date(&input.expand(args.get_str("<end>"))?)?;
+
+    println!("{}", format_span(from.min(to), from.max(to), get_opt(args, "--in"))?);
+    Ok(())
+}
+
+fn print_workdays(args: &Args) -> Result<(), String> {
+    let input    = DateInput::new(args)?;
+    let from     = parse_date(&input.expand(args.get_str("<date>"))?)?;
+    let to       = parse_date(&input.expand(args.get_str("<end>"))?)?;
+    let range    = DateRange::new(from.min(to), from.max(to).succ());
+    let holidays = holiday_dates(&settings(args)?, range)?;
+
+    println!("{}", range.filter(|&date| is_business_day(date, |day| holidays.contains(&day)))
+                        .count());
+    Ok(())
+}
+
+fn print_add_workdays(args: &Args) -> Result<(), String> {
+    let date  = parse_date_arg(args)?;
+    let count = parse_days(args.get_str("<days>"))?;
+
+    // A year has well over 200 business days, so the result is within the
+    // years this covers in the direction of travel, or outside the supported
+    // years, which add_business_days reports.
+    let years = (count.unsigned_abs() / 200 + 1).min((date::MAX_YEAR - date::MIN_YEAR) as u64);
+    let range = if count < 0 {
+        let first = (i64::from(date.year()) - years as i64).max(i64::from(date::MIN_YEAR));
+        DateRange::new(date::dates(first as i32)?.start, date.succ())
+    } else {
+        let last = (i64::from(date.year()) + years as i64).min(i64::from(date::MAX_YEAR));
+        DateRange::new(date, date::dates(last as i32)?.end)
+    };
+    let holidays = holiday_dates(&settings(args)?, range)?;
+
+    let result = add_business_days(date, count, |day| holidays.contains(&day))?;
+    println!("{}  {}", result.format("%Y-%m-%d"), result.format("%A"));
+    Ok(())
+}
+
+/// Formats the time from `from` to `to` (or back, without a sign) in the
+/// given unit, with the days left over, e.g. `2 months, 14 days`.
+fn format_span(from: Date, to: Date, unit: Option<&str>) -> Result<String, String> {
+    let count = |count: i64, unit: &str| {
+        format!("{} {}{}", count.abs(), unit, if count.abs() == 1 { "" } else { "s" })
+    };
+    let days = days_between(from, to);
+
+    match unit.unwrap_or("days") {
+        "days"   => Ok(count(days, "day")),
+        "weeks"  => Ok(match (days / 7, days % 7) {
+            (0, days)     => count(days, "day"),
+            (weeks, 0)    => count(weeks, "week"),
+            (weeks, days) => format!("{}, {}", count(weeks, "week"), count(days, "day"))
+        }),
+        "months" => Ok(match months_and_days_between(from, to) {
+            (0, days)      => count(days, "day"),
+            (months, 0)    => count(i64::from(months), "month"),
+            (months, days) => format!("{}, {}", count(i64::from(months), "month"),
+                                                count(days, "day"))
+        }),
+        unit => Err(format!("unknown unit: '{}' (available: days, weeks, months)", unit))
+    }
+}
+
+fn print_weekday_of(args: &Args) -> Result<(), String> {
+    let (month, day) = parse_month_day(args.get_str("<month-day>"))?;
+    let years        = parse_year_range(args.get_str("<years>"))?;
+
+    for (year, date) in anniversaries(month, day, years) {
+        match date {
+            Some(date) => println!("{}  {}", date.format("%Y-%m-%d"),
+                                             date.format("%A")),
+            None       => println!("{}-{:02}-{:02}  -", year, month, day)
+        }
+    }
+
+    Ok(())
+}
+
+fn print_skeleton(args: &Args) -> Result<(), String> {
+    let month  = parse_date_range(&format!("{}-{:02}", args.get_str("<year>"),
+                                                       parse_month(args.get_str("<month>"))?))?;
+    let markup = get_opt(args, "--format").map(str::parse)
+                                          .transpose()?
+                                          .unwrap_or(Markup::Markdown);
+
+    let settings = settings(args)?;
+
+    print!("{}", skeleton(month, markup, settings.week_numbering.unwrap_or(WeekNumbering::Iso)));
+    Ok(())
+}
+
+fn print_random(args: &Args) -> Result<(), String> {
+    let input     = DateInput::new(args)?;
+    let settings  = settings(args)?;
+    let range     = DateRange::new(parse_date_range(&input.expand(args.get_str("--from"))?)?.start,
+                                   parse_date_range(&input.expand(args.get_str("--to"))?)?.end);
+
+    let count = get_opt(args, "--count").map(parse_count).transpose()?.unwrap_or(1);
+    let seed  = match get_opt(args, "--seed") {
+        Some(seed) => parse_seed(seed)?,
+        None       => SystemTime::now().duration_since(UNIX_EPOCH)
+                                       .map(|time| time.as_nanos() as u64)
+                                       .unwrap_or(0)
+    };
+
+    let holidays = if args.get_bool("--business-days") {
+        holiday_dates(&settings, range)?
+    } else {
+        HashSet::new()
+    };
+
+    let business_days = args.get_bool("--business-days");
+    let dates = random::sample(range, count, seed, |date| {
+        !business_days || is_business_day(date, |day| holidays.contains(&day))
+    })?;
+
+    for date in dates {
+        println!("{}", date.format("%Y-%m-%d"));
+    }
+
+    Ok(())
+}
+
+fn print_occurrences(args: &Args) -> Result<(), String> {
+    let rule  = args.get_str("<rule>").trim();
+    let start = match get_opt(args, "--from") {
+        Some(from) => parse_date_range(&DateInput::new(args)?.expand(from)?)?.start,
+        None       => today(&settings(args)?)
+    };
+    let count = get_opt(args, "--count").map(parse_count).transpose()?.unwrap_or(10);
+
+    if rule.starts_with("FREQ=") || rule.starts_with("RRULE:") {
+        for date in rule.parse::<Rule>()?.first(start, count) {
+            println!("{}  {}", date.format("%Y-%m-%d"), date.format("%A"));
+        }
+    } else {
+        for time in rule.parse::<Schedule>()?.first(start, count) {
+            println!("{} {:02}:{:02}  {}", time.date.format("%Y-%m-%d"), time.hour, time.minute,
+                                           time.date.format("%A"));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_load(args: &Args) -> Result<(), String> {
+    let settings = settings(args)?;
+    let range    = resolve::resolve(&range_args(args, &settings)?, today(&settings))?;
+    let period   = match get_opt(args, "--by").unwrap_or("week").parse()? {
+        load::Period::Week(_) => {
+            load::Period::Week(settings.week_numbering.unwrap_or(WeekNumbering::Iso))
+        },
+        period => period
+    };
+    let holidays = holiday_dates(&settings, range)?;
+    let store    = load_store(args, &settings)?;
+
+    let loads = load::load(&store.query(range), range, period, |date| holidays.contains(&date));
+    print!("{}", load::chart(&loads, period, LOAD_BAR_WIDTH));
+    Ok(())
+}
+
+fn print_periods(args: &Args) -> Result<(), String> {
+    let year     = date::dates(parse_year(args.get_str("<year>"))?)?;
+    let settings = settings(args)?;
+    // Pay days can move back into the year from the next one.
+    let holidays = holiday_dates(&settings, DateRange::new(year.start, year.end.succ()))?;
+
+    let pattern = match args.get_str("--pattern") {
+        "semimonthly" => payroll::Pattern::Semimonthly,
+        "biweekly"    => {
+            let anchor = get_opt(args, "--anchor").ok_or("biweekly pay periods need --anchor")?;
+            payroll::Pattern::Biweekly(parse_date(&DateInput::new(args)?.expand(anchor)?)?)
+        },
+        pattern => return Err(format!("unknown pattern: '{}' (available: semimonthly, \
+                                       biweekly)", pattern))
+    };
+
+    let periods = payroll::periods(year, pattern, |date| holidays.contains(&date));
+
+    if args.get_bool("--grid") {
+        print_range(args, year, periods.iter().map(|period| period.pay_day).collect())
+    } else {
+        print!("{}", payroll::list(&periods));
+        Ok(())
+    }
+}
+
+fn manage_bookmarks(args: &Args) -> Result<(), String> {
+    let mut bookmarks = Bookmarks::user()?;
+    let name = args.get_str("<name>");
+
+    match args.subcommand() {
+        Some("add") => {
+            let date = parse_date_arg(args)?;
+            bookmarks.add(name, date)?;
+            bookmarks.save()
+        },
+        Some("remove") => {
+            bookmarks.remove(name)?;
+            bookmarks.save()
+        },
+        _ => {
+            for (name, date) in bookmarks.iter() {
+                println!("{}  {}", date.format("%Y-%m-%d"), name);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn edit_events(args: &Args) -> Result<(), String> {
+    let path = settings(args)?.events
+                              .ok_or("the event command needs an events file (--events)")?;
+    let mut file    = EventsFile::load(path)?;
+    let input       = DateInput::new(args)?;
+    let parse       = |date| input.expand(date).and_then(|date| parse_date(&date));
+    let date        = parse(args.get_str("<date>"))?;
+    let description = args.get_str("<description>");
+    let rule        = get_opt(args, "--rrule").map(str::parse).transpose()?;
+
+    let change = if args.subcommand() == Some("add") {
+        let event = Event { recurrence: rule, ..Event::new(DateRange::new(date, date.succ()),
+                                                           description) };
+        file.add(&event)
+    } else if args.subcommand() == Some("edit") {
+        let new_date = get_opt(args, "--set-date").map(parse).transpose()?;
+
+        file.edit(date, description, |event| {
+            if let Some(date) = new_date {
+                event.dates = DateRange::new(date, date.succ());
+            }
+            if let Some(description) = get_opt(args, "--set-description") {
+                event.description = description.to_string();
+            }
+            if rule.is_some() {
+                event.recurrence = rule;
+            }
+        })?
+    } else {
+        file.remove(date, description)?
+    };
+
+    print!("--- {0}\n+++ {0}\n{1}", file.path().display(), change.diff());
+
+    if args.get_bool("--dry-run") {
+        println!("Dry run: {} is unchanged", file.path().display());
+    } else if let Some(backup) = file.save()? {
+        println!("Saved {}; the previous version is {}", file.path().display(),
+                                                          backup.display());
+    } else {
+        println!("Created {}", file.path().display());
+    }
+
+    Ok(())
+}
+
+/// Dates given by `--highlight`: a comma-separated list, or with `-` the
+/// dates on the lines of the standard input. Only the first word of a line
+/// is taken, so that dates with times of day can be piped in as they are.
+fn highlight_args(args: &Args) -> Result<Vec<Date>, String> {
+    let dates = match get_opt(args, "--highlight") {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)
+                       .map_err(|e| format!("can't read dates from standard input: {}", e))?;
+
+            input.lines()
+                 .filter_map(|line| line.split_whitespace().next())
+                 .map(String::from)
+                 .collect()
+        },
+        Some(dates) => dates.split(',').map(|date| date.trim().to_string()).collect(),
+        None        => Vec::new()
+    };
+
+    let input = DateInput::new(args)?;
+
+    dates.iter()
+         .map(|date| parse_date(&input.expand(date)?))
+         .collect()
+}
+
+/// Dates of the range given by `--highlight-every`: every so many days from
+/// a date, as `<days>:<date>`, or every day of the week with the name.
+fn highlight_every(args: &Args, range: DateRange) -> Result<Vec<Date>, String> {
+    let rule = match get_opt(args, "--highlight-every") {
+        Some(rule) => rule,
+        None       => return Ok(Vec::new())
+    };
+
+    let (days, anchor) = match rule.split_once(':') {
+        Some((days, anchor)) => (days, anchor),
+        None                 => return Ok(range.weekdays(parse_weekday(rule)?).collect())
+    };
+    let days = match days.trim().parse::<u32>() {
+        Ok(days) if days > 0 => days,
+        _ => return Err(format!("invalid number of days in --highlight-every: '{}'", days))
+    };
+    let anchor = parse_date(&DateInput::new(args)?.expand(anchor.trim())?)?;
+
+    // The first date of the range in step with the anchor, but not before it.
+    let behind = days_between(anchor, range.start);
+    let first  = if behind <= 0 {
+        anchor
+    } else {
+        range.start + chrono::Duration::days((-behind).rem_euclid(i64::from(days)))
+    };
+
+    Ok(range.split_at(first).1.every(days).collect())
+}
+
+/// Parses the `<date>` argument.
+fn parse_date_arg(args: &Args) -> Result<Date, String> {
+    parse_date(&DateInput::new(args)?.expand(args.get_str("<date>"))?)
+}
+
+/// The user's bookmarks, or none if they can't be read, which is warned
+/// about rather than failing commands that don't need them.
+fn user_bookmarks() -> Bookmarks {
+    Bookmarks::user().unwrap_or_else(|e| {
+        warn(format!("ignoring bookmarks: {}", e));
+        Bookmarks::default()
+    })
+}
+
+/// Reader of the dates given on the command line, which can be the names of
+/// bookmarks or be written the local way, e.g. `03/04/2024`. The bookmarks
+/// are read once a date could name one.
+struct DateInput {
+    bookmarks: OnceCell<Bookmarks>,
+    /// Order of dates like `03/04/2024`, and whether it was guessed from the
+    /// locale rather than set, with the warnings of guessing it.
+    order:     DateOrder,
+    guessed:   Option<Diagnostics>
+}
+
+impl DateInput {
+    fn new(args: &Args) -> Result<DateInput, String> {
+        let mut diagnostics = Diagnostics::new();
+        let order           = settings(args)?.date_order;
+
+        Ok(DateInput {
+            bookmarks: OnceCell::new(),
+            order:     order.unwrap_or_else(|| DateOrder::from_env(&mut diagnostics)),
+            guessed:   if order.is_none() { Some(diagnostics) } else { None }
+        })
+    }
+
+    /// Rewrites a date or range of dates into the form the parse module
+    /// reads. Dates whose day and month could be swapped are warned about,
+    /// along with a locale the order couldn't be guessed from, unless the
+    /// order was set explicitly.
+    fn expand(&self, input: &str) -> Result<String, String> {
+        let expanded = if Bookmarks::named_in(input) {
+            self.bookmarks.get_or_init(user_bookmarks).expand(input)
+        } else {
+            input.to_string()
+        };
+        let normalized = normalize_dates(&expanded, self.order)?;
+
+        if let Some(ref guessed) = self.guessed {
+            if !normalized.diagnostics.is_empty() {
+                report(guessed);
+            }
+            for warning in normalized.diagnostics.warnings() {
+                eprintln!("calendar: warning: {}; use --date-order to change it", warning);
+            }
+        }
+
+        Ok(normalized.input)
+    }
+}
+
+/// Prints the warnings to stderr.
+fn report(diagnostics: &Diagnostics) {
+    diagnostics.warnings().iter().for_each(warn);
+}
+
+fn warn<W: fmt::Display>(warning: W) {
+    eprintln!("calendar: warning: {}", warning);
+}
+
+/// People whose days off are shown: the one selected with `--person` and
+/// those given with `--overlay`, with the styles of their days off.
+fn people(args: &Args) -> Result<Vec<(String, Person, Style)>, String> {
+    let config = config(args)?;
+    let names  = get_opt(args, "--person").into_iter()
+                                          .chain(get_opt(args, "--overlay").into_iter()
+                                                                           .flat_map(|names| {
+                                                                               names.split(',')
+                                                                           }))
+                                          .map(str::trim)
+                                          .filter(|name| !name.is_empty());
+
+    let mut result: Vec<(String, Person, Style)> = Vec::new();
+
+    for name in names {
+        if result.iter().any(|(other, _, _)| other == name) {
+            continue;
+        }
+
+        let person = config.person(name)?;
+        let style  = person.color.unwrap_or(PERSON_STYLES[result.len() % PERSON_STYLES.len()]);
+        result.push((name.to_string(), person, style));
+    }
+
+    Ok(result)
+}
+
+/// Days in the range the person is off, according to their time-off file.
+/// Reminders in it that can't be read are left out with a warning.
+fn days_off(person: &Person, range: DateRange, diagnostics: &mut Diagnostics)
+    -> Result<HashSet<Date>, String>
+{
+    let events = match person.time_off {
+        Some(ref path) => calendar::events::load_lenient(path, diagnostics)?,
+        None           => return Ok(HashSet::new())
+    };
+
+    Ok(events.iter()
+             .flat_map(|event| event.occurrences(range))
+             .filter_map(|dates| dates.intersect(range))
+             .flatten()
+             .collect())
+}
+
+/// Lists the people in their colors, each followed by how many of their
+/// vacation days they have taken in every year of the range. Days off on
+/// weekends and holidays don't count.
+fn people_summary(people: &[(String, Person, Style)], settings: &Settings, range: DateRange)
+    -> Result<String, String>
+{
+    let name_width = people.iter().map(|(name, _, _)| name.chars().count()).max();
+    let mut result = String::new();
+
+    for &(ref name, ref person, style) in people {
+        let padding = " ".repeat(name_width.unwrap_or(0) - name.chars().count());
+        let budget  = match person.vacation_days {
+            Some(budget) if person.time_off.is_some() => budget,
+            _ => {
+                result.push_str(&format!("\n{}", style.paint(name)));
+                continue;
+            }
+        };
+
+        let settings = person.settings.clone().or(settings.clone());
+
+        for year in range.start.year()..=range.end.pred().year() {
+            let year     = date::dates(year)?;
+            let holidays = holiday_dates(&settings, year)?;
+            // Warnings about the file were reported with the days marked.
+            let taken    = days_off(person, year, &mut Diagnostics::new())?
+                .into_iter()
+                .filter(|&date| weekday(date) < 5 && !holidays.contains(&date))
+                .count() as u32;
+            let left     = match budget.checked_sub(taken) {
+                Some(left) => format!("{} left", left),
+                None       => format!("{} over budget", taken - budget)
+            };
+
+            result.push_str(&format!("\n{}{}  {} of {} vacation days taken in {}, {}",
+                                     style.paint(name), padding, taken, budget,
+                                     year.start.year(), left));
+        }
+    }
+
+    if !result.is_empty() {
+        result.push('\n');
+    }
+
+    Ok(result)
+}
+
+/// Reads the configuration file selected by `--config` or `--no-config`.
+fn config(args: &Args) -> Result<Config, String> {
+    let source = match (args.get_bool("--no-config"), get_opt(args, "--config")) {
+        (true, _)           => config::Source::Disabled,
+        (false, Some(path)) => config::Source::File(PathBuf::from(path)),
+        (false, None)       => config::Source::Default
+    };
+
+    source.load()
+}
+
+/// Merges the options given on the command line with the profile of the
+/// selected person, the selected preset and the defaults of the
+/// configuration file, in this order of precedence.
+fn settings(args: &Args) -> Result<Settings, String> {
+    // The holidays and skeleton commands have formats of their own.
+    let format = match args.command() {
+        Some("holidays") | Some("skeleton") => None,
+        _ => get_opt(args, "--format").map(str::parse).transpose()?
+    };
+    let format = if compat_cal(args) { Some(Format::Cal) } else { format };
+
+    // Like the cal of BSD and util-linux, which follow the British reform.
+    let reform = match (args.get_bool("--julian-calendar"), get_opt(args, "--reform")) {
+        (true, _)       => Some(Reckoning::Julian),
+        (false, None) if format == Some(Format::Cal) => Some(Reckoning::reform_in(1752)?),
+        (false, reform) => reform.map(str::parse).transpose()?
+    };
+
+    let timezone = if args.get_bool("--utc") {
+        Some(Zone::Utc)
+    } else {
+        get_opt(args, "--timezone").map(str::parse).transpose()?
+    };
+
+    let settings = Settings {
+        months_per_line: get_opt(args, "--months-per-line").map(parse_months_per_line)
+                                                            .transpose()?,
+        banner:        if args.get_bool("--no-banner") { Some(false) } else { None },
+        week_start:    get_opt(args, "--week-start").map(parse_weekday).transpose()?,
+        week_numbering: get_opt(args, "--week-numbering").map(str::parse).transpose()?,
+        year_start:    get_opt(args, "--year-start").map(parse_month).transpose()?,
+        workweek:      if args.get_bool("--workweek") { Some(true) } else { None },
+        flip:          if args.get_bool("--flip") { Some(true) } else { None },
+        vertical:      if args.get_bool("--vertical") { Some(true) } else { None },
+        compact:       if args.get_bool("--compact") { Some(true) } else { None },
+        julian:        if args.get_bool("--julian") { Some(true) } else { None },
+        reform,
+        overlay:       get_opt(args, "--calendar-overlay").map(str::parse).transpose()?,
+        date_order:    get_opt(args, "--date-order").map(str::parse).transpose()?,
+        moon:          if args.get_bool("--moon") { Some(true) } else { None },
+        location:      get_opt(args, "--location").map(str::parse).transpose()?,
+        title_format:  get_opt(args, "--title-format").map(str::parse).transpose()?,
+        weekday_header: if args.get_bool("--weekday-header") { Some(true) } else { None },
+        day_width:     get_opt(args, "--day-width").map(parse_day_width).transpose()?,
+        month_gap:     get_opt(args, "--month-gap").map(parse_month_gap).transpose()?,
+        weekends:      get_opt(args, "--weekends").map(str::parse).transpose()?,
+        holidays:      get_opt(args, "--holidays").map(String::from),
+        holiday_style: get_opt(args, "--holiday-style").map(str::parse).transpose()?,
+        heatmap:       if args.get_bool("--heatmap") { Some(true) } else { None },
+        heat_scale:    get_opt(args, "--heat-scale").map(str::parse).transpose()?,
+        events:        get_opt(args, "--events").map(PathBuf::from),
+        ical:          get_opt(args, "--ical").map(PathBuf::from),
+        ical_url:      get_opt(args, "--ical-url").map(String::from),
+        legend:        if args.get_bool("--legend") { Some(true) } else { None },
+        ages:          if args.get_bool("--ages") { Some(true) } else { None },
+        footnotes:     if args.get_bool("--footnotes") { Some(true) } else { None },
+        format,
+        page_size:     get_opt(args, "--page-size").map(str::parse).transpose()?,
+        width:         None,
+        timezone,
+        today:         get_opt(args, "--today").map(parse_date).transpose()?
+    };
+
+    let config = config(args)?;
+
+    let settings = match get_opt(args, "--person") {
+        Some(name) => settings.or(config.person(name)?.settings),
+        None       => settings
+    };
+
+    let settings = match get_opt(args, "--preset") {
+        Some(name) => settings.or(config.preset(name)?),
+        None       => settings
+    };
+
+    Ok(settings.or(config.defaults))
+}
+
+/// The range in the calendar of `--reform`: with `--reform=1752`, the
+/// months of 1700 are those of the Julian calendar.
+fn reckoned(range: DateRange, settings: &Settings) -> DateRange {
+    let reckoning = settings.reform.unwrap_or_default();
+    DateRange::new(reckoning.rename(range.start), reckoning.rename(range.end))
+}
+
+/// Whether `cal` compatibility was requested with `--compat=cal`.
+fn compat_cal(args: &Args) -> bool {
+    get_opt(args, "--compat") == Some("cal")
+}
+
+/// Dates of the holidays of the configured country in the years of the
+/// range. None without a country.
+fn holiday_dates(settings: &Settings, range: DateRange) -> Result<HashSet<Date>, String> {
+    match settings.holidays {
+        Some(ref code) => {
+            let provider = holiday_provider(code)?;
+            Ok((range.start.year()..=range.end.pred().year())
+                   .flat_map(|year| provider.holidays(year))
+                   .map(|holiday| holiday.date)
+                   .collect())
+        },
+        None => Ok(HashSet::new())
+    }
+}
+
+/// The holidays of the country of the settings in the range, as events
+/// with their names.
+fn holiday_events(settings: &Settings, range: DateRange) -> Result<Vec<Event>, String> {
+    match settings.holidays {
+        Some(ref code) => {
+            let provider = holiday_provider(code)?;
+            Ok((range.start.year()..=range.end.pred().year())
+                   .flat_map(|year| provider.holidays(year))
+                   .filter(|holiday| range.contains(holiday.date))
+                   .map(|holiday| Event::new(DateRange::new(holiday.date, holiday.date.succ()),
+                                             &holiday.name))
+                   .collect())
+        },
+        None => Ok(Vec::new())
+    }
+}
+
+/// Event sources of the settings, with the `--ical-url` feed fetched into
+/// the cache unless `--offline` is given.
+fn sources(args: &Args, settings: &Settings) -> Result<Sources, String> {
+    let feed = match settings.ical_url {
+        Some(ref url) => {
+            let cache = Cache::user().ok_or("can't determine the cache directory")?;
+            Some(remote::fetch(url, &cache, args.get_bool("--offline"))?)
+        },
+        None => None
+    };
+
+    Ok(Sources { events: settings.events.clone(),
+                 ical:   settings.ical.clone(),
+                 feed })
+}
+
+/// Loads the events of the sources, reporting events that were left out.
+fn load_store(args: &Args, settings: &Settings) -> Result<EventStore, String> {
+    let store = EventStore::load(sources(args, settings)?)?;
+    report(&store.diagnostics());
+    Ok(store)
+}
+
+fn holiday_provider(code: &str) -> Result<RuleSet, String> {
+    holidays::country(code).ok_or_else(|| {
+        format!("no holidays known for country '{}' (available: {})",
+                code, holidays::COUNTRIES.join(", "))
+    })
+}
+
+/// Parses a break given by `--break-at` as `DATE=LABEL`, or just a date
+/// labeled with itself.
+fn parse_break(input: &str, dates: &DateInput) -> Result<(Date, String), String> {
+    let mut parts = input.splitn(2, '=');
+    let date      = parse_date(&dates.expand(parts.next().unwrap_or("").trim())?)?;
+    let label     = parts.next()
+                         .map(|label| label.trim().to_string())
+                         .unwrap_or_else(|| date.format("%Y-%m-%d").to_string());
+
+    Ok((date, label))
+}
+
+/// Returns the value of an option that has no default, if it was given.
+fn get_opt<'a>(args: &'a Args, key: &str) -> Option<&'a str> {
+    match args.get_str(key) {
+        ""    => None,
+        value => Some(value)
+    }
+}
+
+/// Checksum of what the calendar depends on besides the range: the
+/// arguments, the settings, today's date, the width of the terminal and
+/// when the events, bookmarks and time-off files changed. Output cached
+/// under `--cache-key` is reused while the checksum is the same, so at most
+/// until midnight. Dates read from the standard input can't be checked, so
+/// they can't be cached.
+fn render_checksum(args: &Args, settings: &Settings) -> Result<String, String> {
+    if get_opt(args, "--highlight") == Some("-") {
+        return Err("--cache-key can't be used with --highlight=-, which reads the dates \
+                    from the standard input".to_string());
+    }
+
+    let mut hasher = DefaultHasher::new();
+
+    env::args().collect::<Vec<_>>().hash(&mut hasher);
+    format!("{:?}", settings).hash(&mut hasher);
+    today(settings).num_days_from_ce().hash(&mut hasher);
+    terminal::width().hash(&mut hasher);
+
+    let time_off = people(args)?.into_iter().filter_map(|(_, person, _)| person.time_off);
+    let files    = settings.events.iter()
+                                  .chain(&settings.ical)
+                                  .cloned()
+                                  .chain(Bookmarks::user_path())
+                                  .chain(time_off);
+    for file in files {
+        fs::metadata(file).and_then(|metadata| metadata.modified()).ok().hash(&mut hasher);
+    }
+
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// The cache of rendered calendars, checking the name of an entry given by
+/// `--cache-key`.
+fn render_cache(key: &str) -> Result<Cache, String> {
+    if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("invalid cache key '{}' (use only letters, digits, '-' and '_')",
+                           key));
+    }
+
+    Cache::user().ok_or_else(|| "can't determine the cache directory".to_string())
+}
+
+/// Output cached under the key and the warnings rendering it gave, if it
+/// was rendered with the same checksum.
+fn read_cached(key: &str, checksum: &str) -> Result<Option<(String, Vec<String>)>, String> {
+    let cache = render_cache(key)?;
+    let entry = cache.read(cache::Kind::Render, key).map_err(|e| {
+        format!("{}: {}", cache.dir(cache::Kind::Render).display(), e)
+    })?;
+
+    Ok(entry.and_then(|entry| {
+        let mut parts = entry.splitn(3, '\n');
+        let sum       = parts.next()?;
+        let count     = parts.next()?.parse::<usize>().ok()?;
+        let mut lines = parts.next()?.splitn(count + 1, '\n');
+        let warnings  = lines.by_ref().take(count).map(String::from).collect();
+        let output    = lines.next()?;
+
+        if sum == checksum { Some((output.to_string(), warnings)) } else { None }
+    }))
+}
+
+/// Caches the output under the key, preceded by its checksum and the
+/// warnings rendering it gave, a line each.
+fn write_cached(key: &str, checksum: &str, output: &str, diagnostics: &Diagnostics)
+    -> Result<(), String>
+{
+    let cache    = render_cache(key)?;
+    let warnings = diagnostics.warnings();
+    let entry    = once(checksum.to_string())
+                       .chain(once(warnings.len().to_string()))
+                       .chain(warnings.iter().map(|warning| warning.to_string().replace('\n', " ")))
+                       .chain(once(output.to_string()))
+                       .collect::<Vec<_>>()
+                       .join("\n");
+
+    cache.write(cache::Kind::Render, key, &entry).map_err(|e| {
+        format!("{}: {}", cache.dir(cache::Kind::Render).display(), e)
+    })
+}
+
+fn manage_cache(args: &Args) -> Result<(), String> {
+    let cache = Cache::user().ok_or("can't determine the cache directory")?;
+    let kinds = match get_opt(args, "<cache>") {
+        Some(name) => vec![cache::Kind::from_name(name)?],
+        None       => cache::Kind::ALL.to_vec()
+    };
+
+    for kind in kinds {
+        let error = |e| format!("{}: {}", cache.dir(kind).display(), e);
+
+        if args.subcommand() == Some("clear") {
+            let count = cache.clear(kind).map_err(error)?;
+            println!("{}: removed {}", kind.name(), count_entries(count));
+            continue;
+        }
+
+        let entries = cache.entries(kind).map_err(error)?;
+        let size    = entries.iter().map(|entry| entry.size).sum();
+
+        match entries.iter().map(|entry| entry.age()).max() {
+            Some(oldest) => println!("{}: {}, {}, oldest {} old",
+                                     kind.name(), count_entries(entries.len()),
+                                     format_size(size), format_age(oldest)),
+            None         => println!("{}: empty", kind.name())
+        }
+
+        for entry in entries {
+            println!("  {}  {}  {} old", entry.name, format_size(entry.size),
+                                         format_age(entry.age()));
+        }
+    }
+
+    Ok(())
+}
+
+fn count_entries(count: usize) -> String {
+    format!("{} {}", count, if count == 1 { "entry" } else { "entries" })
+}
+
+fn print_completions(args: &Args) -> Result<(), String> {
+    let shell = args.get_str("<shell>").parse::<Shell>()?;
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cli::command(), "calendar", &mut script);
+    io::stdout().write_all(&script).map_err(|e| e.to_string())
+}
+
+/// The events of a legend, with the ages of birthdays and the numbers of
+/// anniversaries if they're to be shown.
+fn legend_events(events: Vec<Event>, settings: &Settings) -> Vec<Event> {
+    if settings.ages.unwrap_or(false) {
+        events.into_iter().map(Event::counted).collect()
+    } else {
+        events
+    }
+}
+
+/// Today's date in the configured time zone, unless it's set with
+/// `--today`.
+fn today(settings: &Settings) -> Date {
+    settings.today.unwrap_or_else(|| date::today(settings.timezone.unwrap_or(Zone::Local)))
+}
+
+/// First day of the week: the configured one, or else the one of the week
+/// numbering.
+fn week_start(settings: &Settings) -> Weekday {
+    settings.week_start
+            .or_else(|| settings.week_numbering.map(WeekNumbering::first_day))
+            .unwrap_or(Weekday::Mon)
+}
+
+/// Geometry of text output: the compact one with `--compact`, adjusted by
+/// the other layout settings. Days of the year get a column more than days
+/// of the month, for their third digit.
+fn layout(settings: &Settings) -> LayoutConfig {
+    let base = if settings.compact.unwrap_or(false) {
+        LayoutConfig::compact()
+    } else {
+        LayoutConfig::default()
+    };
+    let extra = if settings.julian.unwrap_or(false) { 1 } else { 0 };
+
+    let mut result = base;
+    result.day_width = settings.day_width.unwrap_or(base.day_width + extra);
+    result.month_gap = settings.month_gap.unwrap_or(base.month_gap);
+    result
+}
+
+/// Number of months that fit side by side into the terminal.
+fn fitting_months_per_line(settings: &Settings) -> usize {
+    match terminal::width() {
+        Some(width) => (width / month_width(settings)).max(1),
+        None        => 3
+    }
+}
+
+/// Degrades text output in a terminal narrower than a month, unless the
+/// number of months per line is set: to the compact layout if a month fits
+/// in it, and otherwise to the linear format with a line per day.
+fn fit_terminal(mut settings: Settings) -> Settings {
+    let columns = match terminal::width() {
+        Some(width) => width,
+        None        => return settings
+    };
+
+    if settings.months_per_line.is_some() || settings.format.unwrap_or(Format::Text) != Format::Text
+                                          || month_width(&settings) <= columns {
+        return settings;
+    }
+
+    let mut compact = settings.clone();
+    compact.compact = Some(true);
+    if month_width(&compact) <= columns {
+        return compact;
+    }
+
+    settings.format = Some(Format::Linear);
+    settings
+}
+
+/// Width of a month of text output with the settings, in columns.
+fn month_width(settings: &Settings) -> usize {
+    let mut options = Options::default();
+    options.workweek = settings.workweek.unwrap_or(false);
+    options.vertical = settings.vertical.unwrap_or(false);
+    options.julian   = settings.julian.unwrap_or(false);
+    options.moon     = settings.moon.unwrap_or(false);
+    options.layout   = layout(settings);
+
+    format::month_width(&options)
 }
@@ -0,0 +1,135 @@
+//! Markdown output.
+//!
+//! Every row of `months_per_line` months is a GitHub-flavored Markdown table
+//! with a weekday header row and one row per week, the months separated by
+//! a blank column. Holidays are bold, highlighted days are bold and
+//! italic, and days with events are marked with an asterisk, as in the text
+//! output.
+
+use date::{ Date, DateRange };
+use events::Event;
+use format::{ column, day_number, days_per_week, format_dates, has_events, layout_grid,
+              month_name, month_rows, months, range_title, weekday_names, weeks, Options,
+              Renderer };
+use std::iter::once;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Markdown {
+    pub months_per_line: usize
+}
+
+impl Renderer for Markdown {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        let mut result = format!("# {}\n", range_title(range, options));
+
+        for row in month_rows(months(range, options), self.months_per_line) {
+            result.push('\n');
+            result.push_str(&render_months(&row, options));
+        }
+
+        if !legend.is_empty() {
+            result.push('\n');
+        }
+
+        for event in legend {
+            result.push_str(&format!("- {} {}\n", format_dates(event.dates),
+                                                  event.description));
+        }
+
+        result
+    }
+}
+
+/// Lays out the months side by side in a table, below a heading naming
+/// them.
+fn render_months(months: &[DateRange], options: &Options) -> String {
+    let days   = days_per_week(options) as usize;
+    let titles = months.iter().map(|month| month_name(month.start, options)).collect::<Vec<_>>();
+    let lines  = months.iter().map(|&month| {
+        let weeks = weeks(month, options).map(|week| {
+            let mut cells = vec![String::new(); column(week.start, options) as usize];
+            cells.extend(week.map(|date| render_day(date, options)));
+            cells.resize(days, String::new());
+            cells.join(" | ")
+        });
+        once(weekday_names(options).join(" | ")).chain(weeks)
+    });
+
+    let blank     = vec![""; days].join(" | ");
+    let alignment = vec!["---:|".repeat(days); months.len()].join("---|");
+
+    // The alignment row follows the weekday names.
+    let mut result = format!("## {}\n\n", titles.join(", "));
+    for (index, line) in layout_grid(lines, months.len(), blank).enumerate() {
+        result.push_str(&format!("| {} |\n", line.join(" |  | ")));
+
+        if index == 0 {
+            result.push_str(&format!("|{}\n", alignment));
+        }
+    }
+
+    result
+}
+
+fn render_day(date: Date, options: &Options) -> String {
+    let day = if options.highlights.contains(&date) {
+        format!("***{}***", day_number(date, options))
+    } else if options.holidays.contains(&date) {
+        format!("**{}**", day_number(date, options))
+    } else {
+        day_number(date, options).to_string()
+    };
+
+    if has_events(date, options) {
+        format!("\\*{}", day)
+    } else {
+        day
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn render_months_renders_table() {
+    use chrono::{ TimeZone, UTC };
+
+    let month   = DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1));
+    let options = Options {
+        holidays: vec![UTC.ymd(2015, 2, 2)].into_iter().collect(),
+        events:   vec![UTC.ymd(2015, 2, 3)].into_iter().collect(),
+        ..Options::default()
+    };
+
+    assert_eq!(render_months(&[month], &options), "\
+## February
+
+| Mo | Tu | We | Th | Fr | Sa | Su |
+|---:|---:|---:|---:|---:|---:|---:|
+|  |  |  |  |  |  | 1 |
+| **2** | \\*3 | 4 | 5 | 6 | 7 | 8 |
+| 9 | 10 | 11 | 12 | 13 | 14 | 15 |
+| 16 | 17 | 18 | 19 | 20 | 21 | 22 |
+| 23 | 24 | 25 | 26 | 27 | 28 |  |
+");
+}
+
+#[test]
+fn render_months_lays_out_months_side_by_side() {
+    use chrono::{ TimeZone, UTC };
+
+    let months  = [DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1)),
+                   DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1))];
+    let options = Options { workweek: true, ..Options::default() };
+
+    assert_eq!(render_months(&months, &options), "\
+## February, March
+
+| Mo | Tu | We | Th | Fr |  | Mo | Tu | We | Th | Fr |
+|---:|---:|---:|---:|---:|---|---:|---:|---:|---:|---:|
+| 2 | 3 | 4 | 5 | 6 |  | 2 | 3 | 4 | 5 | 6 |
+| 9 | 10 | 11 | 12 | 13 |  | 9 | 10 | 11 | 12 | 13 |
+| 16 | 17 | 18 | 19 | 20 |  | 16 | 17 | 18 | 19 | 20 |
+| 23 | 24 | 25 | 26 | 27 |  | 23 | 24 | 25 | 26 | 27 |
+|  |  |  |  |  |  | 30 | 31 |  |  |  |
+");
+}
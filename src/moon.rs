@@ -0,0 +1,77 @@
+//! Phases of the moon.
+//!
+//! The phases are computed from the mean length of the synodic month,
+//! counting from a known new moon. The true phases deviate from the mean
+//! ones by up to about 14 hours, so a phase close to midnight may be shown a
+//! day early or late.
+
+use date::{ julian_day, Date };
+
+/// Mean time from one new moon to the next, in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+/// Julian date of the new moon of January 6, 2000, 18:14 UTC.
+const NEW_MOON: f64 = 2_451_550.26;
+
+/// Principal phases of the moon.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+    New,
+    FirstQuarter,
+    Full,
+    LastQuarter
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::New, Phase::FirstQuarter, Phase::Full,
+                                 Phase::LastQuarter];
+
+    pub fn symbol(self) -> char {
+        match self {
+            Phase::New          => '●',
+            Phase::FirstQuarter => '◐',
+            Phase::Full         => '○',
+            Phase::LastQuarter  => '◑'
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::New          => "new moon",
+            Phase::FirstQuarter => "first quarter",
+            Phase::Full         => "full moon",
+            Phase::LastQuarter  => "last quarter"
+        }
+    }
+}
+
+/// Returns the principal phase the moon reaches on the day (in UTC), if any.
+pub fn phase(date: Date) -> Option<Phase> {
+    // Julian days start at noon, so the day starts half a day earlier.
+    let midnight = julian_day(date) as f64 - 0.5;
+    let quarter  = |time: f64| ((time - NEW_MOON) / SYNODIC_MONTH * 4.0).floor() as i64;
+
+    let (start, end) = (quarter(midnight), quarter(midnight + 1.0));
+
+    if start == end {
+        None
+    } else {
+        Some(Phase::ALL[end.rem_euclid(4) as usize])
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn phase_finds_principal_phases() {
+    use chrono::{ TimeZone, UTC };
+    use date::DateRange;
+
+    let march = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1));
+    let phases = march.filter_map(|date| phase(date).map(|phase| (date, phase)))
+                      .collect::<Vec<_>>();
+
+    assert_eq!(phases, [(UTC.ymd(2015, 3,  5), Phase::Full),
+                        (UTC.ymd(2015, 3, 13), Phase::LastQuarter),
+                        (UTC.ymd(2015, 3, 20), Phase::New),
+                        (UTC.ymd(2015, 3, 27), Phase::FirstQuarter)]);
+}
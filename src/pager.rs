@@ -0,0 +1,181 @@
+//! Paging of output that doesn't fit into the terminal.
+
+use std::env;
+use std::io::{ self, Write };
+use std::mem;
+use std::process::{ Child, Command, Stdio };
+use terminal;
+
+/// Bytes a pipe holds without being read. Once more than that was written
+/// to the pager, it is reading the output.
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// Writer to the terminal that starts the pager once the output has more
+/// lines than the terminal, so that its beginning isn't scrolled away, and
+/// passes the output on to it as it is written. Output that fits is written
+/// directly by `finish`. If the pager can't be run or fails, like a
+/// misspelled `$PAGER`, the output is written directly as well.
+pub struct Pager {
+    state: State
+}
+
+enum State {
+    /// Lines held back until it's clear whether they fit.
+    Holding(Vec<u8>),
+    /// Written to the pager, along with what it was sent, until it's known
+    /// to be reading it.
+    Paging(Child, Option<Vec<u8>>),
+    /// The pager was quit: the rest isn't wanted.
+    Quit,
+    Direct
+}
+
+impl Pager {
+    pub fn new() -> Pager {
+        Pager { state: State::Holding(Vec::new()) }
+    }
+
+    /// Writes the output held back, or waits for the pager to be quit.
+    pub fn finish(mut self) -> io::Result<()> {
+        match mem::replace(&mut self.state, State::Quit) {
+            State::Holding(output) => io::stdout().write_all(&output),
+            State::Paging(mut child, sent) => {
+                // Closes the input of the pager, so that it sees the end.
+                drop(child.stdin.take());
+                let status = child.wait()?;
+
+                match sent {
+                    Some(sent) if !status.success() => io::stdout().write_all(&sent),
+                    _                               => Ok(())
+                }
+            },
+            State::Quit | State::Direct => io::stdout().flush()
+        }
+    }
+
+    /// Starts the pager with the output held back.
+    fn start(&mut self, output: Vec<u8>) -> io::Result<()> {
+        match command(env::var("PAGER").ok()).stdin(Stdio::piped()).spawn() {
+            Ok(child) => {
+                self.state = State::Paging(child, Some(Vec::new()));
+                self.send(&output)
+            },
+            Err(_) => {
+                self.state = State::Direct;
+                io::stdout().write_all(&output)
+            }
+        }
+    }
+
+    /// Writes to the pager. If it fails before it has read anything, the
+    /// output sent to it is written directly instead, along with the rest.
+    fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+        let (child, sent) = match self.state {
+            State::Paging(ref mut child, ref mut sent) => (child, sent),
+            _                                          => return Ok(())
+        };
+        let written = child.stdin.as_mut().map_or(Ok(()), |stdin| stdin.write_all(buf));
+
+        match written {
+            Ok(()) => {
+                if let Some(ref mut output) = *sent {
+                    output.extend_from_slice(buf);
+                }
+                if sent.as_ref().is_some_and(|output| output.len() > PIPE_CAPACITY) {
+                    *sent = None;
+                }
+                Ok(())
+            },
+            // Quitting the pager before the end closes the pipe.
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                let failed = !child.wait()?.success();
+
+                match sent.take() {
+                    Some(mut output) if failed => {
+                        self.state = State::Direct;
+                        output.extend_from_slice(buf);
+                        io::stdout().write_all(&output)
+                    },
+                    _ => {
+                        self.state = State::Quit;
+                        Ok(())
+                    }
+                }
+            },
+            Err(e) => Err(e)
+        }
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        let output = match self.state {
+            State::Holding(ref mut output) => {
+                output.extend_from_slice(buf);
+                mem::take(output)
+            },
+            State::Paging(..) => return self.send(buf),
+            State::Quit       => return Ok(()),
+            State::Direct     => return io::stdout().write_all(buf)
+        };
+
+        // One line is left for the prompt.
+        let lines = output.iter().filter(|&&byte| byte == b'\n').count();
+        match terminal::height() {
+            Some(height) if lines >= height => self.start(output),
+            Some(_)                         => {
+                self.state = State::Holding(output);
+                Ok(())
+            },
+            None => {
+                self.state = State::Direct;
+                io::stdout().write_all(&output)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.state {
+            State::Direct => io::stdout().flush(),
+            _             => Ok(())
+        }
+    }
+}
+
+/// The pager: `$PAGER`, run by the shell as it can have arguments, or
+/// `less -R`, which shows the escape codes of styles as styles.
+fn command(pager: Option<String>) -> Command {
+    match pager.filter(|pager| !pager.trim().is_empty()) {
+        Some(pager) => {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(pager);
+            command
+        }
+        None => {
+            let mut command = Command::new("less");
+            command.arg("-R");
+            command
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn pager_is_taken_from_the_environment() {
+    let words = |command: Command| {
+        Some(command.get_program()).into_iter()
+                                   .chain(command.get_args())
+                                   .map(|word| word.to_string_lossy().into_owned())
+                                   .collect::<Vec<_>>()
+    };
+
+    assert_eq!(words(command(None)), ["less", "-R"]);
+    assert_eq!(words(command(Some(" ".to_string()))), ["less", "-R"]);
+    assert_eq!(words(command(Some("most -s".to_string()))), ["sh", "-c", "most -s"]);
+}
@@ -0,0 +1,457 @@
+//! Parsing of dates and date ranges given on the command line.
+//!
+//! Dates are read in the `YYYY-MM-DD` format. Dates written the local way,
+//! with slashes or dots like `03/04/2024`, can be rewritten into it first
+//! with `normalize_dates`, reading day, month and year in a `DateOrder`.
+
+use chrono::{ TimeZone, UTC, Weekday };
+use date::{ add_months, check_year, dates, Date, DateRange };
+use diagnostics::{ Diagnostics, Kind };
+use std::env;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Order of the day, month and year in dates like `03/04/2024`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DateOrder {
+    Dmy,
+    Mdy,
+    Ymd
+}
+
+impl FromStr for DateOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DateOrder, String> {
+        match &*s.to_lowercase() {
+            "dmy" => Ok(DateOrder::Dmy),
+            "mdy" => Ok(DateOrder::Mdy),
+            "ymd" => Ok(DateOrder::Ymd),
+            _     => Err(format!("unknown date order: '{}' (available: dmy, mdy, ymd)", s))
+        }
+    }
+}
+
+impl DateOrder {
+    /// The order customary in the locale, e.g. `en_US.UTF-8`: month first in
+    /// the US and the POSIX locale, year first in East Asia, Hungary and
+    /// Lithuania, and day first elsewhere.
+    pub fn of_locale(locale: &str) -> DateOrder {
+        let name = locale.split(['.', '@']).next().unwrap_or("");
+        let language = name.split('_').next().unwrap_or("");
+
+        match (name, language) {
+            ("", _) | ("C", _) | ("POSIX", _)  => DateOrder::Mdy,
+            (_, "en") if name.ends_with("_US") => DateOrder::Mdy,
+            (_, "zh") | (_, "ja") | (_, "ko") | (_, "hu") | (_, "lt") => DateOrder::Ymd,
+            _ => DateOrder::Dmy
+        }
+    }
+
+    /// The order of the locale of dates and times set in the environment.
+    /// A locale that doesn't look like one, e.g. `en_US.UTF-8`, is warned
+    /// about.
+    pub fn from_env(diagnostics: &mut Diagnostics) -> DateOrder {
+        let locale = ["LC_ALL", "LC_TIME", "LANG"].iter()
+                                                  .filter_map(|name| env::var(name).ok())
+                                                  .find(|value| !value.is_empty())
+                                                  .unwrap_or_default();
+        let order  = DateOrder::of_locale(&locale);
+
+        if !locale.is_empty() && !is_locale_name(&locale) {
+            diagnostics.warn(Kind::UnknownLocale,
+                             format!("unknown locale '{}', reading dates as {}", locale,
+                                     order.describe()));
+        }
+
+        order
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            DateOrder::Dmy => "day/month/year",
+            DateOrder::Mdy => "month/day/year",
+            DateOrder::Ymd => "year/month/day"
+        }
+    }
+}
+
+/// Whether the locale is `C`, `POSIX` or a language code with an optional
+/// territory, encoding and modifier, like `pt_BR.UTF-8@euro`.
+fn is_locale_name(locale: &str) -> bool {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    let mut parts = name.splitn(2, '_');
+    let language  = parts.next().unwrap_or("");
+    let territory = parts.next();
+
+    name == "C" || name == "POSIX"
+        || (matches!(language.len(), 2 | 3) && language.chars().all(|c| c.is_ascii_lowercase())
+            && territory.is_none_or(|territory| {
+                matches!(territory.len(), 2 | 3)
+                    && territory.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            }))
+}
+
+/// A date or range of dates rewritten by `normalize_dates`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Normalized {
+    pub input:       String,
+    /// Warnings about dates whose day and month might have been meant the
+    /// other way round, with how they were read.
+    pub diagnostics: Diagnostics
+}
+
+/// Rewrites the dates written with slashes or dots (e.g. `03/04/2024` or
+/// `3.4.2024`) in a date or range of dates (`FIRST..LAST`) into
+/// `YYYY-MM-DD`, reading them in the given order. A date starting with a
+/// four-digit year is always read year first. Everything else is left as
+/// is.
+pub fn normalize_dates(input: &str, order: DateOrder) -> Result<Normalized, String> {
+    let mut diagnostics = Diagnostics::new();
+    let mut parts       = Vec::new();
+
+    for part in input.split("..") {
+        let fields = part.trim().split(['/', '.']).collect::<Vec<_>>();
+
+        if fields.len() != 3 {
+            parts.push(part.to_string());
+            continue;
+        }
+
+        let order = if fields[0].trim().len() == 4 { DateOrder::Ymd } else { order };
+        let (year, month, day) = match order {
+            DateOrder::Dmy => (fields[2], fields[1], fields[0]),
+            DateOrder::Mdy => (fields[2], fields[0], fields[1]),
+            DateOrder::Ymd => (fields[0], fields[1], fields[2])
+        };
+
+        let error = || format!("invalid date: '{}'", part.trim());
+        let year  = parse_year(year).map_err(|_| error())?;
+        let month = parse_number::<u32>(month, "month").map_err(|_| error())?;
+        let day   = parse_number::<u32>(day, "day").map_err(|_| error())?;
+        let date  = UTC.ymd_opt(year, month, day).single().ok_or_else(error)?;
+
+        if order != DateOrder::Ymd && day <= 12 && day != month {
+            diagnostics.warn(Kind::AmbiguousDate,
+                             format!("'{}' is ambiguous, read as {} ({})", part.trim(),
+                                     date.format("%Y-%m-%d"), order.describe()));
+        }
+
+        parts.push(date.format("%Y-%m-%d").to_string());
+    }
+
+    Ok(Normalized { input: parts.join(".."), diagnostics })
+}
+
+fn parse_number<T: FromStr>(input: &str, what: &str) -> Result<T, String> {
+    input.trim().parse().map_err(|_| format!("invalid {}: '{}'", what, input))
+}
+
+/// Parses a year, e.g. `2015`. Years before 1 are given astronomically,
+/// e.g. `-44`, or with `BC`, e.g. `45BC`. The year must be supported, see
+/// `date::check_year`.
+pub fn parse_year(input: &str) -> Result<i32, String> {
+    let trimmed = input.trim();
+    let year    = match trimmed.strip_suffix("BC").or_else(|| trimmed.strip_suffix("bc")) {
+        Some(year) => parse_number::<i32>(year, "year")
+                          .ok()
+                          .filter(|&year| year > 0)
+                          .map(|year| 1 - year)
+                          .ok_or_else(|| format!("invalid year: '{}'", input))?,
+        None       => parse_number(input, "year")?
+    };
+
+    check_year(year)
+}
+
+/// Parses an inclusive range of years, e.g. `2020..2030`. A single year is
+/// accepted as a range of one.
+pub fn parse_year_range(input: &str) -> Result<RangeInclusive<i32>, String> {
+    let (first, last) = match input.find("..") {
+        Some(index) => (parse_year(&input[..index])?,
+                        parse_year(&input[index + 2..])?),
+        None        => { let year = parse_year(input)?; (year, year) }
+    };
+
+    if first > last {
+        return Err(format!("invalid year range: '{}'", input));
+    }
+
+    Ok(first..=last)
+}
+
+/// Parses a day of the year without the year, e.g. `03-15`. Returns a
+/// `(month, day)` pair. February 29 is accepted.
+pub fn parse_month_day(input: &str) -> Result<(u32, u32), String> {
+    let mut parts = input.splitn(2, '-');
+    let month = parse_number(parts.next().unwrap_or(""), "month")?;
+    let day   = parse_number(parts.next().unwrap_or(""), "day")?;
+
+    // 2000 is a leap year, so this accepts every day that exists in any year.
+    if UTC.ymd_opt(2000, month, day).single().is_none() {
+        return Err(format!("invalid month and day: '{}'", input));
+    }
+
+    Ok((month, day))
+}
+
+/// Splits a date into at most `count` parts separated by `-`. A leading
+/// minus is part of the year, as in `-44-03-15`.
+fn date_parts(input: &str, count: usize) -> Vec<&str> {
+    let input    = input.trim();
+    let negative = input.starts_with('-');
+    let mut parts = input[negative as usize..].splitn(count, '-').collect::<Vec<_>>();
+
+    if negative {
+        parts[0] = &input[..parts[0].len() + 1];
+    }
+
+    parts
+}
+
+/// Parses a date in the `YYYY-MM-DD` format.
+pub fn parse_date(input: &str) -> Result<Date, String> {
+    let parts = date_parts(input, 3);
+
+    if parts.len() == 3 {
+        let year  = parse_year(parts[0])?;
+        let month = parse_number(parts[1], "month")?;
+        let day   = parse_number(parts[2], "day")?;
+
+        if let Some(date) = UTC.ymd_opt(year, month, day).single() {
+            return Ok(date);
+        }
+    }
+
+    Err(format!("invalid date: '{}'", input))
+}
+
+/// Parses a range of dates, e.g. `2024-11..2025-03`. Both ends are
+/// inclusive and may be a year, a month (`YYYY-MM`) or a day (`YYYY-MM-DD`),
+/// so `2024-11..2025-03` spans from November 1 to March 31. A single year,
+/// month or day is accepted as well.
+pub fn parse_date_range(input: &str) -> Result<DateRange, String> {
+    let (first, last) = match input.find("..") {
+        Some(index) => (parse_period(&input[..index])?, parse_period(&input[index + 2..])?),
+        None        => { let period = parse_period(input)?; (period, period) }
+    };
+
+    if first.start >= last.end {
+        return Err(format!("invalid date range: '{}'", input));
+    }
+
+    Ok(DateRange::new(first.start, last.end))
+}
+
+/// Parses a year, month or day into the range of its days.
+fn parse_period(input: &str) -> Result<DateRange, String> {
+    let parts = date_parts(input, usize::MAX);
+
+    match parts.len() {
+        1 => dates(parse_year(parts[0])?),
+        2 => {
+            let year  = parse_year(parts[0])?;
+            let month = parse_number(parts[1], "month")?;
+            let first = UTC.ymd_opt(year, month, 1)
+                           .single()
+                           .ok_or_else(|| format!("invalid month: '{}'", input))?;
+
+            Ok(DateRange::new(first, add_months(first, 1)))
+        },
+        _ => {
+            let date = parse_date(input)?;
+            Ok(DateRange::new(date, date.succ()))
+        }
+    }
+}
+
+/// Parses a month, given by its number (e.g. `4`) or by its name (e.g.
+/// `april` or `Apr`).
+pub fn parse_month(input: &str) -> Result<u32, String> {
+    const MONTHS: [&str; 12] = ["january", "february", "march", "april", "may", "june", "july",
+                                "august", "september", "october", "november", "december"];
+
+    let name = input.trim().to_lowercase();
+
+    match name.parse::<u32>() {
+        Ok(month) if (1..=12).contains(&month) => Ok(month),
+        Ok(_)  => Err(format!("invalid month: '{}'", input)),
+        Err(_) => MONTHS.iter()
+                        .position(|full| name.len() >= 3 && full.starts_with(&*name))
+                        .map(|index| index as u32 + 1)
+                        .ok_or_else(|| format!("invalid month: '{}'", input))
+    }
+}
+
+/// Parses a quarter of the year, e.g. `Q3`, `q3` or `3`. Returns its number
+/// from 1 to 4.
+pub fn parse_quarter(input: &str) -> Result<u32, String> {
+    let trimmed = input.trim();
+    let number  = trimmed.strip_prefix(|c| c == 'Q' || c == 'q').unwrap_or(trimmed);
+
+    match number.parse() {
+        Ok(quarter) if (1..=4).contains(&quarter) => Ok(quarter),
+        _ => Err(format!("invalid quarter: '{}' (available: Q1, Q2, Q3, Q4)", input))
+    }
+}
+
+/// Parses the name of a day of the week, e.g. `sunday` or `Sun`.
+pub fn parse_weekday(input: &str) -> Result<Weekday, String> {
+    const WEEKDAYS: [(&str, Weekday); 7] = [("monday",    Weekday::Mon),
+                                            ("tuesday",   Weekday::Tue),
+                                            ("wednesday", Weekday::Wed),
+                                            ("thursday",  Weekday::Thu),
+                                            ("friday",    Weekday::Fri),
+                                            ("saturday",  Weekday::Sat),
+                                            ("sunday",    Weekday::Sun)];
+
+    let name = input.trim().to_lowercase();
+
+    WEEKDAYS.iter()
+            .find(|&&(full, _)| name.len() >= 2 && full.starts_with(&*name))
+            .map(|&(_, weekday)| weekday)
+            .ok_or_else(|| format!("invalid day of the week: '{}'", input))
+}
+
+/// Checks a `strftime` format of month titles, e.g. `%b %Y`, which can't
+/// show the time of day.
+pub fn parse_title_format(input: &str) -> Result<String, String> {
+    let mut title = String::new();
+
+    match write!(title, "{}", UTC.ymd(2015, 3, 1).format(input)) {
+        Ok(()) => Ok(input.to_string()),
+        Err(_) => Err(format!("invalid title format: '{}' (expected a format of dates like \
+                               '%b %Y')", input))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn parse_year_accepts_years_before_1_and_checks_range() {
+    assert_eq!(parse_year("2015"), Ok(2015));
+    assert_eq!(parse_year("0"),    Ok(0));
+    assert_eq!(parse_year("-44"),  Ok(-44));
+    assert_eq!(parse_year("45BC"), Ok(-44));
+    assert_eq!(parse_year("1bc"),  Ok(0));
+    assert!(parse_year("0BC").is_err());
+    assert!(parse_year("-45BC").is_err());
+    assert_eq!(parse_year("1000000"),
+               Err("year 1000000 is out of range (supported are years -9999 to 9999)"
+                   .to_string()));
+}
+
+#[test]
+fn parse_year_range_parses_inclusive_ranges() {
+    assert_eq!(parse_year_range("2020..2030"), Ok(2020..=2030));
+    assert_eq!(parse_year_range("2015"),       Ok(2015..=2015));
+    assert!(parse_year_range("2030..2020").is_err());
+    assert!(parse_year_range("2020..").is_err());
+}
+
+#[test]
+fn normalize_dates_reads_dates_in_order() {
+    let normalized = normalize_dates("03/04/2024..2024-05", DateOrder::Dmy).unwrap();
+    assert_eq!(normalized.input, "2024-04-03..2024-05");
+    assert_eq!(normalized.diagnostics.warnings().iter().map(|w| &*w.message).collect::<Vec<_>>(),
+               ["'03/04/2024' is ambiguous, read as 2024-04-03 (day/month/year)"]);
+    assert_eq!(normalized.diagnostics.warnings()[0].kind, Kind::AmbiguousDate);
+
+    let normalized = normalize_dates("03/14/2024", DateOrder::Mdy).unwrap();
+    assert_eq!(normalized.input, "2024-03-14");
+    assert!(normalized.diagnostics.is_empty());
+
+    assert_eq!(normalize_dates("2024.03.04", DateOrder::Mdy).unwrap().input, "2024-03-04");
+    assert_eq!(normalize_dates("work-start", DateOrder::Dmy).unwrap().input, "work-start");
+    assert!(normalize_dates("03/14/2024", DateOrder::Dmy).is_err());
+}
+
+#[test]
+fn date_order_follows_locale() {
+    assert_eq!(DateOrder::of_locale("en_US.UTF-8"), DateOrder::Mdy);
+    assert_eq!(DateOrder::of_locale("en_GB.UTF-8"), DateOrder::Dmy);
+    assert_eq!(DateOrder::of_locale("ja_JP.UTF-8"), DateOrder::Ymd);
+    assert_eq!(DateOrder::of_locale("C"), DateOrder::Mdy);
+}
+
+#[test]
+fn is_locale_name_recognizes_locales() {
+    assert!(is_locale_name("en_US.UTF-8"));
+    assert!(is_locale_name("es_419"));
+    assert!(is_locale_name("de"));
+    assert!(is_locale_name("POSIX"));
+    assert!(!is_locale_name("english"));
+    assert!(!is_locale_name("en-us"));
+}
+
+#[test]
+fn parse_month_day_parses_month_and_day() {
+    assert_eq!(parse_month_day("03-15"), Ok((3, 15)));
+    assert_eq!(parse_month_day("2-29"),  Ok((2, 29)));
+    assert!(parse_month_day("02-30").is_err());
+    assert!(parse_month_day("13-01").is_err());
+    assert!(parse_month_day("03").is_err());
+}
+
+#[test]
+fn parse_date_parses_iso_dates() {
+    assert_eq!(parse_date("2015-03-14"), Ok(UTC.ymd(2015, 3, 14)));
+    assert_eq!(parse_date(" 2016-2-29 "), Ok(UTC.ymd(2016, 2, 29)));
+    assert!(parse_date("2015-02-29").is_err());
+    assert!(parse_date("2015-03").is_err());
+    assert!(parse_date("yesterday").is_err());
+    assert_eq!(parse_date("-44-03-15"), Ok(UTC.ymd(-44, 3, 15)));
+    assert_eq!(parse_date("0000-02-29"), Ok(UTC.ymd(0, 2, 29)));
+    assert!(parse_date("1900-02-29").is_err());
+    assert!(parse_date("10000-01-01").is_err());
+}
+
+#[test]
+fn parse_month_accepts_numbers_and_names() {
+    assert_eq!(parse_month("4"),     Ok(4));
+    assert_eq!(parse_month("April"), Ok(4));
+    assert_eq!(parse_month("sep"),   Ok(9));
+    assert!(parse_month("13").is_err());
+    assert!(parse_month("ju").is_err());
+}
+
+#[test]
+fn parse_quarter_accepts_numbers_with_or_without_q() {
+    assert_eq!(parse_quarter("Q3"), Ok(3));
+    assert_eq!(parse_quarter("q1"), Ok(1));
+    assert_eq!(parse_quarter("4"),  Ok(4));
+    assert!(parse_quarter("Q5").is_err());
+    assert!(parse_quarter("QQ1").is_err());
+}
+
+#[test]
+fn parse_weekday_accepts_names_and_abbreviations() {
+    assert_eq!(parse_weekday("Sunday"), Ok(Weekday::Sun));
+    assert_eq!(parse_weekday("tu"),     Ok(Weekday::Tue));
+    assert!(parse_weekday("t").is_err());
+    assert!(parse_weekday("someday").is_err());
+}
+
+#[test]
+fn parse_title_format_accepts_formats_of_dates() {
+    assert_eq!(parse_title_format("%b %Y"), Ok("%b %Y".to_string()));
+    assert_eq!(parse_title_format("Month %m"), Ok("Month %m".to_string()));
+    assert!(parse_title_format("%H:%M").is_err());
+    assert!(parse_title_format("%Q").is_err());
+}
+
+#[test]
+fn parse_date_range_parses_years_months_and_days() {
+    assert_eq!(parse_date_range("2024-11..2025-03"),
+               Ok(DateRange::new(UTC.ymd(2024, 11, 1), UTC.ymd(2025, 4, 1))));
+    assert_eq!(parse_date_range("2015"), dates(2015));
+    assert_eq!(parse_date_range("2015-02"),
+               Ok(DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 3, 1))));
+    assert_eq!(parse_date_range("2015-02-10..2015"),
+               Ok(DateRange::new(UTC.ymd(2015, 2, 10), UTC.ymd(2016, 1, 1))));
+    assert!(parse_date_range("2025..2024").is_err());
+    assert!(parse_date_range("2015-13").is_err());
+    assert_eq!(parse_date_range("-1..1-01"),
+               Ok(DateRange::new(UTC.ymd(-1, 1, 1), UTC.ymd(1, 2, 1))));
+}
@@ -0,0 +1,116 @@
+//! Payroll periods.
+//!
+//! A year is split into pay periods, either twice a month (the 1st to the
+//! 15th and the 16th to the end of the month) or every two weeks counted
+//! from an anchor, the first day of any pay period. Every period is paid on
+//! its last day, or on the last business day before it if that day is a
+//! weekend or a holiday. The periods of a year are those paid in it.
+
+use chrono::{ Datelike, Duration };
+use date::{ add_business_days, days_between, is_business_day, Date, DateRange };
+use format::format_dates;
+
+/// How often pay periods start.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// On the 1st and the 16th of every month.
+    Semimonthly,
+    /// Every two weeks from the anchor.
+    Biweekly(Date)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PayPeriod {
+    pub dates:   DateRange,
+    pub pay_day: Date
+}
+
+/// Returns the pay periods paid in the range. Business days skip the days
+/// `is_holiday` returns true for.
+pub fn periods<F>(range: DateRange, pattern: Pattern, is_holiday: F) -> Vec<PayPeriod>
+    where F: Fn(Date) -> bool
+{
+    // A month on either side, so that the periods paid in the range are
+    // whole, and paying early can't move a pay day into it unseen.
+    let days  = DateRange::new(range.start - Duration::days(31), range.end + Duration::days(31));
+    let key   = move |date: &Date| match pattern {
+        Pattern::Semimonthly      => (date.year(), date.month(), u32::from(date.day() > 15)),
+        Pattern::Biweekly(anchor) => (days_between(anchor, *date).div_euclid(14) as i32, 0, 0)
+    };
+    let groups = days.group_by(key).collect::<Vec<_>>();
+
+    // The first and last groups may be cut off by the extended range.
+    groups[1..groups.len() - 1].iter()
+                               .map(|&dates| PayPeriod { dates,
+                                                         pay_day: pay_day(dates, &is_holiday) })
+                               .filter(|period| range.contains(period.pay_day))
+                               .collect()
+}
+
+/// The last day of the period, or the business day before it.
+fn pay_day<F>(dates: DateRange, is_holiday: F) -> Date
+    where F: Fn(Date) -> bool
+{
+    let last = dates.end.pred();
+
+    if is_business_day(last, &is_holiday) {
+        last
+    } else {
+        // Only fails for periods ending on the first day of the first
+        // supported year, which are paid on that day then.
+        add_business_days(last, -1, is_holiday).unwrap_or(last)
+    }
+}
+
+/// Lists the periods, a line each with its days and pay day. Pay days moved
+/// before the end of their period are followed by the weekday of the end.
+pub fn list(periods: &[PayPeriod]) -> String {
+    periods.iter()
+           .map(|period| {
+               let last  = period.dates.end.pred();
+               let moved = if period.pay_day == last {
+                   String::new()
+               } else {
+                   format!(" (moved from {})", last.format("%a"))
+               };
+
+               format!("{:<22}  paid {}{}\n", format_dates(period.dates),
+                                              period.pay_day.format("%a %Y-%m-%d"), moved)
+           })
+           .collect()
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+
+#[test]
+fn semimonthly_periods_are_paid_on_the_last_business_day() {
+    let range   = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 3, 1));
+    let periods = periods(range, Pattern::Semimonthly, |date| date == UTC.ymd(2015, 1, 30));
+
+    assert_eq!(periods.len(), 4);
+    assert_eq!(periods[0].dates, DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 16)));
+    assert_eq!(list(&periods), "\
+2015-01-01..2015-01-15  paid Thu 2015-01-15
+2015-01-16..2015-01-31  paid Thu 2015-01-29 (moved from Sat)
+2015-02-01..2015-02-15  paid Fri 2015-02-13 (moved from Sun)
+2015-02-16..2015-02-28  paid Fri 2015-02-27 (moved from Sat)
+");
+}
+
+#[test]
+fn biweekly_periods_count_from_the_anchor() {
+    let range   = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1));
+    let periods = periods(range, Pattern::Biweekly(UTC.ymd(2016, 1, 9)), |_| false);
+
+    // Periods from Saturday to Friday, the first starting in the previous
+    // year.
+    assert_eq!(periods.len(), 26);
+    assert_eq!(periods[0], PayPeriod {
+        dates:   DateRange::new(UTC.ymd(2014, 12, 27), UTC.ymd(2015, 1, 10)),
+        pay_day: UTC.ymd(2015, 1, 9)
+    });
+    assert_eq!(periods[25].pay_day, UTC.ymd(2015, 12, 25));
+}
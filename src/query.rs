@@ -0,0 +1,212 @@
+//! Evaluation of the expressions of the query command.
+//!
+//! An expression is a date (`2025-01-01` or `today`), a number, a word (a
+//! country code) or a function applied to expressions, like
+//! `days_until(2025-01-01)` or `is_holiday(add_days(today,1),DE)`. It
+//! evaluates to a single value, printed without decoration for scripts.
+
+use calendar::date::{ add_business_days, add_days, business_days_between, days_between,
+                      is_business_day, week_number, weekday, Date, WeekNumbering };
+use calendar::holidays::{ self, HolidayProvider };
+use calendar::parse::parse_date;
+use chrono::Datelike;
+use std::cell::RefCell;
+use std::collections::{ HashMap, HashSet };
+use std::fmt;
+
+/// Functions a query can apply, with their arguments.
+pub const FUNCTIONS: [&str; 13] = [
+    "today", "weeknum(date)", "weekday(date)", "day_of_year(date)", "quarter(date)",
+    "days_until(date)", "days_between(date,date)", "add_days(date,days)",
+    "is_weekend(date)", "is_holiday(date[,country])", "is_workday(date[,country])",
+    "add_workdays(date,days[,country])", "workdays_between(date,date[,country])"
+];
+
+/// Value of an expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Date(Date),
+    Number(i64),
+    Bool(bool),
+    Word(String)
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Date(date)     => write!(f, "{}", date.format("%Y-%m-%d")),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Bool(value)    => write!(f, "{}", value),
+            Value::Word(ref word) => write!(f, "{}", word)
+        }
+    }
+}
+
+/// What expressions are evaluated against.
+pub struct Context {
+    pub today:     Date,
+    /// Country of `is_holiday` and `workdays_between` when none is given.
+    pub country:   Option<String>,
+    /// Numbering of the weeks of `weeknum`.
+    pub numbering: WeekNumbering
+}
+
+/// Evaluates the expression.
+pub fn evaluate(input: &str, context: &Context) -> Result<Value, String> {
+    let input = input.trim();
+
+    let (name, args) = match input.find('(') {
+        Some(open) if input.ends_with(')') => {
+            let args = split_args(&input[open + 1..input.len() - 1])?
+                           .into_iter()
+                           .map(|arg| evaluate(arg, context))
+                           .collect::<Result<Vec<_>, _>>()?;
+            (input[..open].trim(), args)
+        },
+        Some(_) => return Err(format!("unbalanced parentheses in '{}'", input)),
+        None    => return Ok(literal(input, context))
+    };
+
+    let date = |index: usize| match args.get(index) {
+        Some(&Value::Date(date)) => Ok(date),
+        Some(other)              => Err(format!("{}: expected a date, got '{}'", name, other)),
+        None                     => Err(format!("{}: missing argument {}", name, index + 1))
+    };
+    let number = |index: usize| match args.get(index) {
+        Some(&Value::Number(number)) => Ok(number),
+        Some(other)                  => {
+            Err(format!("{}: expected a number, got '{}'", name, other))
+        },
+        None                         => Err(format!("{}: missing argument {}", name, index + 1))
+    };
+    let is_holiday = |index: usize| -> Result<Box<dyn Fn(Date) -> bool>, String> {
+        let code = match args.get(index) {
+            Some(Value::Word(code)) => code.clone(),
+            Some(other)             => return Err(format!("{}: expected a country, got '{}'",
+                                                          name, other)),
+            None                    => context.country.clone().ok_or_else(|| {
+                format!("{}: no country given (add it or use --holidays)", name)
+            })?
+        };
+        let provider = holidays::country(&code).ok_or_else(|| {
+            format!("no holidays known for country '{}'", code)
+        })?;
+
+        // Counting workdays asks for many dates of the same years.
+        let years = RefCell::new(HashMap::new());
+
+        Ok(Box::new(move |date: Date| {
+            years.borrow_mut()
+                 .entry(date.year())
+                 .or_insert_with(|| provider.holidays(date.year())
+                                           .iter()
+                                           .map(|holiday| holiday.date)
+                                           .collect::<HashSet<_>>())
+                 .contains(&date)
+        }))
+    };
+
+    let value = match name {
+        "today"            => Value::Date(context.today),
+        "weeknum"          => Value::Number(i64::from(week_number(&date(0)?, context.numbering).1)),
+        "weekday"          => Value::Word(date(0)?.format("%A").to_string()),
+        "day_of_year"      => Value::Number(i64::from(date(0)?.ordinal())),
+        "quarter"          => Value::Number(i64::from(date(0)?.month0() / 3 + 1)),
+        "days_until"       => Value::Number(days_between(context.today, date(0)?)),
+        "days_between"     => Value::Number(days_between(date(0)?, date(1)?)),
+        "add_days"         => Value::Date(add_days(date(0)?, number(1)?)?),
+        "is_weekend"       => Value::Bool(weekday(date(0)?) >= 5),
+        "is_holiday"       => Value::Bool((is_holiday(1)?)(date(0)?)),
+        "is_workday"       => Value::Bool(is_business_day(date(0)?, is_holiday(1)?)),
+        "add_workdays"     => Value::Date(add_business_days(date(0)?, number(1)?,
+                                                            is_holiday(2)?)?),
+        "workdays_between" => Value::Number(business_days_between(date(0)?, date(1)?,
+                                                                  is_holiday(2)?)),
+        _                  => return Err(format!("unknown function '{}' (available: {})",
+                                                 name, FUNCTIONS.join(", ")))
+    };
+
+    Ok(value)
+}
+
+/// Value of an expression without parentheses.
+fn literal(input: &str, context: &Context) -> Value {
+    if input == "today" {
+        Value::Date(context.today)
+    } else if let Ok(date) = parse_date(input) {
+        Value::Date(date)
+    } else if let Ok(number) = input.parse() {
+        Value::Number(number)
+    } else {
+        Value::Word(input.to_string())
+    }
+}
+
+/// Splits the arguments of a function at the commas outside parentheses.
+fn split_args(input: &str) -> Result<Vec<&str>, String> {
+    if input.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args  = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in input.char_indices() {
+        match c {
+            '('               => depth += 1,
+            ')' if depth == 0 => return Err(format!("unbalanced parentheses in '{}'", input)),
+            ')'               => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(&input[start..index]);
+                start = index + 1;
+            },
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(format!("unbalanced parentheses in '{}'", input));
+    }
+
+    args.push(&input[start..]);
+    Ok(args)
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+
+#[cfg(test)]
+fn query(input: &str) -> Result<String, String> {
+    let context = Context { today:     UTC.ymd(2024, 12, 20),
+                            country:   Some("DE".to_string()),
+                            numbering: WeekNumbering::Iso };
+    evaluate(input, &context).map(|value| value.to_string())
+}
+
+#[test]
+fn evaluate_applies_functions() {
+    assert_eq!(query("weeknum(today)"), Ok("51".to_string()));
+    assert_eq!(query("weeknum(2024-12-30)"), Ok("1".to_string()));
+    assert_eq!(query("days_until(2025-01-01)"), Ok("12".to_string()));
+    assert_eq!(query("weekday(add_days(today, -1))"), Ok("Thursday".to_string()));
+    assert_eq!(query("is_holiday(2024-12-25,DE)"), Ok("true".to_string()));
+    assert_eq!(query("is_holiday(2024-12-27)"), Ok("false".to_string()));
+    assert_eq!(query("workdays_between(2024-12-23,2025-01-06)"), Ok("7".to_string()));
+    assert_eq!(query("today"), Ok("2024-12-20".to_string()));
+}
+
+#[test]
+fn evaluate_rejects_malformed_queries() {
+    assert!(query("weeknum(today").is_err());
+    assert!(query("weeknum(today))").is_err());
+    assert!(query("weeknum(5)").is_err());
+    assert!(query("days_between(today)").is_err());
+    assert!(query("nonsense(today)").is_err());
+    assert!(query("add_days(today,9223372036854775807)").is_err());
+    assert!(query("add_days(9999-12-31,1)").is_err());
+    assert!(query("add_workdays(today,1000000000,DE)").is_err());
+    assert!(query("add_workdays(9999-12-30,2,DE)").is_err());
+}
@@ -0,0 +1,80 @@
+//! Random dates.
+//!
+//! Dates are drawn with a small built-in generator (SplitMix64), so that the
+//! same seed gives the same dates on every platform and in every version.
+
+use date::{ Date, DateRange };
+use std::collections::HashSet;
+
+/// SplitMix64 pseudo-random number generator.
+struct Rng {
+    state: u64
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `bound`, which must not be zero.
+    fn below(&mut self, bound: u32) -> u32 {
+        (self.next() % bound as u64) as u32
+    }
+}
+
+/// Draws `count` distinct dates of the range for which `include` returns
+/// true, in the order they were drawn. Fails if there aren't enough such
+/// dates.
+pub fn sample<F>(range: DateRange, count: usize, seed: u64, include: F)
+    -> Result<Vec<Date>, String>
+    where F: Fn(Date) -> bool
+{
+    let available = range.filter(|&date| include(date)).count();
+    if count > available {
+        return Err(format!("can't draw {} dates, the range has only {}", count, available));
+    }
+
+    let mut rng    = Rng::new(seed);
+    let mut drawn  = HashSet::new();
+    let mut result = Vec::with_capacity(count);
+
+    while result.len() < count {
+        let date = range.day(rng.below(range.days())).expect("offset in range");
+
+        if include(date) && drawn.insert(date) {
+            result.push(date);
+        }
+    }
+
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn sample_is_deterministic_and_distinct() {
+    use chrono::{ TimeZone, UTC };
+    use date::weekday;
+
+    let range   = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 2, 1));
+    let weekday = |date: Date| weekday(date) < 5;
+
+    let dates = sample(range, 10, 42, weekday).unwrap();
+    assert_eq!(dates, sample(range, 10, 42, weekday).unwrap());
+    assert_ne!(dates, sample(range, 10, 43, weekday).unwrap());
+
+    assert_eq!(dates.iter().collect::<HashSet<_>>().len(), 10);
+    assert!(dates.iter().all(|&date| weekday(date) && date >= range.start && date < range.end));
+
+    assert_eq!(sample(range, 22, 1, weekday).unwrap().len(), 22);
+    assert!(sample(range, 23, 1, weekday).is_err());
+}
@@ -0,0 +1,773 @@
+//! Recurrence rules (RRULE) as defined by iCalendar (RFC 5545).
+//!
+//! Supported are the `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY` and `YEARLY`),
+//! `INTERVAL`, `BYDAY`, `BYMONTH`, `COUNT` and `UNTIL` parts. Weeks start
+//! on Monday.
+//!
+//! Besides, there are named rules for what RRULEs can't say or only
+//! awkwardly, like `last-business-day-of-month` or
+//! `first-monday-of-quarter`.
+
+use chrono::{ Datelike, Duration, TimeZone, UTC, Weekday };
+use date::{ self, add_months, is_business_day, Date, DateRange, MAX_YEAR, MIN_YEAR };
+use std::fmt;
+use std::str::FromStr;
+
+/// Years searched for occurrences by `Rule::first`, so that rules that never
+/// match, e.g. February 30, don't search forever.
+const SEARCH_YEARS: i32 = 400;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly
+}
+
+/// Weekday in a `BYDAY` list, optionally restricted to the n-th such weekday
+/// of the month or year (counted from the end if negative).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ByDay {
+    pub weekday: Weekday,
+    pub nth:     Option<i32>
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval:  u32,
+    pub by_day:    Vec<ByDay>,
+    pub by_month:  Vec<u32>,
+    pub count:     Option<u32>,
+    pub until:     Option<Date>
+}
+
+impl Rule {
+    pub fn new(frequency: Frequency) -> Rule {
+        Rule {
+            frequency,
+            interval: 1,
+            by_day:   Vec::new(),
+            by_month: Vec::new(),
+            count:    None,
+            until:    None
+        }
+    }
+
+    /// Returns the dates of the occurrences of an event first taking place on
+    /// `start`, up to (excluding) `end`.
+    pub fn dates(&self, start: Date, end: Date) -> Vec<Date> {
+        self.dates_in(start, DateRange::new(start, end))
+    }
+
+    /// Returns the dates of the occurrences of an event first taking place on
+    /// `start` that are in the range. Unless the rule has a `COUNT`, the
+    /// periods before the range are skipped rather than gone through, so
+    /// that events starting long before it are quick to look up.
+    pub fn dates_in(&self, start: Date, range: DateRange) -> Vec<Date> {
+        let mut result = Vec::new();
+        let mut seen   = 0;
+        let end = match self.until {
+            Some(until) if until < range.end => until.succ(),
+            _                                => range.end
+        };
+        let skipped = match self.count {
+            None    => self.periods_before(start, range.start),
+            Some(_) => 0
+        };
+
+        for period in skipped.. {
+            let (first, candidates) = match self.period(start, period) {
+                Some(period) => period,
+                None         => break
+            };
+            if first >= end { break; }
+
+            for date in candidates {
+                if date < start { continue; }
+                if date >= end  { return result; }
+                if self.count.is_some_and(|count| seen >= count) { return result; }
+
+                seen += 1;
+                if date >= range.start {
+                    result.push(date);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Number of periods after the one containing `start` that end before
+    /// `from`, or fewer.
+    fn periods_before(&self, start: Date, from: Date) -> u32 {
+        if from <= start {
+            return 0;
+        }
+
+        let months  = |date: Date| i64::from(date.year()) * 12 + i64::from(date.month0());
+        let periods = match self.frequency {
+            Frequency::Daily   => date::days_between(start, from),
+            Frequency::Weekly  => date::days_between(start, from) / 7,
+            Frequency::Monthly => months(from) - months(start),
+            Frequency::Yearly  => i64::from(from.year() - start.year())
+        };
+
+        // The period before the one reached is complete, whatever day of
+        // its period `start` is on.
+        (periods / i64::from(self.interval) - 1).max(0) as u32
+    }
+
+    /// Returns the dates of the first `count` occurrences of an event first
+    /// taking place on `start`. Fewer if the rule ends before, or if there
+    /// are no more within `SEARCH_YEARS`.
+    pub fn first(&self, start: Date, count: usize) -> Vec<Date> {
+        let mut years = 1;
+
+        loop {
+            let end    = add_months(start, 12 * years);
+            let result = self.dates(start, end);
+            let ended  = self.until.is_some_and(|until| until < end);
+
+            if result.len() >= count || years >= SEARCH_YEARS || ended {
+                return result.into_iter().take(count).collect();
+            }
+
+            years = (years * 2).min(SEARCH_YEARS);
+        }
+    }
+
+    /// Returns the first day of the `index`-th period after the one containing
+    /// `start`, and the ordered candidate dates in that period. None once the
+    /// period starts after `MAX_YEAR`.
+    fn period(&self, start: Date, index: u32) -> Option<(Date, Vec<Date>)> {
+        // Steps of more periods than fit in the supported years start after
+        // them from any start, which keeps the dates below in chrono's range.
+        let per_year = match self.frequency {
+            Frequency::Daily   => 366,
+            Frequency::Weekly  => 53,
+            Frequency::Monthly => 12,
+            Frequency::Yearly  => 1
+        };
+        let step = u64::from(index) * u64::from(self.interval);
+        if step > per_year * (MAX_YEAR - MIN_YEAR + 1) as u64 {
+            return None;
+        }
+        let step = step as i64;
+
+        let period = match self.frequency {
+            Frequency::Daily => {
+                let day = start + Duration::days(step);
+                let candidates = if self.matches_day(day) && self.matches_month(day) {
+                    vec![day]
+                } else {
+                    vec![]
+                };
+
+                (day, candidates)
+            },
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64)
+                                   + Duration::weeks(step);
+                let mut candidates = if self.by_day.is_empty() {
+                    vec![monday + Duration::days(start.weekday().num_days_from_monday() as i64)]
+                } else {
+                    self.by_day.iter()
+                               .map(|by_day| monday + Duration::days(
+                                   by_day.weekday.num_days_from_monday() as i64))
+                               .collect()
+                };
+                candidates.retain(|&date| self.matches_month(date));
+                candidates.sort();
+
+                (monday, candidates)
+            },
+            Frequency::Monthly => {
+                let first = add_months(start, step as i32);
+                let candidates = if self.matches_month(first) {
+                    self.in_month(start, first)
+                } else {
+                    vec![]
+                };
+
+                (first, candidates)
+            },
+            Frequency::Yearly => {
+                let first = UTC.ymd(start.year() + step as i32, 1, 1);
+                let mut candidates = if !self.by_month.is_empty() {
+                    self.by_month.iter()
+                                 .flat_map(|&month| self.in_month(start,
+                                                                  UTC.ymd(first.year(), month, 1)))
+                                 .collect()
+                } else if !self.by_day.is_empty() {
+                    self.by_weekdays(first, add_months(first, 12))
+                } else {
+                    UTC.ymd_opt(first.year(), start.month(), start.day())
+                       .single()
+                       .into_iter()
+                       .collect()
+                };
+                candidates.sort();
+                candidates.dedup();
+
+                (first, candidates)
+            }
+        };
+
+        Some(period).filter(|&(first, _)| first.year() <= MAX_YEAR)
+    }
+
+    /// Candidate dates in the month starting at `first`.
+    fn in_month(&self, start: Date, first: Date) -> Vec<Date> {
+        if self.by_day.is_empty() {
+            UTC.ymd_opt(first.year(), first.month(), start.day())
+               .single()
+               .into_iter()
+               .collect()
+        } else {
+            self.by_weekdays(first, add_months(first, 1))
+        }
+    }
+
+    /// Dates in `[first, end)` matching the `BYDAY` list.
+    fn by_weekdays(&self, first: Date, end: Date) -> Vec<Date> {
+        let mut result = Vec::new();
+
+        for by_day in &self.by_day {
+            let all = weekdays_between(first, end, by_day.weekday);
+
+            match by_day.nth {
+                Some(n) => result.extend(nth(&all, n)),
+                None    => result.extend(all)
+            }
+        }
+
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    fn matches_day(&self, date: Date) -> bool {
+        self.by_day.is_empty()
+            || self.by_day.iter().any(|by_day| by_day.weekday == date.weekday())
+    }
+
+    fn matches_month(&self, date: Date) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Parses a rule such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE`. A leading
+    /// `RRULE:` is ignored.
+    fn from_str(s: &str) -> Result<Rule, String> {
+        let input = s.trim();
+        let input = input.strip_prefix("RRULE:").unwrap_or(input);
+
+        let mut frequency = None;
+        let mut rule      = Rule::new(Frequency::Daily);
+
+        for part in input.split(';').filter(|part| !part.is_empty()) {
+            let mut pair = part.splitn(2, '=');
+            let name  = pair.next().unwrap_or("");
+            let value = pair.next().ok_or_else(|| format!("invalid rule part: '{}'", part))?;
+
+            match name {
+                "FREQ"     => frequency     = Some(parse_frequency(value)?),
+                "INTERVAL" => rule.interval = parse_positive(name, value)?,
+                "COUNT"    => rule.count    = Some(parse_positive(name, value)?),
+                "UNTIL"    => rule.until    = Some(parse_until(value)?),
+                "BYDAY"    => rule.by_day   = value.split(',')
+                                                   .map(parse_by_day)
+                                                   .collect::<Result<_, _>>()?,
+                "BYMONTH"  => rule.by_month = value.split(',')
+                                                   .map(parse_month)
+                                                   .collect::<Result<_, _>>()?,
+                "WKST"     => {},
+                _          => return Err(format!("unsupported rule part: '{}'", part))
+            }
+        }
+
+        rule.frequency = frequency.ok_or_else(|| format!("rule without FREQ: '{}'", s))?;
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Writes the rule as it is parsed, without the default `INTERVAL=1`,
+    /// e.g. `FREQ=MONTHLY;BYDAY=-1FR`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily   => "DAILY",
+            Frequency::Weekly  => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly  => "YEARLY"
+        };
+        write!(f, "FREQ={}", frequency)?;
+
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days = self.by_day.iter().map(format_by_day).collect::<Vec<_>>();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month.is_empty() {
+            let months = self.by_month.iter().map(u32::to_string).collect::<Vec<_>>();
+            write!(f, ";BYMONTH={}", months.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Which day of a period a named rule picks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Day,
+    /// Day that isn't on a weekend or a holiday.
+    BusinessDay,
+    Weekday(Weekday)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Period {
+    Month,
+    Quarter,
+    Year
+}
+
+impl Period {
+    fn months(self) -> i32 {
+        match self {
+            Period::Month   => 1,
+            Period::Quarter => 3,
+            Period::Year    => 12
+        }
+    }
+
+    /// First day of the period containing the date.
+    fn first(self, date: Date) -> Date {
+        let months = self.months() as u32;
+        UTC.ymd(date.year(), date.month0() / months * months + 1, 1)
+    }
+}
+
+const ORDINALS: [(&str, i32); 6] = [("first", 1), ("second", 2), ("third", 3), ("fourth", 4),
+                                    ("fifth", 5), ("last", -1)];
+
+const WEEKDAYS: [(&str, Weekday); 7] = [("monday", Weekday::Mon), ("tuesday", Weekday::Tue),
+                                        ("wednesday", Weekday::Wed),
+                                        ("thursday", Weekday::Thu), ("friday", Weekday::Fri),
+                                        ("saturday", Weekday::Sat), ("sunday", Weekday::Sun)];
+
+/// Rule named like `last-business-day-of-month` or `second-tuesday-of-year`:
+/// the n-th (`first` to `fifth`, or `last`) day, business day or weekday of
+/// every month, quarter or year.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NamedRule {
+    /// Counted from the end if negative.
+    pub nth:    i32,
+    pub unit:   Unit,
+    pub period: Period
+}
+
+impl NamedRule {
+    /// Returns the dates of the occurrences from `start`, up to (excluding)
+    /// `end`. Business days skip the days `is_holiday` returns true for.
+    pub fn dates<F>(&self, start: Date, end: Date, is_holiday: F) -> Vec<Date>
+        where F: Fn(Date) -> bool
+    {
+        self.dates_in(start, DateRange::new(start, end), is_holiday)
+    }
+
+    /// Returns the dates of the occurrences from `start` that are in the
+    /// range, starting with the period the range starts in.
+    pub fn dates_in<F>(&self, start: Date, range: DateRange, is_holiday: F) -> Vec<Date>
+        where F: Fn(Date) -> bool
+    {
+        let (start, end) = (start.max(range.start), range.end);
+        let mut result   = Vec::new();
+        let mut first    = self.period.first(start);
+
+        while first < end {
+            let next = add_months(first, self.period.months());
+            let days = match self.unit {
+                Unit::Day              => days_between(first, next),
+                Unit::BusinessDay      => days_between(first, next)
+                                              .into_iter()
+                                              .filter(|&date| is_business_day(date, &is_holiday))
+                                              .collect(),
+                Unit::Weekday(weekday) => weekdays_between(first, next, weekday)
+            };
+
+            result.extend(nth(&days, self.nth).filter(|&date| date >= start && date < end));
+            first = next;
+        }
+
+        result
+    }
+}
+
+impl FromStr for NamedRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<NamedRule, String> {
+        let error = || format!("unknown rule: '{}' (expected e.g. last-business-day-of-month \
+                                or first-monday-of-quarter)", s);
+
+        let (day, period) = s.trim().rsplit_once("-of-").ok_or_else(error)?;
+        let (nth, unit)   = day.split_once('-').ok_or_else(error)?;
+
+        let nth    = ORDINALS.iter().find(|&&(name, _)| name == nth).ok_or_else(error)?.1;
+        let unit   = match unit {
+            "day"          => Unit::Day,
+            "business-day" => Unit::BusinessDay,
+            unit           => Unit::Weekday(WEEKDAYS.iter()
+                                                    .find(|&&(name, _)| name == unit)
+                                                    .ok_or_else(error)?
+                                                    .1)
+        };
+        let period = match period {
+            "month"   => Period::Month,
+            "quarter" => Period::Quarter,
+            "year"    => Period::Year,
+            _         => return Err(error())
+        };
+
+        Ok(NamedRule { nth, unit, period })
+    }
+}
+
+impl fmt::Display for NamedRule {
+    /// Writes the name of the rule, e.g. `last-business-day-of-month`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nth  = ORDINALS.iter().find(|&&(_, nth)| nth == self.nth).map_or("", |&(name, _)| name);
+        let unit = match self.unit {
+            Unit::Day              => "day",
+            Unit::BusinessDay      => "business-day",
+            Unit::Weekday(weekday) => WEEKDAYS[weekday.num_days_from_monday() as usize].0
+        };
+        let period = match self.period {
+            Period::Month   => "month",
+            Period::Quarter => "quarter",
+            Period::Year    => "year"
+        };
+
+        write!(f, "{}-{}-of-{}", nth, unit, period)
+    }
+}
+
+/// How an event recurs: an RRULE or a named rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Recurrence {
+    Rule(Rule),
+    Named(NamedRule)
+}
+
+impl Recurrence {
+    /// Returns the dates of the occurrences of an event first taking place on
+    /// `start` that are in the range. Business days of named rules skip the
+    /// days `is_holiday` returns true for.
+    pub fn dates_in<F>(&self, start: Date, range: DateRange, is_holiday: F) -> Vec<Date>
+        where F: Fn(Date) -> bool
+    {
+        match *self {
+            Recurrence::Rule(ref rule)   => rule.dates_in(start, range),
+            Recurrence::Named(ref named) => named.dates_in(start, range, is_holiday)
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    /// Parses an RRULE if the input starts with `FREQ=` or `RRULE:`, and a
+    /// named rule otherwise.
+    fn from_str(s: &str) -> Result<Recurrence, String> {
+        let input = s.trim();
+
+        if input.starts_with("FREQ=") || input.starts_with("RRULE:") {
+            input.parse().map(Recurrence::Rule)
+        } else {
+            input.parse().map(Recurrence::Named)
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Recurrence::Rule(ref rule)   => rule.fmt(f),
+            Recurrence::Named(ref named) => named.fmt(f)
+        }
+    }
+}
+
+fn format_by_day(by_day: &ByDay) -> String {
+    let weekday = match by_day.weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU"
+    };
+
+    match by_day.nth {
+        Some(nth) => format!("{}{}", nth, weekday),
+        None      => weekday.to_string()
+    }
+}
+
+/// The n-th of the dates, counted from the end if negative.
+fn nth(dates: &[Date], nth: i32) -> Option<Date> {
+    if nth > 0 {
+        dates.get(nth as usize - 1).cloned()
+    } else {
+        dates.len().checked_sub((-nth) as usize).map(|index| dates[index])
+    }
+}
+
+/// All dates in `[first, end)`.
+fn days_between(first: Date, end: Date) -> Vec<Date> {
+    (0..(end - first).num_days()).map(|days| first + Duration::days(days)).collect()
+}
+
+/// All dates in `[first, end)` falling on the given weekday.
+fn weekdays_between(first: Date, end: Date, weekday: Weekday) -> Vec<Date> {
+    let offset = (7 + weekday.num_days_from_monday()
+                    - first.weekday().num_days_from_monday()) % 7;
+    let mut date   = first + Duration::days(offset as i64);
+    let mut result = Vec::new();
+
+    while date < end {
+        result.push(date);
+        date = date + Duration::weeks(1);
+    }
+
+    result
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, String> {
+    match value {
+        "DAILY"   => Ok(Frequency::Daily),
+        "WEEKLY"  => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY"  => Ok(Frequency::Yearly),
+        _         => Err(format!("unsupported frequency: '{}'", value))
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(num) if num > 0 => Ok(num),
+        _ => Err(format!("{} must be a positive number: '{}'", name, value))
+    }
+}
+
+fn parse_month(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(month) if (1..=12).contains(&month) => Ok(month),
+        _ => Err(format!("invalid month: '{}'", value))
+    }
+}
+
+fn parse_until(value: &str) -> Result<Date, String> {
+    let digits = |range: ::std::ops::Range<usize>| value.get(range)
+                                                       .and_then(|s| s.parse().ok());
+
+    match (digits(0..4), digits(4..6), digits(6..8)) {
+        (Some(year), Some(month), Some(day)) => {
+            UTC.ymd_opt(year as i32, month, day)
+               .single()
+               .ok_or_else(|| format!("invalid UNTIL: '{}'", value))
+        },
+        _ => Err(format!("invalid UNTIL: '{}'", value))
+    }
+}
+
+fn parse_by_day(value: &str) -> Result<ByDay, String> {
+    let error = || format!("invalid BYDAY: '{}'", value);
+    if value.len() < 2 { return Err(error()); }
+
+    let (nth, weekday) = value.split_at(value.len() - 2);
+    let weekday = match weekday {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _    => return Err(error())
+    };
+
+    let nth = match nth {
+        ""  => None,
+        nth => match nth.trim_start_matches('+').parse::<i32>() {
+            Ok(nth) if nth != 0 && nth.abs() <= 53 => Some(nth),
+            _                                      => return Err(error())
+        }
+    };
+
+    Ok(ByDay { weekday, nth })
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+fn dates(rule: &str, start: Date, end: Date) -> Vec<Date> {
+    rule.parse::<Rule>().unwrap().dates(start, end)
+}
+
+#[test]
+fn dates_in_skips_the_periods_before_the_range() {
+    let start = UTC.ymd(2015, 1, 31);
+    let range = DateRange::new(UTC.ymd(2017, 3, 1), UTC.ymd(2017, 9, 1));
+
+    for rule in ["FREQ=DAILY;INTERVAL=9", "FREQ=WEEKLY;INTERVAL=3;BYDAY=MO,SU",
+                 "FREQ=MONTHLY;INTERVAL=5", "FREQ=YEARLY;BYMONTH=3,8", "FREQ=MONTHLY;COUNT=30"] {
+        let rule = rule.parse::<Rule>().unwrap();
+        let all  = rule.dates(start, range.end).into_iter()
+                                               .filter(|&date| range.contains(date))
+                                               .collect::<Vec<_>>();
+
+        assert_eq!(rule.dates_in(start, range), all, "{}", rule);
+    }
+}
+
+#[test]
+fn rule_can_be_parsed() {
+    let rule = "RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,2MO;COUNT=5".parse();
+
+    assert_eq!(rule, Ok(Rule {
+        frequency: Frequency::Monthly,
+        interval:  2,
+        by_day:    vec![ByDay { weekday: Weekday::Fri, nth: Some(-1) },
+                        ByDay { weekday: Weekday::Mon, nth: Some(2) }],
+        by_month:  vec![],
+        count:     Some(5),
+        until:     None
+    }));
+
+    assert!("INTERVAL=2".parse::<Rule>().is_err());
+    assert!("FREQ=HOURLY".parse::<Rule>().is_err());
+    assert!("FREQ=DAILY;BYDAY=XX".parse::<Rule>().is_err());
+}
+
+#[test]
+fn rule_is_written_as_parsed() {
+    let rule = "RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,MO;BYMONTH=3,9;UNTIL=20151231"
+        .parse::<Rule>()
+        .unwrap();
+
+    assert_eq!(rule.to_string(),
+               "FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,MO;BYMONTH=3,9;UNTIL=20151231");
+    assert_eq!(Rule::new(Frequency::Weekly).to_string(), "FREQ=WEEKLY");
+}
+
+#[test]
+fn first_returns_given_number_of_occurrences() {
+    let rule = "FREQ=YEARLY;BYMONTH=2;BYDAY=-1MO".parse::<Rule>().unwrap();
+
+    assert_eq!(rule.first(UTC.ymd(2015, 3, 1), 3),
+               vec![UTC.ymd(2016, 2, 29), UTC.ymd(2017, 2, 27), UTC.ymd(2018, 2, 26)]);
+    let rule = "FREQ=DAILY;COUNT=2".parse::<Rule>().unwrap();
+    assert_eq!(rule.first(UTC.ymd(2015, 1, 1), 5).len(), 2);
+}
+
+#[test]
+fn daily_rule_repeats_every_interval_days() {
+    assert_eq!(dates("FREQ=DAILY;INTERVAL=3", UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 6)),
+               vec![UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 2), UTC.ymd(2015, 2, 5)]);
+    assert_eq!(dates("FREQ=DAILY;BYDAY=SA,SU;COUNT=3", UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1)),
+               vec![UTC.ymd(2015, 1, 3), UTC.ymd(2015, 1, 4), UTC.ymd(2015, 1, 10)]);
+}
+
+#[test]
+fn rules_stop_at_last_supported_year() {
+    for frequency in &["DAILY", "WEEKLY", "MONTHLY", "YEARLY"] {
+        let rule = format!("FREQ={};INTERVAL=4000000000", frequency).parse::<Rule>().unwrap();
+        assert_eq!(rule.first(UTC.ymd(2025, 1, 1), 3), vec![UTC.ymd(2025, 1, 1)]);
+    }
+
+    assert_eq!(dates("FREQ=YEARLY;INTERVAL=5000", UTC.ymd(2015, 1, 1), UTC.ymd(20000, 1, 1)),
+               vec![UTC.ymd(2015, 1, 1), UTC.ymd(7015, 1, 1)]);
+}
+
+#[test]
+fn weekly_rule_repeats_on_given_weekdays() {
+    assert_eq!(dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH", UTC.ymd(2015, 1, 1),
+                     UTC.ymd(2015, 1, 20)),
+               vec![UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 12), UTC.ymd(2015, 1, 15)]);
+    assert_eq!(dates("FREQ=WEEKLY;UNTIL=20150115", UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1)),
+               vec![UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 8), UTC.ymd(2015, 1, 15)]);
+}
+
+#[test]
+fn monthly_rule_repeats_on_day_of_month_or_nth_weekday() {
+    assert_eq!(dates("FREQ=MONTHLY", UTC.ymd(2015, 1, 31), UTC.ymd(2015, 6, 1)),
+               vec![UTC.ymd(2015, 1, 31), UTC.ymd(2015, 3, 31), UTC.ymd(2015, 5, 31)]);
+    assert_eq!(dates("FREQ=MONTHLY;BYDAY=-1FR", UTC.ymd(2015, 1, 1), UTC.ymd(2015, 4, 1)),
+               vec![UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 27), UTC.ymd(2015, 3, 27)]);
+}
+
+#[test]
+fn yearly_rule_repeats_on_date_or_nth_weekday_of_month() {
+    assert_eq!(dates("FREQ=YEARLY", UTC.ymd(2012, 2, 29), UTC.ymd(2020, 3, 1)),
+               vec![UTC.ymd(2012, 2, 29), UTC.ymd(2016, 2, 29), UTC.ymd(2020, 2, 29)]);
+    assert_eq!(dates("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", UTC.ymd(2014, 1, 1), UTC.ymd(2016, 1, 1)),
+               vec![UTC.ymd(2014, 11, 27), UTC.ymd(2015, 11, 26)]);
+}
+
+#[test]
+fn named_rule_can_be_parsed_and_written() {
+    let rule = "last-business-day-of-month".parse::<NamedRule>();
+    assert_eq!(rule, Ok(NamedRule { nth: -1, unit: Unit::BusinessDay, period: Period::Month }));
+    assert_eq!(rule.unwrap().to_string(), "last-business-day-of-month");
+
+    let rule = "first-monday-of-quarter".parse::<Recurrence>().unwrap();
+    assert_eq!(rule, Recurrence::Named(NamedRule { nth:    1,
+                                                   unit:   Unit::Weekday(Weekday::Mon),
+                                                   period: Period::Quarter }));
+    assert_eq!(rule.to_string(), "first-monday-of-quarter");
+
+    assert!("sixth-day-of-month".parse::<NamedRule>().is_err());
+    assert!("last-workday-of-month".parse::<NamedRule>().is_err());
+    assert!("last-day-of-week".parse::<NamedRule>().is_err());
+}
+
+#[test]
+fn named_rule_picks_nth_day_of_every_period() {
+    let named = |rule: &str| rule.parse::<NamedRule>().unwrap();
+    // Good Friday and Easter Monday.
+    let holiday = |date| date == UTC.ymd(2015, 4, 3) || date == UTC.ymd(2015, 4, 6);
+
+    assert_eq!(named("last-business-day-of-month").dates(UTC.ymd(2015, 1, 1),
+                                                          UTC.ymd(2015, 6, 1), |_| false),
+               vec![UTC.ymd(2015, 1, 30), UTC.ymd(2015, 2, 27), UTC.ymd(2015, 3, 31),
+                    UTC.ymd(2015, 4, 30), UTC.ymd(2015, 5, 29)]);
+    assert_eq!(named("first-business-day-of-month").dates(UTC.ymd(2015, 4, 1),
+                                                           UTC.ymd(2015, 5, 1), |_| false),
+               vec![UTC.ymd(2015, 4, 1)]);
+    assert_eq!(named("third-business-day-of-month").dates(UTC.ymd(2015, 4, 1),
+                                                           UTC.ymd(2015, 5, 1), holiday),
+               vec![UTC.ymd(2015, 4, 7)]);
+    assert_eq!(named("first-monday-of-quarter").dates(UTC.ymd(2015, 2, 1),
+                                                       UTC.ymd(2016, 1, 1), |_| false),
+               vec![UTC.ymd(2015, 4, 6), UTC.ymd(2015, 7, 6), UTC.ymd(2015, 10, 5)]);
+    assert_eq!(named("last-day-of-year").dates(UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1),
+                                               |_| false),
+               vec![UTC.ymd(2015, 12, 31)]);
+}
@@ -0,0 +1,301 @@
+//! Events written for the `remind` and `when` programs, so that their files
+//! can be read as events files.
+//!
+//! Of remind, `REM` commands are read with the text after `MSG`, `MSF` or
+//! `CAL`: on a date (`REM 14 Mar 2015 MSG Pi day`, `REM 2015-03-14 MSG
+//! ...`), every year (`REM 25 Dec`), every month (`REM 1`), or on days of
+//! the week (`REM Mon Wed`), where a day of the month after them picks the
+//! first such weekday from it, e.g. the second Monday with `REM Mon 8`.
+//! Advance warnings (`+3`), times (`AT 10:00`), durations, priorities and
+//! tags are ignored, and so are other commands, like `SET` and `OMIT`.
+//! Reminders using anything else, like `UNTIL`, `SKIP` or repeats (`*7`),
+//! can't be read; the store leaves them out with a warning.
+//!
+//! Of when, lines like `2015 mar 14 , Pi day` are read, with `*` for every
+//! year or month, and expressions of `y` (year), `m` (month), `d` (day),
+//! `w` (day of the week) and `a` (its occurrence in the month) joined by
+//! `&`, e.g. `w=mon & a=1 , Planning`.
+
+use chrono::{ Datelike, TimeZone, UTC, Weekday };
+use date::{ Date, DateRange, MIN_YEAR };
+use events::Event;
+use parse::{ parse_date, parse_month, parse_weekday, parse_year };
+use recurrence::{ ByDay, Frequency, Recurrence, Rule };
+
+/// Year recurring events are taken to start in, the first supported one, as
+/// neither format has a first date. Yearly events on February 29 start in
+/// the next leap year.
+const FIRST_YEAR: i32 = MIN_YEAR;
+
+/// Commands of remind that aren't reminders.
+const COMMANDS: [&str; 20] = ["BANNER", "CLEAR-OMIT-CONTEXT", "DEBUG", "DUMPVARS", "ERRMSG",
+                              "EXIT", "FLUSH", "FSET", "IF", "ELSE", "ENDIF", "IFTRIG", "INCLUDE",
+                              "OMIT", "POP-OMIT-CONTEXT", "PRESERVE", "PUSH-OMIT-CONTEXT", "SET",
+                              "UNSET", "FUNSET"];
+
+/// Words of a reminder before its text.
+const BODIES: [&str; 3] = ["MSG", "MSF", "CAL"];
+
+/// Whether the line is a reminder of remind, i.e. a `REM` command.
+pub fn is_reminder(line: &str) -> bool {
+    first_word(line).eq_ignore_ascii_case("REM")
+}
+
+/// Whether the line is a command of remind other than a reminder, or one of
+/// its comments, starting with `;`.
+pub fn is_command(line: &str) -> bool {
+    let word = first_word(line);
+    line.starts_with(';') || COMMANDS.iter().any(|command| command.eq_ignore_ascii_case(word))
+}
+
+/// Whether the line looks like an event of when: it starts with a year or
+/// `*`, or with an expression like `w=mon`.
+pub fn is_when_event(line: &str) -> bool {
+    let word = first_word(line);
+    let expression = line.find('=')
+                         .is_some_and(|end| line[..end].trim().len() == 1
+                                            && "ymdwa".contains(line[..end].trim()));
+
+    line.contains(',')
+        && (word == "*" || (!word.is_empty() && word.chars().all(|c| c.is_ascii_digit()))
+            || expression)
+}
+
+/// Parses a `REM` command.
+pub fn parse_reminder(line: &str) -> Result<Event, String> {
+    const EXPECTED: &str = "expected 'REM date MSG text'";
+
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let body  = words.iter()
+                     .position(|word| BODIES.iter().any(|body| body.eq_ignore_ascii_case(word)))
+                     .ok_or(EXPECTED)?;
+
+    let mut when  = When::default();
+    let mut words = words[1..body].iter();
+    while let Some(&word) = words.next() {
+        let upper = word.to_uppercase();
+
+        if ["AT", "DURATION", "PRIORITY", "TAG"].contains(&&*upper) {
+            words.next();
+        } else if word.starts_with('+') {
+            // Advance warnings don't move the reminder.
+        } else if let Ok(date) = parse_date(word) {
+            when.year  = Some(date.year());
+            when.month = Some(date.month());
+            when.day   = Some(date.day());
+        } else if word.len() == 4 && word.chars().all(|c| c.is_ascii_digit()) {
+            when.year = Some(parse_year(word)?);
+        } else if let Ok(day) = word.parse::<u32>() {
+            when.day = Some(day);
+        } else if let Ok(month) = parse_month(word) {
+            when.month = Some(month);
+        } else if let Ok(weekday) = parse_weekday(word) {
+            when.weekdays.push(weekday);
+        } else {
+            return Err(format!("unsupported in a reminder: '{}'", word));
+        }
+    }
+
+    // A day with weekdays is the first day of the week they can be on.
+    if !when.weekdays.is_empty() {
+        if let Some(day) = when.day.take() {
+            if day % 7 != 1 || day > 29 {
+                return Err(format!("unsupported day with a day of the week: '{}' \
+                                    (expected 1, 8, 15, 22 or 29)", day));
+            }
+            when.nth = Some(day as i32 / 7 + 1);
+        }
+    }
+
+    let text = words_after(line, body);
+    when.event(&text.replace("%\"", ""))
+}
+
+/// Parses an event of when, e.g. `* dec 25 , Christmas`.
+pub fn parse_when_event(line: &str) -> Result<Event, String> {
+    const EXPECTED: &str = "expected 'year month day , description'";
+
+    let (date, description) = line.split_once(',').ok_or(EXPECTED)?;
+    let mut when = When::default();
+
+    if date.contains(|c| "|!<>()".contains(c)) {
+        return Err(format!("unsupported expression: '{}'", date.trim()));
+    } else if date.contains('=') {
+        for term in date.split('&') {
+            let (name, value) = term.split_once('=')
+                                    .ok_or_else(|| format!("unsupported expression: '{}'",
+                                                           term.trim()))?;
+            let value = value.trim();
+
+            match name.trim() {
+                "y" => when.year  = Some(parse_year(value)?),
+                "m" => when.month = Some(parse_month(value)?),
+                "d" => when.day   = Some(number(value, "day")?),
+                "w" => when.weekdays.push(parse_weekday(value)?),
+                "a" => when.nth   = Some(number(value, "occurrence")? as i32),
+                _   => return Err(format!("unsupported expression: '{}'", term.trim()))
+            }
+        }
+    } else {
+        let parts = date.split_whitespace().collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err(EXPECTED.to_string());
+        }
+
+        let any = |part: &str| part == "*";
+        when.year  = if any(parts[0]) { None } else { Some(parse_year(parts[0])?) };
+        when.month = if any(parts[1]) { None } else { Some(parse_month(parts[1])?) };
+        when.day   = Some(number(parts[2], "day")?);
+    }
+
+    when.event(description.trim())
+}
+
+/// Days an event takes place on, as given in either format.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct When {
+    year:     Option<i32>,
+    month:    Option<u32>,
+    day:      Option<u32>,
+    weekdays: Vec<Weekday>,
+    /// Which of the weekdays in the month, counting from 1.
+    nth:      Option<i32>
+}
+
+impl When {
+    /// Returns the event on these days, recurring unless the date is given
+    /// in full.
+    fn event(&self, description: &str) -> Result<Event, String> {
+        let unsupported = || Err(format!("unsupported date of '{}'", description));
+
+        if self.weekdays.is_empty() {
+            if self.nth.is_some() {
+                return unsupported();
+            }
+
+            let (date, rule) = match (self.year, self.month, self.day) {
+                (Some(year), Some(month), Some(day)) => (first_date(year, month, day, 1)?, None),
+                (None, Some(month), Some(day)) => {
+                    (first_date(FIRST_YEAR, month, day, 8)?, Some(Rule::new(Frequency::Yearly)))
+                },
+                (year, None, Some(day)) => {
+                    (first_date(year.unwrap_or(FIRST_YEAR), 1, day, 1)?,
+                     Some(Rule { until: year.map(last_day), ..Rule::new(Frequency::Monthly) }))
+                },
+                _ => return unsupported()
+            };
+
+            return Ok(event(date, rule, description));
+        }
+
+        if self.day.is_some() {
+            return unsupported();
+        }
+
+        let by_day    = self.weekdays.iter()
+                                     .map(|&weekday| ByDay { weekday, nth: self.nth })
+                                     .collect();
+        let by_month  = self.month.into_iter().collect();
+        let frequency = match (self.month, self.nth) {
+            (_, None)       => Frequency::Weekly,
+            (None, Some(_)) => Frequency::Monthly,
+            (Some(_), _)    => Frequency::Yearly
+        };
+        let start = UTC.ymd(self.year.unwrap_or(FIRST_YEAR), 1, 1);
+        let rule  = Rule { by_day, by_month, until: self.year.map(last_day),
+                           ..Rule::new(frequency) };
+
+        Ok(event(start, Some(rule), description))
+    }
+}
+
+fn event(date: Date, rule: Option<Rule>, description: &str) -> Event {
+    Event { recurrence: rule.map(Recurrence::Rule),
+            ..Event::new(DateRange::new(date, date.succ()), description) }
+}
+
+/// The month and day in the year or, if there's no such day, in one of the
+/// `years` after it, for February 29.
+fn first_date(year: i32, month: u32, day: u32, years: i32) -> Result<Date, String> {
+    (year..year + years).filter_map(|year| UTC.ymd_opt(year, month, day).single())
+                        .next()
+                        .ok_or_else(|| format!("invalid date: {:02}-{:02}", month, day))
+}
+
+fn last_day(year: i32) -> Date {
+    UTC.ymd(year, 12, 31)
+}
+
+fn number(input: &str, what: &str) -> Result<u32, String> {
+    input.parse().map_err(|_| format!("invalid {}: '{}'", what, input))
+}
+
+fn first_word(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+/// The rest of the line after its first `count` words and the whitespace
+/// following them.
+fn words_after(line: &str, count: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..=count {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest.trim_end()
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+fn dates(event: &Event, year: i32) -> Vec<Date> {
+    event.occurrences(::date::dates(year).unwrap()).iter().map(|dates| dates.start).collect()
+}
+
+#[test]
+fn reminders_are_read_on_dates_and_weekdays() {
+    let pi = parse_reminder("REM 14 Mar 2015 +3 AT 10:00 MSG Pi day %\"at noon%\"").unwrap();
+    assert_eq!(pi, Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)),
+                              "Pi day at noon"));
+
+    let christmas = parse_reminder("REM Dec 25 MSG Christmas").unwrap();
+    assert_eq!(dates(&christmas, 2015), [UTC.ymd(2015, 12, 25)]);
+    assert_eq!(dates(&christmas, 1850), [UTC.ymd(1850, 12, 25)]);
+
+    let planning = parse_reminder("rem Mon 8 Sep msg Planning").unwrap();
+    assert_eq!(dates(&planning, 2015), [UTC.ymd(2015, 9, 14)]);
+
+    let payday = parse_reminder("REM 1 MSG Payday").unwrap();
+    assert_eq!(dates(&payday, 2015).len(), 12);
+
+    let gym = parse_reminder("REM Tue Thu 2015 MSG Gym").unwrap();
+    assert_eq!((dates(&gym, 2015).len(), dates(&gym, 2016).len()), (105, 0));
+
+    assert_eq!(parse_reminder("REM Mon 3 MSG Nope"),
+               Err("unsupported day with a day of the week: '3' (expected 1, 8, 15, 22 or \
+                    29)".to_string()));
+    assert_eq!(parse_reminder("REM Mon *7 MSG Nope"),
+               Err("unsupported in a reminder: '*7'".to_string()));
+    assert_eq!(parse_reminder("REM Mon"), Err("expected 'REM date MSG text'".to_string()));
+}
+
+#[test]
+fn when_events_are_read_on_dates_and_expressions() {
+    let pi = parse_when_event("2015 mar 14 , Pi day").unwrap();
+    assert_eq!(pi, Event::new(DateRange::new(UTC.ymd(2015, 3, 14), UTC.ymd(2015, 3, 15)),
+                              "Pi day"));
+
+    let leap = parse_when_event("* feb 29 , Leap day").unwrap();
+    assert_eq!((dates(&leap, 2015), dates(&leap, 2016)), (vec![], vec![UTC.ymd(2016, 2, 29)]));
+
+    let rent = parse_when_event("* * 1 , Rent").unwrap();
+    assert_eq!(dates(&rent, 2015).len(), 12);
+
+    let planning = parse_when_event("w=mon & a=1 , Planning").unwrap();
+    assert_eq!(dates(&planning, 2015)[..2], [UTC.ymd(2015, 1, 5), UTC.ymd(2015, 2, 2)]);
+
+    assert!(is_when_event("w=mon & a=1 , Planning") && is_when_event("* dec 25 , Christmas"));
+    assert!(!is_when_event("2015-03-14 = Pi day, again"));
+    assert_eq!(parse_when_event("w=mon | w=tue , Nope"),
+               Err("unsupported expression: 'w=mon | w=tue'".to_string()));
+}
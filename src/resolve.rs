@@ -0,0 +1,323 @@
+//! Resolution of the range to show.
+//!
+//! The range can be given in several incomplete ways on the command line:
+//! just a month number, a start without an end, a number of months and so
+//! on. The heuristics filling in the rest live here, separate from parsing
+//! the arguments, so that they can be tested against a fixed date.
+
+use calendar::date::{ add_months, check_year, dates, dates_starting, months_around,
+                      months_from, year_starting, Date, DateRange };
+use calendar::parse::{ parse_date_range, parse_year };
+use chrono::{ Datelike, TimeZone, UTC };
+
+/// Number of months shown from a start given without an end.
+const HORIZON: u32 = 12;
+
+/// The arguments describing the range, with bookmarks already expanded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeArgs {
+    /// Year, month, day or range of them, or just the number of a month.
+    pub range:      Option<String>,
+    /// End of the range started by `range`.
+    pub last:       Option<String>,
+    pub from:       Option<String>,
+    pub to:         Option<String>,
+    pub months:     Option<u32>,
+    /// Whether to show the months before and after `from` too.
+    pub around:     bool,
+    /// Whether a month number means its next occurrence rather than the
+    /// month of the current year.
+    pub upcoming:   bool,
+    /// Whether to take the arguments as `cal` does: `[[month] year]`,
+    /// defaulting to the current month.
+    pub cal:        bool,
+    /// First month of the years, if not January.
+    pub year_start: Option<u32>,
+    /// Quarter of the year to show, from 1 to 4.
+    pub quarter:    Option<u32>
+}
+
+/// Returns the range described by the arguments, relative to `today`:
+///
+/// - A month number (e.g. `11`) is that month of the current year, or its
+///   next occurrence if `upcoming` is set.
+/// - Otherwise `range`, up to `last` if given, is parsed as a range.
+/// - `months` counts months from `from`, and `around` shows the months
+///   around it. `from` defaults to the current month.
+/// - `from` and `to` span the months between them. Without `to`, the range
+///   ends `HORIZON` months after `from`; without `from`, it starts with the
+///   current month.
+/// - Without any of them, it's the current year.
+///
+/// With `year_start`, years given as just a year and the current year start
+/// with that month of the year, e.g. `2025` is April 2025 to March 2026 for
+/// fiscal years starting in April.
+///
+/// With `cal`, none of this applies and the arguments are resolved like
+/// `cal` does.
+///
+/// With `quarter`, the range must be a single year, and only its quarter is
+/// shown; quarters count from the start of the year.
+pub fn resolve(args: &RangeArgs, today: Date) -> Result<DateRange, String> {
+    let range = if args.cal { resolve_cal(args, today)? } else { resolve_range(args, today)? };
+
+    let range = match args.quarter {
+        Some(quarter) => quarter_of(range, quarter, args.year_start.unwrap_or(1))?,
+        None          => range
+    };
+
+    check_range(range)
+}
+
+/// Returns the range if all its days are in years the calendar supports,
+/// and an error otherwise.
+pub fn check_range(range: DateRange) -> Result<DateRange, String> {
+    check_year(range.start.year())?;
+    check_year(range.end.pred().year())?;
+    Ok(range)
+}
+
+fn resolve_range(args: &RangeArgs, today: Date) -> Result<DateRange, String> {
+    let window = args.from.is_some() || args.to.is_some() || args.months.is_some()
+                                     || args.around;
+
+    if let Some(ref range) = args.range {
+        if window {
+            return Err("a range can't be combined with -3, --months, --from or --to"
+                       .to_string());
+        }
+
+        if let Some(years) = args.year_start.and_then(|month| years(range, args.last.as_ref(),
+                                                                     month)) {
+            return years;
+        }
+
+        return match (args.last.as_ref(), month_number(range)) {
+            (Some(last), _)     => parse_date_range(&format!("{}..{}", range, last)),
+            (None, Some(month)) => Ok(month_of(month, today, args.upcoming)),
+            (None, None)        => parse_date_range(range)
+        };
+    }
+
+    let from = match args.from {
+        Some(ref from) => parse_date_range(from)?.start,
+        None           => today
+    };
+
+    if args.around && args.months.is_some() {
+        return Err("-3 and --months can't be used together".to_string());
+    }
+
+    if args.to.is_some() && (args.around || args.months.is_some()) {
+        return Err("--to can't be combined with -3 or --months".to_string());
+    }
+
+    let range = match (args.months, args.to.as_ref()) {
+        // Checked first, as huge counts would overflow.
+        (Some(count), _) => {
+            check_year(from.year() + (count / 12) as i32)?;
+            months_from(from, count)
+        },
+        _ if args.around => months_around(from, 1, 1)?,
+        (None, Some(to)) => DateRange::new(add_months(from, 0), parse_date_range(to)?.end),
+        (None, None) if args.from.is_some() => months_from(from, HORIZON),
+        (None, None)     => match args.year_start {
+            Some(month) => dates_starting(year_starting(today, month), month)?,
+            None        => dates(today.year())?
+        }
+    };
+
+    if range.start >= range.end {
+        return Err("the range ends before it starts".to_string());
+    }
+
+    Ok(range)
+}
+
+fn resolve_cal(args: &RangeArgs, today: Date) -> Result<DateRange, String> {
+    match (args.range.as_ref(), args.last.as_ref()) {
+        (Some(month), Some(year)) => {
+            let month = month_number(month).ok_or_else(|| format!("invalid month: '{}'", month))?;
+            let year  = parse_year(year)?;
+            Ok(month_of(month, UTC.ymd(year, 1, 1), false))
+        },
+        (Some(year), None) => dates(parse_year(year)?),
+        _                  => Ok(month_of(today.month(), today, false))
+    }
+}
+
+/// Returns the given quarter (1 to 4) of the range, which must be a year
+/// starting with the month `year_start`.
+fn quarter_of(range: DateRange, quarter: u32, year_start: u32) -> Result<DateRange, String> {
+    if range != dates_starting(year_starting(range.start, year_start), year_start)? {
+        return Err("--quarter needs a single year".to_string());
+    }
+
+    Ok(months_from(add_months(range.start, (quarter as i32 - 1) * 3), 3))
+}
+
+/// Returns the years from `range` up to `last` (or in `range`, given as
+/// `2024..2026`), starting with `month`, if they are given as just years.
+fn years(range: &str, last: Option<&String>, month: u32) -> Option<Result<DateRange, String>> {
+    let (first, last) = match (last, range.find("..")) {
+        (Some(last), _)  => (range, &**last),
+        (None, Some(at)) => (&range[..at], &range[at + 2..]),
+        (None, None)     => (range, range)
+    };
+
+    let is_year = |input: &str| {
+        let digits = input.strip_prefix('-').unwrap_or(input);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    };
+    if !is_year(first) || !is_year(last) || month_number(range).is_some() && first == last {
+        return None;
+    }
+
+    Some(match (parse_year(first), parse_year(last)) {
+        (Ok(first), Ok(last)) if first <= last => {
+            dates_starting(first, month).and_then(|first| {
+                Ok(DateRange::new(first.start, dates_starting(last, month)?.end))
+            })
+        },
+        (Ok(_), Ok(_)) => Err(format!("invalid date range: '{}'", range)),
+        (Err(e), _) | (_, Err(e)) => Err(e)
+    })
+}
+
+/// Returns the month if the input is just the number of a month.
+fn month_number(input: &str) -> Option<u32> {
+    if input.is_empty() || input.len() > 2 || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    input.parse().ok().filter(|month| (1..=12).contains(month))
+}
+
+fn month_of(month: u32, today: Date, upcoming: bool) -> DateRange {
+    let year  = if upcoming && month < today.month() { today.year() + 1 } else { today.year() };
+    let first = UTC.ymd(year, month, 1);
+
+    DateRange::new(first, add_months(first, 1))
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+fn args(range: Option<&str>) -> RangeArgs {
+    RangeArgs { range: range.map(String::from), ..RangeArgs::default() }
+}
+
+#[test]
+fn resolve_takes_month_number_as_month_of_current_year() {
+    let today = UTC.ymd(2015, 6, 10);
+
+    assert_eq!(resolve(&args(Some("11")), today),
+               Ok(DateRange::new(UTC.ymd(2015, 11, 1), UTC.ymd(2015, 12, 1))));
+    assert_eq!(resolve(&args(Some("03")), today),
+               Ok(DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 4, 1))));
+    assert_eq!(resolve(&RangeArgs { upcoming: true, ..args(Some("3")) }, today),
+               Ok(DateRange::new(UTC.ymd(2016, 3, 1), UTC.ymd(2016, 4, 1))));
+    assert_eq!(resolve(&RangeArgs { upcoming: true, ..args(Some("6")) }, today),
+               Ok(DateRange::new(UTC.ymd(2015, 6, 1), UTC.ymd(2015, 7, 1))));
+
+    // Anything else is a year.
+    assert_eq!(resolve(&args(Some("13")), today), dates(13));
+    assert_eq!(resolve(&args(Some("2011")), today), dates(2011));
+}
+
+#[test]
+fn resolve_fills_in_missing_ends() {
+    let today = UTC.ymd(2015, 6, 10);
+
+    assert_eq!(resolve(&args(None), today), dates(2015));
+    assert_eq!(resolve(&RangeArgs { last: Some("2016".to_string()), ..args(Some("2014")) },
+                       today),
+               Ok(DateRange::new(UTC.ymd(2014, 1, 1), UTC.ymd(2017, 1, 1))));
+
+    let from = RangeArgs { from: Some("2015-03".to_string()), ..args(None) };
+    assert_eq!(resolve(&from, today),
+               Ok(DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2016, 3, 1))));
+    assert_eq!(resolve(&RangeArgs { to: Some("2015-04".to_string()), ..from.clone() }, today),
+               Ok(DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 5, 1))));
+    assert_eq!(resolve(&RangeArgs { months: Some(2), ..from.clone() }, today),
+               Ok(DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 5, 1))));
+    assert_eq!(resolve(&RangeArgs { around: true, ..from }, today),
+               Ok(DateRange::new(UTC.ymd(2015, 2, 1), UTC.ymd(2015, 5, 1))));
+
+    assert_eq!(resolve(&RangeArgs { to: Some("2015-08".to_string()), ..args(None) }, today),
+               Ok(DateRange::new(UTC.ymd(2015, 6, 1), UTC.ymd(2015, 9, 1))));
+    assert!(resolve(&RangeArgs { to: Some("2015-01".to_string()), ..args(None) }, today)
+            .is_err());
+    assert!(resolve(&RangeArgs { around: true, ..args(Some("2015")) }, today).is_err());
+}
+
+#[test]
+fn resolve_rejects_ranges_beyond_supported_years() {
+    let today = UTC.ymd(2015, 6, 10);
+
+    assert!(resolve(&RangeArgs { months: Some(1_000_000), ..args(None) }, today).is_err());
+    assert!(resolve(&RangeArgs { months: Some(u32::MAX), ..args(None) }, today).is_err());
+    assert!(resolve(&RangeArgs { around: true, from: Some("9999-12".to_string()),
+                                 ..args(None) }, today).is_err());
+    assert_eq!(resolve(&args(Some("-44")), today), dates(-44));
+    assert!(resolve(&RangeArgs { year_start: Some(4), ..args(Some("9999")) }, today).is_err());
+    assert!(months_around(UTC.ymd(-9999, 1, 1), 1, 1).is_err());
+}
+
+#[test]
+fn resolve_starts_years_with_year_start() {
+    let today  = UTC.ymd(2015, 2, 10);
+    let fiscal = |range: Option<&str>| RangeArgs { year_start: Some(4), ..args(range) };
+
+    assert_eq!(resolve(&fiscal(None), today),
+               Ok(DateRange::new(UTC.ymd(2014, 4, 1), UTC.ymd(2015, 4, 1))));
+    assert_eq!(resolve(&fiscal(Some("2025")), today),
+               Ok(DateRange::new(UTC.ymd(2025, 4, 1), UTC.ymd(2026, 4, 1))));
+    assert_eq!(resolve(&fiscal(Some("2024..2025")), today),
+               Ok(DateRange::new(UTC.ymd(2024, 4, 1), UTC.ymd(2026, 4, 1))));
+    assert_eq!(resolve(&fiscal(Some("11")), today),
+               Ok(DateRange::new(UTC.ymd(2015, 11, 1), UTC.ymd(2015, 12, 1))));
+    assert_eq!(resolve(&fiscal(Some("2025-01")), today),
+               Ok(DateRange::new(UTC.ymd(2025, 1, 1), UTC.ymd(2025, 2, 1))));
+    assert!(resolve(&fiscal(Some("2025..2024")), today).is_err());
+    assert_eq!(resolve(&fiscal(Some("-5")), today),
+               Ok(DateRange::new(UTC.ymd(-5, 4, 1), UTC.ymd(-4, 4, 1))));
+    assert_eq!(resolve(&fiscal(Some("-6..-5")), today),
+               Ok(DateRange::new(UTC.ymd(-6, 4, 1), UTC.ymd(-4, 4, 1))));
+}
+
+#[test]
+fn resolve_picks_quarter_of_year() {
+    let today   = UTC.ymd(2015, 6, 10);
+    let quarter = |range: Option<&str>, year_start| RangeArgs {
+        quarter: Some(3),
+        year_start,
+        ..args(range)
+    };
+
+    assert_eq!(resolve(&quarter(Some("2014"), None), today),
+               Ok(DateRange::new(UTC.ymd(2014, 7, 1), UTC.ymd(2014, 10, 1))));
+    assert_eq!(resolve(&quarter(None, None), today),
+               Ok(DateRange::new(UTC.ymd(2015, 7, 1), UTC.ymd(2015, 10, 1))));
+    assert_eq!(resolve(&quarter(Some("2025"), Some(4)), today),
+               Ok(DateRange::new(UTC.ymd(2025, 10, 1), UTC.ymd(2026, 1, 1))));
+    assert!(resolve(&quarter(Some("2014..2015"), None), today).is_err());
+    assert!(resolve(&quarter(Some("2014-03"), None), today).is_err());
+}
+
+#[test]
+fn resolve_takes_month_and_year_like_cal() {
+    let today = UTC.ymd(2015, 6, 10);
+    let cal   = |range: Option<&str>, last: Option<&str>| RangeArgs {
+        last: last.map(String::from),
+        cal:  true,
+        ..args(range)
+    };
+
+    assert_eq!(resolve(&cal(None, None), today),
+               Ok(DateRange::new(UTC.ymd(2015, 6, 1), UTC.ymd(2015, 7, 1))));
+    assert_eq!(resolve(&cal(Some("11"), None), today), dates(11));
+    assert_eq!(resolve(&cal(Some("2"), Some("2014")), today),
+               Ok(DateRange::new(UTC.ymd(2014, 2, 1), UTC.ymd(2014, 3, 1))));
+    assert!(resolve(&cal(Some("13"), Some("2014")), today).is_err());
+}
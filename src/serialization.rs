@@ -0,0 +1,90 @@
+//! Serde support, with the `serde` feature.
+//!
+//! Dates are written as `YYYY-MM-DD` strings, ranges as a `start` and an
+//! `end` (excluded) date, and recurrence rules as in events files, e.g.
+//! `FREQ=WEEKLY;BYDAY=MO` or `last-business-day-of-month`. `Date` is an
+//! alias of a chrono type, so it can't implement the traits itself; use
+//! `#[serde(with = "calendar::serialization::date")]` on fields of dates.
+
+use recurrence::Recurrence;
+use serde::de::{ self, Deserialize, Deserializer };
+use serde::ser::{ Serialize, Serializer };
+
+/// Serializes a `Date` as a `YYYY-MM-DD` string and back.
+pub mod date {
+    use date::Date;
+    use parse::parse_date;
+    use serde::de::{ self, Deserialize, Deserializer };
+    use serde::ser::Serializer;
+
+    pub fn serialize<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date.format("%Y-%m-%d"))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        let input = String::deserialize(deserializer)?;
+        parse_date(&input).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Recurrence, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+use chrono::{ TimeZone, UTC };
+#[cfg(test)]
+use date::DateRange;
+#[cfg(test)]
+use events::Event;
+
+#[test]
+fn events_round_trip_through_json() {
+    let mut event = Event::new(DateRange::new(UTC.ymd(2015, 1, 5), UTC.ymd(2015, 1, 7)),
+                               "Team offsite");
+    event.recurrence = Some("FREQ=MONTHLY;BYDAY=1MO".parse().unwrap());
+
+    let json = ::serde_json::to_string(&event).unwrap();
+    assert_eq!(json, concat!(r#"{"dates":{"start":"2015-01-05","end":"2015-01-07"},"#,
+                             r#""description":"Team offsite","#,
+                             r#""recurrence":"FREQ=MONTHLY;BYDAY=1MO"}"#));
+    assert_eq!(::serde_json::from_str::<Event>(&json).unwrap(), event);
+
+    assert!(::serde_json::from_str::<DateRange>(r#"{"start":"2015-02-30","end":"2015-03-01"}"#)
+                .is_err());
+}
+
+#[test]
+fn birthdays_round_trip_through_json() {
+    let event = ::events::parse("birthday 1983-06-04 \"Alice\"").unwrap().remove(0);
+
+    let json = ::serde_json::to_string(&event).unwrap();
+    assert_eq!(json, concat!(r#"{"dates":{"start":"1983-06-04","end":"1983-06-05"},"#,
+                             r#""description":"Alice","recurrence":"FREQ=YEARLY","#,
+                             r#""yearly":{"Birthday":1983}}"#));
+    assert_eq!(::serde_json::from_str::<Event>(&json).unwrap(), event);
+}
+
+#[test]
+fn holidays_and_calendars_serialize_to_json() {
+    let holiday = ::holidays::Holiday { date: UTC.ymd(2015, 12, 26),
+                                        name: "St. Stephen's Day, \"Boxing Day\"".to_string() };
+    assert_eq!(::serde_json::to_string(&holiday).unwrap(),
+               r#"{"date":"2015-12-26","name":"St. Stephen's Day, \"Boxing Day\""}"#);
+
+    let range    = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2015, 1, 3));
+    let calendar = ::Calendar::range(range).format(::format::Format::List);
+    assert_eq!(::serde_json::to_string(&calendar).unwrap(),
+               concat!(r#"{"range":{"start":"2015-01-01","end":"2015-01-03"},"format":"list","#,
+                       r#""output":"2015-01-01 Thursday\n2015-01-02 Friday\n"}"#));
+}
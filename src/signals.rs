@@ -0,0 +1,87 @@
+//! Signals for the long-running modes, watch and interactive: SIGHUP asks
+//! to read the configuration and events again, SIGTERM and SIGINT to shut
+//! down cleanly, restoring the terminal.
+//!
+//! The modes poll for signals between their other work, at least every
+//! `TICK`. Only Unix has these signals; elsewhere none are received.
+
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// How long the long-running modes wait for other input before checking
+/// for signals.
+pub const TICK: Duration = Duration::from_millis(100);
+
+/// What a signal asks for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Reload,
+    Shutdown
+}
+
+/// Signals received since listening started.
+pub struct Signals(Receiver<Signal>);
+
+impl Signals {
+    /// Starts listening, in a thread of its own. Signals are only caught
+    /// once listening, so this should be called once, when a long-running
+    /// mode starts.
+    #[cfg(unix)]
+    pub fn listen() -> Result<Signals, String> {
+        use signal_hook::consts::{ SIGHUP, SIGINT, SIGTERM };
+        use std::sync::mpsc::channel;
+        use std::thread;
+
+        let mut signals = ::signal_hook::iterator::Signals::new([SIGHUP, SIGINT, SIGTERM])
+                              .map_err(|e| format!("can't listen for signals: {}", e))?;
+        let (sender, receiver) = channel();
+
+        thread::spawn(move || {
+            for signal in signals.forever() {
+                let signal = if signal == SIGHUP { Signal::Reload } else { Signal::Shutdown };
+                if sender.send(signal).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Signals(receiver))
+    }
+
+    /// Starts listening, which receives nothing where there are no signals.
+    #[cfg(not(unix))]
+    pub fn listen() -> Result<Signals, String> {
+        let (_, receiver) = ::std::sync::mpsc::channel();
+        Ok(Signals(receiver))
+    }
+
+    /// The next signal received, if any. A shutdown is returned before
+    /// reloads received along with it.
+    pub fn poll(&self) -> Option<Signal> {
+        let received = self.0.try_iter().collect::<Vec<_>>();
+
+        if received.contains(&Signal::Shutdown) {
+            Some(Signal::Shutdown)
+        } else {
+            received.first().cloned()
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn poll_puts_shutdown_before_reload() {
+    let (sender, receiver) = ::std::sync::mpsc::channel();
+    let signals = Signals(receiver);
+
+    assert_eq!(signals.poll(), None);
+
+    sender.send(Signal::Reload).unwrap();
+    assert_eq!(signals.poll(), Some(Signal::Reload));
+
+    sender.send(Signal::Reload).unwrap();
+    sender.send(Signal::Shutdown).unwrap();
+    assert_eq!(signals.poll(), Some(Signal::Shutdown));
+    assert_eq!(signals.poll(), None);
+}
@@ -0,0 +1,73 @@
+//! Note skeletons.
+//!
+//! A skeleton is an outline with a heading for every day of a range, named
+//! after the date, its weekday and ISO week, ready to be filled with notes.
+
+use date::{ week_number, DateRange, WeekNumbering };
+use format::{ range_title, Options };
+use std::str::FromStr;
+
+/// Markup of a skeleton.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Markup {
+    Org,
+    Markdown
+}
+
+impl FromStr for Markup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Markup, String> {
+        match s {
+            "org"             => Ok(Markup::Org),
+            "md" | "markdown" => Ok(Markup::Markdown),
+            _                 => Err(format!("unknown skeleton format: '{}' \
+                                              (available: org, md)", s))
+        }
+    }
+}
+
+/// Returns the skeleton of the range: a top-level heading with its title
+/// and a second-level heading for every day, with its week of the
+/// numbering.
+pub fn skeleton(range: DateRange, markup: Markup, numbering: WeekNumbering) -> String {
+    let (title, day, separator) = match markup {
+        Markup::Org      => ("*", "**", ""),
+        Markup::Markdown => ("#", "##", "\n")
+    };
+
+    let mut result = format!("{} {}\n{}", title, range_title(range, &Options::default()),
+                                           separator);
+
+    for date in range {
+        result.push_str(&format!("{} {} {} (week {})\n{}", day, date.format("%Y-%m-%d"),
+                                                         date.format("%A"),
+                                                         week_number(&date, numbering).1,
+                                                         separator));
+    }
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn skeleton_has_a_heading_per_day() {
+    use chrono::{ TimeZone, UTC };
+
+    let range = DateRange::new(UTC.ymd(2015, 3, 1), UTC.ymd(2015, 3, 3));
+
+    assert_eq!(skeleton(range, Markup::Org, WeekNumbering::Iso), "\
+* March 2015
+** 2015-03-01 Sunday (week 9)
+** 2015-03-02 Monday (week 10)
+");
+    assert_eq!(skeleton(range, Markup::Markdown, WeekNumbering::Us), "\
+# March 2015
+
+## 2015-03-01 Sunday (week 10)
+
+## 2015-03-02 Monday (week 10)
+
+");
+}
@@ -0,0 +1,133 @@
+//! Event store shared between threads.
+//!
+//! The store keeps the events loaded from the configured sources. Reloading
+//! reads the sources first and then replaces the whole event set at once, so
+//! queries running concurrently see either the old or the new events, never
+//! a mix of both.
+
+use date::DateRange;
+use diagnostics::Diagnostics;
+use events::{ self, Event };
+use ical;
+use std::path::PathBuf;
+use std::sync::{ Arc, RwLock, RwLockReadGuard, RwLockWriteGuard };
+
+/// Files events are read from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sources {
+    /// File in the format of the `events` module.
+    pub events: Option<PathBuf>,
+    /// iCalendar file.
+    pub ical:   Option<PathBuf>,
+    /// Local copy of an iCalendar feed fetched from a URL, read like `ical`.
+    pub feed:   Option<PathBuf>
+}
+
+impl Sources {
+    /// Reads the events from all sources. Events of iCalendar files and
+    /// reminders of remind that can't be read, which often come from
+    /// elsewhere, are left out with a warning.
+    pub fn load(&self, diagnostics: &mut Diagnostics) -> Result<Vec<Event>, String> {
+        let mut result = Vec::new();
+
+        if let Some(ref path) = self.events {
+            result.extend(events::load_lenient(path, diagnostics)?);
+        }
+
+        for path in self.ical.iter().chain(&self.feed) {
+            result.extend(ical::load_lenient(path, diagnostics)?);
+        }
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    sources:     Sources,
+    events:      Vec<Event>,
+    diagnostics: Diagnostics
+}
+
+/// Handle to the events loaded from a set of sources. Clones share the same
+/// events.
+#[derive(Clone, Debug)]
+pub struct EventStore {
+    inner: Arc<RwLock<Inner>>
+}
+
+impl EventStore {
+    /// Creates a store with the events read from the sources.
+    pub fn load(sources: Sources) -> Result<EventStore, String> {
+        let store = EventStore {
+            inner: Arc::new(RwLock::new(Inner { sources,
+                                                  events:      Vec::new(),
+                                                  diagnostics: Diagnostics::new() }))
+        };
+
+        store.reload()?;
+        Ok(store)
+    }
+
+    /// Reads the sources again and replaces the events with the result. On
+    /// error, the events are left unchanged.
+    pub fn reload(&self) -> Result<(), String> {
+        let sources         = self.read().sources.clone();
+        let mut diagnostics = Diagnostics::new();
+        let events          = sources.load(&mut diagnostics)?;
+
+        let mut inner = self.write();
+        inner.events      = events;
+        inner.diagnostics = diagnostics;
+        Ok(())
+    }
+
+    /// Warnings of the last successful load.
+    pub fn diagnostics(&self) -> Diagnostics {
+        self.read().diagnostics.clone()
+    }
+
+    /// Returns the occurrences of the events in the range, as of the time of
+    /// the call.
+    pub fn query(&self, range: DateRange) -> Vec<Event> {
+        events::expand(&self.read().events, range)
+    }
+
+    // A panic while holding the lock can't leave the events half-replaced,
+    // so a poisoned lock is safe to keep using.
+    fn read(&self) -> RwLockReadGuard<'_, Inner> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Inner> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn reload_replaces_events_seen_by_all_handles() {
+    use chrono::{ TimeZone, UTC };
+    use std::{ env, fs, thread };
+
+    let path  = env::temp_dir().join(format!("calendar-store-test-{}.txt",
+                                             ::std::process::id()));
+    let range = DateRange::new(UTC.ymd(2015, 1, 1), UTC.ymd(2016, 1, 1));
+
+    fs::write(&path, "2015-03-14 = Pi day\n").unwrap();
+    let store = EventStore::load(Sources { events: Some(path.clone()), ..Sources::default() })
+                          .unwrap();
+    assert_eq!(store.query(range).len(), 1);
+
+    fs::write(&path, "2015-03-14 = Pi day\n2015-12-24 = Christmas Eve\n").unwrap();
+    let handle = store.clone();
+    thread::spawn(move || handle.reload().unwrap()).join().unwrap();
+    assert_eq!(store.query(range).len(), 2);
+
+    fs::write(&path, "invalid\n").unwrap();
+    assert!(store.reload().is_err());
+    assert_eq!(store.query(range).len(), 2);
+
+    fs::remove_file(path).unwrap();
+}
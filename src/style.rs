@@ -0,0 +1,145 @@
+//! Text styles for terminal output.
+
+use std::str::FromStr;
+
+/// Style of a piece of text, rendered using ANSI escape codes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Plain,
+    Bold,
+    Dim,
+    Underline,
+    Reverse,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    /// One of the 256 colors of the extended palette, given by its number.
+    Color256(u8)
+}
+
+impl Style {
+    fn code(self) -> Option<String> {
+        let code = match self {
+            Style::Plain       => return None,
+            Style::Bold        => "1",
+            Style::Dim         => "2",
+            Style::Underline   => "4",
+            Style::Reverse     => "7",
+            Style::Red         => "31",
+            Style::Green       => "32",
+            Style::Yellow      => "33",
+            Style::Blue        => "34",
+            Style::Magenta     => "35",
+            Style::Cyan        => "36",
+            Style::Color256(n) => return Some(format!("38;5;{}", n))
+        };
+
+        Some(code.to_string())
+    }
+
+    /// Wraps `text` in the escape codes of this style.
+    pub fn paint(self, text: &str) -> String {
+        match self.code() {
+            Some(code) => format!("\x1b[{}m{}\x1b[0m", code, text),
+            None       => text.to_string()
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Style, String> {
+        match s {
+            "plain"     => Ok(Style::Plain),
+            "bold"      => Ok(Style::Bold),
+            "dim"       => Ok(Style::Dim),
+            "underline" => Ok(Style::Underline),
+            "reverse"   => Ok(Style::Reverse),
+            "red"       => Ok(Style::Red),
+            "green"     => Ok(Style::Green),
+            "yellow"    => Ok(Style::Yellow),
+            "blue"      => Ok(Style::Blue),
+            "magenta"   => Ok(Style::Magenta),
+            "cyan"      => Ok(Style::Cyan),
+            _           => s.parse().map(Style::Color256)
+                                    .map_err(|_| format!("unknown style: '{}'", s))
+        }
+    }
+}
+
+/// Styles for increasing amounts, from the least to the most, e.g. for
+/// coloring days by how often they occur.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scale(pub Vec<Style>);
+
+impl Scale {
+    /// Returns the level of `count` out of `max`, i.e. the index of its
+    /// style: the scale is divided into equal parts, the first starting at
+    /// 1. None for a count of zero or an empty scale.
+    pub fn level(&self, count: u32, max: u32) -> Option<usize> {
+        if self.0.is_empty() || count == 0 || max == 0 {
+            return None;
+        }
+
+        let levels = self.0.len() as u64;
+        Some(((u64::from(count.min(max)) * levels - 1) / u64::from(max)) as usize)
+    }
+
+    /// Returns the style of `count` out of `max`, at its `level`.
+    pub fn style(&self, count: u32, max: u32) -> Style {
+        self.level(count, max).map_or(Style::Plain, |level| self.0[level])
+    }
+}
+
+impl Default for Scale {
+    /// Shades of green, darkest first, like contribution graphs.
+    fn default() -> Scale {
+        Scale(vec![Style::Color256(22), Style::Color256(28), Style::Color256(34),
+                   Style::Color256(46)])
+    }
+}
+
+impl FromStr for Scale {
+    type Err = String;
+
+    /// Parses a comma-separated list of styles, e.g. `dim,green,bold`.
+    fn from_str(s: &str) -> Result<Scale, String> {
+        s.split(',').map(|style| style.trim().parse()).collect::<Result<_, _>>().map(Scale)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn paint_wraps_text_in_escape_codes() {
+    assert_eq!(Style::Plain.paint("12"), "12");
+    assert_eq!(Style::Dim.paint("12"),   "\x1b[2m12\x1b[0m");
+    assert_eq!(Style::Red.paint("12"),   "\x1b[31m12\x1b[0m");
+    assert_eq!(Style::Color256(28).paint("12"), "\x1b[38;5;28m12\x1b[0m");
+}
+
+#[test]
+fn scale_divides_counts_into_levels() {
+    let scale = "dim,green,bold".parse::<Scale>().unwrap();
+
+    assert_eq!(scale.style(0, 9), Style::Plain);
+    assert_eq!(scale.style(1, 9), Style::Dim);
+    assert_eq!(scale.style(3, 9), Style::Dim);
+    assert_eq!(scale.style(4, 9), Style::Green);
+    assert_eq!(scale.style(9, 9), Style::Bold);
+    assert_eq!(scale.style(1, 1), Style::Bold);
+    assert!("dim,sparkly".parse::<Scale>().is_err());
+}
+
+#[test]
+fn style_can_be_parsed() {
+    assert_eq!("dim".parse(),  Ok(Style::Dim));
+    assert_eq!("cyan".parse(), Ok(Style::Cyan));
+    assert_eq!("208".parse(),  Ok(Style::Color256(208)));
+    assert!("sparkly".parse::<Style>().is_err());
+}
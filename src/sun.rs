@@ -0,0 +1,150 @@
+//! Sunrise and sunset.
+//!
+//! The times are computed with the sunrise equation from the declination of
+//! the sun at its transit, which puts them within a few minutes of the
+//! almanac for the present centuries, away from the polar circles. The
+//! position of the sun is measured from the equinox, so the seasons stay in
+//! place over the millennia, but the tilt of the axis and the length of the
+//! day change, so times far from the present are rough. The sun counts as
+//! risen once its upper edge clears the horizon, with the usual allowance for
+//! refraction.
+
+use date::{ julian_day, Date };
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Julian date of the epoch J2000, January 1, 2000, 12:00 TT.
+const J2000: f64 = 2_451_545.0;
+/// Tilt of the axis of the earth, in degrees.
+const OBLIQUITY: f64 = 23.4397;
+/// Altitude of the center of the sun at sunrise and sunset, in degrees.
+const HORIZON: f64 = -0.833;
+
+/// A place on earth, in degrees of latitude north and longitude east.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Location {
+    pub latitude:  f64,
+    pub longitude: f64
+}
+
+impl FromStr for Location {
+    type Err = String;
+
+    /// Parses the latitude and longitude separated by a comma, like
+    /// `52.52,13.40`, south and west being negative.
+    fn from_str(s: &str) -> Result<Location, String> {
+        let error = || format!("invalid location: '{}' (expected latitude,longitude in \
+                                degrees, like 52.52,13.40)", s);
+
+        let (latitude, longitude) = s.split_once(',').ok_or_else(error)?;
+        let latitude  = latitude.trim().parse::<f64>().map_err(|_| error())?;
+        let longitude = longitude.trim().parse::<f64>().map_err(|_| error())?;
+
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(error());
+        }
+
+        Ok(Location { latitude, longitude })
+    }
+}
+
+/// When the sun is up on a day.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Daylight {
+    /// Sunrise and sunset, in minutes after midnight.
+    Between(u32, u32),
+    /// The sun doesn't set, as in summer beyond the polar circles.
+    AllDay,
+    /// The sun doesn't rise.
+    AllNight
+}
+
+impl fmt::Display for Daylight {
+    /// Writes the sunrise and sunset like `05:33–20:41`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Daylight::Between(rise, set) => write!(f, "{:02}:{:02}–{:02}:{:02}", rise / 60,
+                                                   rise % 60, set / 60, set % 60),
+            Daylight::AllDay             => f.write_str("no sunset"),
+            Daylight::AllNight           => f.write_str("no sunrise")
+        }
+    }
+}
+
+/// Returns when the sun is up on the day at the location, in the time
+/// `offset` seconds east of UTC.
+pub fn daylight(date: Date, location: Location, offset: i32) -> Daylight {
+    let (sin, cos) = (|degrees: f64| degrees.to_radians().sin(),
+                      |degrees: f64| degrees.to_radians().cos());
+
+    // Mean solar noon at the location, in days since J2000.
+    let noon    = julian_day(date) as f64 - J2000 - location.longitude / 360.0;
+    let anomaly = (357.5291 + 0.985_600_28 * noon).rem_euclid(360.0);
+    let center  = 1.9148 * sin(anomaly) + 0.02 * sin(2.0 * anomaly)
+                  + 0.0003 * sin(3.0 * anomaly);
+    // The mean longitude follows the tropical year; adding the anomaly to a
+    // fixed perihelion would drift away from the seasons.
+    let mean_longitude     = 280.460 + 0.985_647_4 * noon;
+    let ecliptic_longitude = (mean_longitude + center).rem_euclid(360.0);
+
+    let transit     = J2000 + noon + 0.0053 * sin(anomaly)
+                      - 0.0069 * sin(2.0 * ecliptic_longitude);
+    let declination = (sin(ecliptic_longitude) * sin(OBLIQUITY)).asin();
+
+    let hour_angle = (sin(HORIZON) - sin(location.latitude) * declination.sin())
+                     / (cos(location.latitude) * declination.cos());
+    if hour_angle < -1.0 {
+        return Daylight::AllDay;
+    }
+    if hour_angle > 1.0 {
+        return Daylight::AllNight;
+    }
+
+    // Julian days start at noon, so the day starts half a day earlier.
+    let half  = hour_angle.acos() / (2.0 * PI);
+    let start = julian_day(date) as f64 - 0.5 - offset as f64 / 86_400.0;
+    let clock = |time: f64| (((time - start) * 1440.0).round() as i64).rem_euclid(1440) as u32;
+
+    Daylight::Between(clock(transit - half), clock(transit + half))
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn location_parses_latitude_and_longitude() {
+    assert_eq!("52.52,13.40".parse(), Ok(Location { latitude: 52.52, longitude: 13.4 }));
+    assert_eq!("-33.87, 151.21".parse(), Ok(Location { latitude: -33.87, longitude: 151.21 }));
+
+    assert!("52.52".parse::<Location>().is_err());
+    assert!("91,0".parse::<Location>().is_err());
+    assert!("north,east".parse::<Location>().is_err());
+}
+
+#[test]
+fn daylight_finds_sunrise_and_sunset() {
+    use chrono::{ TimeZone, UTC };
+
+    let berlin  = Location { latitude: 52.52, longitude: 13.40 };
+    let tromso  = Location { latitude: 69.65, longitude: 18.96 };
+    let minutes = |hours: u32, minutes: u32| hours * 60 + minutes;
+
+    // Central European Summer Time and Central European Time.
+    assert_eq!(daylight(UTC.ymd(2015, 6, 21), berlin, 7200),
+               Daylight::Between(minutes(4, 43), minutes(21, 33)));
+    assert_eq!(daylight(UTC.ymd(2015, 12, 21), berlin, 3600),
+               Daylight::Between(minutes(8, 15), minutes(15, 54)));
+
+    assert_eq!(daylight(UTC.ymd(2015, 6, 21), tromso, 7200), Daylight::AllDay);
+    assert_eq!(daylight(UTC.ymd(2015, 12, 21), tromso, 3600), Daylight::AllNight);
+
+    assert_eq!(Daylight::Between(minutes(4, 43), minutes(21, 33)).to_string(), "04:43–21:33");
+
+    // Days stay longest at the summer solstice millennia away.
+    let length = |date| match daylight(date, berlin, 3600) {
+        Daylight::Between(rise, set) => set - rise,
+        other                        => panic!("{:?}", other)
+    };
+    assert!(length(UTC.ymd(-3000, 6, 21)) > length(UTC.ymd(-3000, 4, 21)) + 60);
+    assert!(length(UTC.ymd(-3000, 6, 21)) > length(UTC.ymd(-3000, 8, 21)) + 60);
+}
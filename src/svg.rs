@@ -0,0 +1,258 @@
+//! SVG output, for printing.
+//!
+//! The year is laid out on a single page, with the months arranged in rows
+//! and every day drawn as a box of its own. All coordinates are in
+//! millimeters. The box of a day has the CSS class of its role, like HTML
+//! output.
+
+use date::{ Date, DateRange };
+use events::Event;
+use format::{ column, day_number, day_role, days_per_week, format_dates, has_events, month_name,
+              month_rows, months, range_title, weekday_names, weeks, Options, Renderer };
+use html::escape;
+use std::fmt::Write;
+use std::str::FromStr;
+
+const MARGIN: f64 = 10.0;
+/// Space between months.
+const GAP: f64 = 6.0;
+/// Height of the year title.
+const TITLE_HEIGHT: f64 = 14.0;
+const LEGEND_LINE_HEIGHT: f64 = 5.0;
+/// Rows of a month: the month name, weekday names and six weeks.
+const MONTH_ROWS: usize = 8;
+
+const STYLE: &str = "
+text     { font-family: sans-serif; }
+.title   { font-size: 10px; font-weight: bold; text-anchor: middle; }
+.month   { font-weight: bold; text-anchor: middle; }
+.weekday { text-anchor: middle; fill: #555; }
+.day     { text-anchor: middle; }
+rect     { fill: none; stroke: #bbb; stroke-width: 0.2; }
+.weekend { fill: #eee; }
+.holiday { fill: #fdd; }
+.today   { stroke: #000; stroke-width: 0.5; }
+.highlight { fill: #ff0; }
+.event   { font-weight: bold; text-decoration: underline; }
+.legend  { font-size: 3.5px; }
+";
+
+/// Size of a page in millimeters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PageSize {
+    pub width:  f64,
+    pub height: f64
+}
+
+impl PageSize {
+    pub const A4: PageSize = PageSize { width: 210.0, height: 297.0 };
+}
+
+impl FromStr for PageSize {
+    type Err = String;
+
+    /// Parses a page size: `a3`, `a4`, `a5`, `letter`, `legal` or
+    /// `WIDTHxHEIGHT` in millimeters.
+    fn from_str(s: &str) -> Result<PageSize, String> {
+        let size = |width, height| Ok(PageSize { width, height });
+
+        match &*s.to_lowercase() {
+            "a3"     => size(297.0, 420.0),
+            "a4"     => size(210.0, 297.0),
+            "a5"     => size(148.0, 210.0),
+            "letter" => size(215.9, 279.4),
+            "legal"  => size(215.9, 355.6),
+            other    => {
+                let mut parts = other.splitn(2, 'x').map(|part| part.trim().parse::<f64>());
+
+                match (parts.next(), parts.next()) {
+                    (Some(Ok(width)), Some(Ok(height)))
+                        if width.is_finite() && height.is_finite() => {
+                        if width <= 2.0 * MARGIN || height <= 2.0 * MARGIN + TITLE_HEIGHT {
+                            return Err(format!("page size '{}' leaves no room inside the \
+                                                margins and title", s));
+                        }
+                        size(width, height)
+                    },
+                    _ => Err(format!("invalid page size: '{}'", s))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Svg {
+    pub page:            PageSize,
+    /// Number of months side by side, at least one.
+    pub months_per_line: usize
+}
+
+impl Svg {
+    /// Checks that `months` months and `legend_lines` lines of legend fit on
+    /// the page.
+    pub fn check(&self, months: usize, legend_lines: usize) -> Result<(), String> {
+        let (month_width, month_height) = self.month_size(months, legend_lines);
+
+        if month_width > 0.0 && month_height > 0.0 {
+            Ok(())
+        } else {
+            Err(format!("a page of {}x{} mm is too small for {} months and {} lines of legend",
+                        self.page.width, self.page.height, months, legend_lines))
+        }
+    }
+
+    /// Width and height of a month, which are negative when the months
+    /// don't fit.
+    fn month_size(&self, months: usize, legend_lines: usize) -> (f64, f64) {
+        let PageSize { width, height } = self.page;
+        let columns       = self.months_per_line.max(1);
+        let rows          = months.div_ceil(columns).max(1);
+        let legend_height = LEGEND_LINE_HEIGHT * legend_lines as f64;
+
+        ((width - 2.0 * MARGIN - GAP * (columns - 1) as f64) / columns as f64,
+         (height - 2.0 * MARGIN - TITLE_HEIGHT - legend_height - GAP * (rows - 1) as f64)
+         / rows as f64)
+    }
+}
+
+impl Renderer for Svg {
+    fn render(&self, range: DateRange, options: &Options, legend: &[Event]) -> String {
+        let PageSize { width, height } = self.page;
+        let mut result = String::new();
+
+        writeln!(result, "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+                          width=\"{0}mm\" height=\"{1}mm\" viewBox=\"0 0 {0} {1}\">",
+                 width, height).unwrap();
+        writeln!(result, "<style>{}</style>", STYLE).unwrap();
+        writeln!(result, "<text class=\"title\" x=\"{}\" y=\"{}\">{}</text>",
+                 width / 2.0, MARGIN + TITLE_HEIGHT * 0.6, range_title(range, options))
+            .unwrap();
+
+        let columns = self.months_per_line.max(1);
+        let months  = months(range, options);
+        let top     = MARGIN + TITLE_HEIGHT;
+        let legend_height = LEGEND_LINE_HEIGHT * legend.len() as f64;
+
+        // Months that don't fit, which `check` reports, are drawn empty.
+        let (month_width, month_height) = self.month_size(months.len(), legend.len());
+        let (month_width, month_height) = (month_width.max(0.0), month_height.max(0.0));
+
+        for (row, months) in month_rows(months, columns).enumerate() {
+            for (column, month) in months.into_iter().enumerate() {
+                let x = MARGIN + column as f64 * (month_width + GAP);
+                let y = top + row as f64 * (month_height + GAP);
+
+                render_month(&mut result, month, options, Frame { x, y,
+                                                                  width:  month_width,
+                                                                  height: month_height });
+            }
+        }
+
+        let legend_top = height - MARGIN - legend_height;
+
+        for (index, event) in legend.iter().enumerate() {
+            writeln!(result, "<text class=\"legend\" x=\"{}\" y=\"{:.2}\">{}  {}</text>",
+                     MARGIN, legend_top + LEGEND_LINE_HEIGHT * (index + 1) as f64,
+                     format_dates(event.dates), escape(&event.description)).unwrap();
+        }
+
+        result.push_str("</svg>\n");
+        result
+    }
+}
+
+/// Area of the page a month is drawn into.
+#[derive(Copy, Clone, Debug)]
+struct Frame {
+    x:      f64,
+    y:      f64,
+    width:  f64,
+    height: f64
+}
+
+fn render_month(out: &mut String, month: DateRange, options: &Options, frame: Frame) {
+    let cell_width  = frame.width / days_per_week(options) as f64;
+    let cell_height = frame.height / MONTH_ROWS as f64;
+    let font_size   = (cell_height * 0.5).min(cell_width * 0.45);
+    // Baseline of the text in a row, so that it is vertically centered.
+    let baseline    = |row: usize| frame.y + cell_height * (row as f64 + 0.5) + font_size * 0.35;
+    let center      = |column: u32| frame.x + cell_width * (column as f64 + 0.5);
+
+    writeln!(out, "<g font-size=\"{:.2}\">", font_size).unwrap();
+    writeln!(out, "<text class=\"month\" x=\"{:.2}\" y=\"{:.2}\">{}</text>",
+             frame.x + frame.width / 2.0, baseline(0), month_name(month.start, options)).unwrap();
+
+    for (index, name) in weekday_names(options).iter().enumerate() {
+        writeln!(out, "<text class=\"weekday\" x=\"{:.2}\" y=\"{:.2}\">{}</text>",
+                 center(index as u32), baseline(1), name).unwrap();
+    }
+
+    for (row, week) in weeks(month, options).enumerate() {
+        for date in week {
+            let column = column(date, options);
+
+            writeln!(out, "<rect class=\"{}\" x=\"{:.2}\" y=\"{:.2}\" \
+                           width=\"{:.2}\" height=\"{:.2}\"/>",
+                     day_classes(date, options), frame.x + cell_width * column as f64,
+                     frame.y + cell_height * (row + 2) as f64, cell_width, cell_height)
+                .unwrap();
+
+            let class = if has_events(date, options) { "day event" } else { "day" };
+            writeln!(out, "<text class=\"{}\" x=\"{:.2}\" y=\"{:.2}\">{}</text>",
+                     class, center(column), baseline(row + 2), day_number(date, options))
+                .unwrap();
+        }
+    }
+
+    out.push_str("</g>\n");
+}
+
+fn day_classes(date: Date, options: &Options) -> String {
+    let mut classes = day_role(date, options).class().into_iter().collect::<Vec<_>>();
+    if options.today == Some(date) {
+        classes.push("today".to_string());
+    }
+
+    classes.join(" ")
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn page_size_can_be_parsed() {
+    assert_eq!("A4".parse(), Ok(PageSize::A4));
+    assert_eq!("100x150".parse(), Ok(PageSize { width: 100.0, height: 150.0 }));
+    assert!("100x".parse::<PageSize>().is_err());
+    assert!("0x100".parse::<PageSize>().is_err());
+    assert!("infx100".parse::<PageSize>().is_err());
+    assert!("100xNaN".parse::<PageSize>().is_err());
+    assert!("1x1".parse::<PageSize>().is_err());
+    assert!("20x100".parse::<PageSize>().is_err());
+    assert!("100x34".parse::<PageSize>().is_err());
+}
+
+#[test]
+fn check_rejects_pages_too_small_for_the_months() {
+    let svg = Svg { page: PageSize { width: 100.0, height: 100.0 }, months_per_line: 4 };
+    assert_eq!(svg.check(12, 0), Ok(()));
+    assert!(svg.check(12, 11).is_err());
+
+    let svg = Svg { page: PageSize { width: 30.0, height: 100.0 }, months_per_line: 4 };
+    assert!(svg.check(12, 0).is_err());
+}
+
+#[test]
+fn render_lays_out_months_on_the_page() {
+    use date::dates;
+
+    let svg = Svg { page: PageSize { width: 100.0, height: 100.0 }, months_per_line: 4 };
+    let output = svg.render(dates(2015).unwrap(), &Options::default(), &[]);
+
+    assert!(output.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" \
+                                width=\"100mm\" height=\"100mm\" viewBox=\"0 0 100 100\">"));
+    assert_eq!(output.matches("<rect").count(), 365);
+    // Four months per row, three rows.
+    assert!(output.contains("<text class=\"month\" x=\"17.75\" y=\"")); // January
+    assert!(output.contains("<text class=\"month\" x=\"82.25\" y=\"")); // April
+}
@@ -0,0 +1,149 @@
+//! The terminal the output is shown in: its size and whether it shows
+//! styles.
+//!
+//! Styles are escape codes, which the consoles of Windows, like cmd.exe and
+//! PowerShell, only interpret once virtual terminal processing is enabled.
+//! Where it can't be, the escape codes are left out rather than shown as
+//! text.
+
+use std::borrow::Cow;
+use std::env;
+use std::io::{ self, IsTerminal, Write };
+use terminal_size::{ terminal_size, Height, Width };
+
+/// Columns of the terminal, if the output is shown in one.
+pub fn width() -> Option<usize> {
+    size().map(|(width, _)| width)
+}
+
+/// Lines of the terminal, if the output is shown in one.
+pub fn height() -> Option<usize> {
+    size().map(|(_, height)| height)
+}
+
+/// Size of the terminal in columns and lines. Terminals whose size can't be
+/// queried, like the mintty of Git for Windows and MSYS2, fall back to
+/// `$COLUMNS` and `$LINES`.
+fn size() -> Option<(usize, usize)> {
+    if let Some((Width(width), Height(height))) = terminal_size() {
+        return Some((width as usize, height as usize));
+    }
+
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+
+    let variable = |name| env::var(name).ok().and_then(|value| value.parse().ok());
+    variable("COLUMNS").and_then(|width| variable("LINES").map(|height| (width, height)))
+}
+
+/// Whether the terminal shows styles, enabling them in the console of
+/// Windows first.
+#[cfg(windows)]
+pub fn shows_styles() -> bool {
+    ::crossterm::ansi_support::supports_ansi()
+}
+
+/// Whether the terminal shows styles, which all but the console of Windows
+/// do.
+#[cfg(not(windows))]
+pub fn shows_styles() -> bool {
+    true
+}
+
+/// The output as the terminal shows it: without the escape codes of styles
+/// if it's a console that doesn't interpret them. Output that isn't shown
+/// in a terminal is left as it is.
+pub fn displayable(output: &str) -> Cow<'_, str> {
+    if leaves_out_styles() {
+        Cow::Owned(unstyled(output))
+    } else {
+        Cow::Borrowed(output)
+    }
+}
+
+/// Whether the output is shown in a console that doesn't interpret escape
+/// codes.
+fn leaves_out_styles() -> bool {
+    io::stdout().is_terminal() && !shows_styles()
+}
+
+/// Writer that passes on the output as the terminal shows it, like
+/// `displayable`, for output written in parts. An escape code can be split
+/// between writes.
+pub struct Displayable<W> {
+    out:      W,
+    unstyled: bool,
+    escape:   bool
+}
+
+impl<W: Write> Displayable<W> {
+    pub fn new(out: W) -> Displayable<W> {
+        Displayable { out, unstyled: leaves_out_styles(), escape: false }
+    }
+}
+
+impl<W: Write> Write for Displayable<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.unstyled {
+            return self.out.write(buf);
+        }
+
+        // Escape codes are ASCII, which never is part of another character
+        // in UTF-8.
+        let mut text = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            match byte {
+                b'\x1b'                         => self.escape = true,
+                _ if !self.escape               => text.push(byte),
+                _ if byte.is_ascii_alphabetic() => self.escape = false,
+                _                               => ()
+            }
+        }
+
+        self.out.write_all(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// The text without escape codes, each of which runs up to a letter, like
+/// `\x1b[31m`.
+fn unstyled(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut escape = false;
+
+    for c in text.chars() {
+        match c {
+            '\x1b'                       => escape = true,
+            _ if !escape                 => result.push(c),
+            _ if c.is_ascii_alphabetic() => escape = false,
+            _                            => ()
+        }
+    }
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn unstyled_leaves_out_escape_codes() {
+    assert_eq!(unstyled("  \x1b[31m12\x1b[0m \x1b[38;5;208m★\x1b[0m"), "  12 ★");
+    assert_eq!(unstyled("\x1b[2J\x1b[HJanuary"), "January");
+    assert_eq!(unstyled("plain"), "plain");
+}
+
+#[test]
+fn displayable_leaves_out_escape_codes_split_between_writes() {
+    let mut output = Vec::new();
+    {
+        let mut out = Displayable { out: &mut output, unstyled: true, escape: false };
+        out.write_all(b"  \x1b[3").unwrap();
+        out.write_all("1m12\x1b[0m ★".as_bytes()).unwrap();
+    }
+    assert_eq!(String::from_utf8(output).unwrap(), "  12 ★");
+}
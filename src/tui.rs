@@ -0,0 +1,181 @@
+//! Interactive mode: browsing the calendar in the terminal.
+//!
+//! The months around a selected day are drawn by the caller, with the text
+//! layout, and redrawn whenever a key moves the selection. Enter lists the
+//! events of the selected day below the months.
+
+use calendar::date::{ add_months, months_from, Date };
+use chrono::{ Datelike, Duration };
+use crossterm::event::{ self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers };
+use crossterm::{ cursor, execute, terminal };
+use signals::{ Signal, Signals, TICK };
+use std::io::{ self, Write };
+
+const HELP: &str = "arrows/hjkl: day and week  H/L: month  K/J: year  t: today  \
+                    enter: events  q: quit";
+
+/// What a key does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Moves the selection by the number of days.
+    Days(i64),
+    /// Moves the selection by the number of months, to the same day or the
+    /// last day of a shorter month.
+    Months(i32),
+    Today,
+    ShowEvents,
+    Quit
+}
+
+impl Action {
+    pub fn of_key(key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+
+        match key.code {
+            KeyCode::Left     | KeyCode::Char('h') => Some(Action::Days(-1)),
+            KeyCode::Right    | KeyCode::Char('l') => Some(Action::Days(1)),
+            KeyCode::Up       | KeyCode::Char('k') => Some(Action::Days(-7)),
+            KeyCode::Down     | KeyCode::Char('j') => Some(Action::Days(7)),
+            KeyCode::PageUp   | KeyCode::Char('H') => Some(Action::Months(-1)),
+            KeyCode::PageDown | KeyCode::Char('L') => Some(Action::Months(1)),
+            KeyCode::Char('K')                     => Some(Action::Months(-12)),
+            KeyCode::Char('J')                     => Some(Action::Months(12)),
+            KeyCode::Char('t')                     => Some(Action::Today),
+            KeyCode::Enter                         => Some(Action::ShowEvents),
+            KeyCode::Esc      | KeyCode::Char('q') => Some(Action::Quit),
+            _                                      => None
+        }
+    }
+}
+
+/// The selected day, and whether its events are shown.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct State {
+    pub selected:    Date,
+    pub today:       Date,
+    pub show_events: bool
+}
+
+impl State {
+    pub fn new(selected: Date, today: Date) -> State {
+        State { selected, today, show_events: false }
+    }
+
+    /// Applies the action. Returns false if it quits.
+    pub fn apply(&mut self, action: Action) -> bool {
+        self.show_events = false;
+
+        match action {
+            Action::Days(days)     => self.selected = self.selected + Duration::days(days),
+            Action::Months(months) => self.selected = shift_months(self.selected, months),
+            Action::Today          => self.selected = self.today,
+            Action::ShowEvents     => self.show_events = true,
+            Action::Quit           => return false
+        }
+
+        true
+    }
+}
+
+fn shift_months(date: Date, months: i32) -> Date {
+    let month = months_from(add_months(date, months), 1);
+    month.day(date.day0().min(month.days() - 1)).unwrap_or(month.start)
+}
+
+/// Runs the interactive calendar until quit or a signal. `render` draws
+/// the months around the selected day, and `events` lists what happens on
+/// a day. Returns the signal, `Signal::Shutdown` if quit, with the state
+/// left as it was to carry on after a reload. The terminal is restored
+/// either way.
+pub fn run<R, E>(state: &mut State, signals: &Signals, render: R, events: E)
+    -> Result<Signal, String>
+    where R: Fn(Date) -> String, E: Fn(Date) -> Vec<String>
+{
+    let _screen = Screen::enter().map_err(|e| e.to_string())?;
+    let mut out = io::stdout();
+
+    loop {
+        let mut lines = render(state.selected).lines().map(String::from).collect::<Vec<_>>();
+        lines.push(String::new());
+
+        if state.show_events {
+            let events = events(state.selected);
+            lines.push(format!("{}:", state.selected.format("%A, %B %-d, %Y")));
+            if events.is_empty() {
+                lines.push("  no events".to_string());
+            }
+            lines.extend(events.into_iter().map(|event| format!("  {}", event)));
+        } else {
+            lines.push(HELP.to_string());
+        }
+
+        // The terminal is in raw mode, so lines need a carriage return too.
+        execute!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))
+            .and_then(|_| write!(out, "{}", lines.join("\r\n")))
+            .and_then(|_| out.flush())
+            .map_err(|e| e.to_string())?;
+
+        let input = loop {
+            if let Some(signal) = signals.poll() {
+                return Ok(signal);
+            }
+            if event::poll(TICK).map_err(|e| e.to_string())? {
+                break event::read().map_err(|e| e.to_string())?;
+            }
+        };
+        let action = match input {
+            Event::Key(key) if key.kind == KeyEventKind::Press => Action::of_key(key),
+            _                                                 => None
+        };
+
+        if let Some(action) = action {
+            if !state.apply(action) {
+                return Ok(Signal::Shutdown);
+            }
+        }
+    }
+}
+
+/// The alternate screen in raw mode, restored when dropped, also on errors.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn state_moves_selection_by_days_months_and_years() {
+    use chrono::{ TimeZone, UTC };
+
+    let mut state = State::new(UTC.ymd(2024, 1, 31), UTC.ymd(2024, 6, 1));
+
+    assert!(state.apply(Action::Months(1)));
+    assert_eq!(state.selected, UTC.ymd(2024, 2, 29));
+    assert!(state.apply(Action::Months(12)));
+    assert_eq!(state.selected, UTC.ymd(2025, 2, 28));
+    assert!(state.apply(Action::Days(7)));
+    assert_eq!(state.selected, UTC.ymd(2025, 3, 7));
+
+    assert!(state.apply(Action::ShowEvents));
+    assert!(state.show_events);
+    assert!(state.apply(Action::Today));
+    assert_eq!(state.selected, UTC.ymd(2024, 6, 1));
+    assert!(!state.show_events);
+
+    assert!(!state.apply(Action::Quit));
+}
@@ -0,0 +1,86 @@
+//! Watch mode: redrawing the calendar whenever an event source changes.
+
+use calendar::store::EventStore;
+use notify::{ self, Event, RecursiveMode, Watcher };
+use signals::{ Signal, Signals, TICK };
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{ channel, Receiver, RecvTimeoutError };
+use std::time::Duration;
+
+/// How long to wait for further changes before reloading. Editors often
+/// write a file in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Calls `render` now and again after every change of the files, once the
+/// store has reloaded the events. Returns the signal that ends watching,
+/// or an error. Errors while reloading are reported, but the previous
+/// events are kept and watching continues.
+pub fn watch<F>(store: &EventStore, files: &[PathBuf], signals: &Signals, mut render: F)
+    -> Result<Signal, String>
+    where F: FnMut()
+{
+    let files = files.iter()
+                     .map(|file| fs::canonicalize(file)
+                                     .map_err(|e| format!("{}: {}", file.display(), e)))
+                     .collect::<Result<HashSet<_>, _>>()?;
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+
+    // Watch the directories rather than the files, so that files replaced by
+    // renaming a new version over them are still noticed.
+    let dirs = files.iter().filter_map(|file| file.parent()).collect::<HashSet<_>>();
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)
+               .map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+
+    render();
+
+    loop {
+        if let Some(signal) = signals.poll() {
+            return Ok(signal);
+        }
+
+        let event = match receiver.recv_timeout(TICK) {
+            Ok(event)                           => event.map_err(|e| e.to_string())?,
+            Err(RecvTimeoutError::Timeout)      => continue,
+            Err(RecvTimeoutError::Disconnected) => break
+        };
+        if !touches(&event, &files) { continue; }
+
+        drain(&receiver, DEBOUNCE);
+
+        match store.reload() {
+            Ok(())     => render(),
+            Err(error) => eprintln!("calendar: {}", error)
+        }
+    }
+
+    Err("file watcher stopped".to_string())
+}
+
+fn touches(event: &Event, files: &HashSet<PathBuf>) -> bool {
+    !event.kind.is_access() && event.paths.iter().any(|path| files.contains(path))
+}
+
+/// Discards messages until none arrives for `delay`.
+fn drain<T>(receiver: &Receiver<T>, delay: Duration) {
+    while receiver.recv_timeout(delay).is_ok() {}
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn drain_discards_pending_messages() {
+    let (sender, receiver) = channel();
+
+    for i in 0..3 {
+        sender.send(i).unwrap();
+    }
+
+    drain(&receiver, Duration::from_millis(10));
+    assert!(receiver.try_recv().is_err());
+}
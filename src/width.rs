@@ -0,0 +1,118 @@
+//! Width of text on the terminal.
+//!
+//! Terminals don't agree on how wide some characters are. East Asian
+//! "ambiguous" characters (e.g. `±`, `★`, Greek and Cyrillic letters) are
+//! one column on most terminals but two on those set up for CJK, and some
+//! fonts render symbols wider than Unicode says. All padding and centering
+//! of text output measures through `Width`, so that the alignment can be
+//! fixed in the configuration for such terminals.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use unicode_width::UnicodeWidthChar;
+
+/// How the width of characters is determined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Measure {
+    /// Every character is one column.
+    Chars,
+    /// Widths from the Unicode standard: wide characters are two columns,
+    /// combining and control characters none.
+    Unicode
+}
+
+impl FromStr for Measure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Measure, String> {
+        match s {
+            "chars"   => Ok(Measure::Chars),
+            "unicode" => Ok(Measure::Unicode),
+            _         => Err(format!("unknown width measure: '{}' \
+                                      (available: chars, unicode)", s))
+        }
+    }
+}
+
+/// Measurement of text width.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Width {
+    pub measure:        Measure,
+    /// Whether ambiguous characters are two columns wide, as on terminals
+    /// set up for CJK. Only applies to `Measure::Unicode`.
+    pub ambiguous_wide: bool,
+    /// Widths of single characters, taking precedence over `measure`.
+    pub overrides:      BTreeMap<char, usize>
+}
+
+impl Default for Width {
+    fn default() -> Width {
+        Width { measure: Measure::Unicode, ambiguous_wide: false, overrides: BTreeMap::new() }
+    }
+}
+
+impl Width {
+    /// Number of columns the character occupies.
+    pub fn of_char(&self, c: char) -> usize {
+        if let Some(&width) = self.overrides.get(&c) {
+            return width;
+        }
+
+        match self.measure {
+            Measure::Chars                          => 1,
+            Measure::Unicode if self.ambiguous_wide => c.width_cjk().unwrap_or(0),
+            Measure::Unicode                        => c.width().unwrap_or(0)
+        }
+    }
+
+    /// Number of columns the text occupies. Escape sequences don't count.
+    pub fn of(&self, text: &str) -> usize {
+        let mut width = 0;
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                width += self.of_char(c);
+            }
+        }
+
+        width
+    }
+
+    /// Centers the text in `width` columns, with the extra column on the
+    /// right if the padding can't be split evenly. Text wider than that is
+    /// returned as is.
+    pub fn center(&self, text: &str, width: usize) -> String {
+        let padding = width.saturating_sub(self.of(text));
+        let left    = padding / 2;
+
+        format!("{}{}{}", " ".repeat(left), text, " ".repeat(padding - left))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[test]
+fn width_measures_characters() {
+    let unicode = Width::default();
+    let chars   = Width { measure: Measure::Chars, ..Width::default() };
+    let cjk     = Width { ambiguous_wide: true, ..Width::default() };
+
+    assert_eq!(unicode.of("März"), 4);
+    assert_eq!(unicode.of("一月"), 4);
+    assert_eq!(chars.of("一月"), 2);
+    assert_eq!(unicode.of("\x1b[1m★\x1b[0m"), 1);
+    assert_eq!(cjk.of("★"), 2);
+
+    let mut overrides = BTreeMap::new();
+    overrides.insert('★', 2);
+    assert_eq!(Width { overrides, ..Width::default() }.of("★1"), 3);
+}
+
+#[test]
+fn center_pads_by_width() {
+    assert_eq!(Width::default().center("一月", 7), " 一月  ");
+    assert_eq!(Width::default().center("January", 3), "January");
+}
@@ -0,0 +1,128 @@
+//! Snapshots of whole years of text output, compared against the files in
+//! `tests/golden`, so that a change to the layout shows up as a difference
+//! rather than as months that are silently out of alignment.
+//!
+//! After an intended change, write the files anew with
+//! `UPDATE_GOLDEN=1 cargo test --test golden` and review their diff.
+
+extern crate calendar;
+extern crate chrono;
+
+use calendar::calendars::System;
+use calendar::date::Reckoning;
+use calendar::prelude::*;
+use calendar::render_year;
+use chrono::Weekday;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Compares the output with the golden file of the name, or writes it if
+/// `UPDATE_GOLDEN` is set.
+fn check(name: &str, output: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+                                                       .join("golden")
+                                                       .join(format!("{}.txt", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, output).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!("can't read {}: {} (run with UPDATE_GOLDEN=1 to write it)", path.display(), e)
+    });
+
+    // The first differing line is easier to read than the whole calendar.
+    let differing = expected.lines()
+                            .zip(output.lines())
+                            .enumerate()
+                            .find(|&(_, (expected, actual))| expected != actual);
+    if let Some((index, (expected, actual))) = differing {
+        panic!("{} differs at line {}:\nexpected: {:?}\n  actual: {:?}", name, index + 1,
+               expected, actual);
+    }
+
+    assert_eq!(expected.lines().count(), output.lines().count(),
+               "{} has another number of lines", name);
+    assert!(expected == output, "{} differs in line endings", name);
+}
+
+/// Calendar of the whole year.
+fn whole_year(year: i32) -> Calendar {
+    Calendar::year(year).unwrap()
+}
+
+#[test]
+fn year() {
+    check("year", &render_year(2015, &Options::default()).unwrap());
+}
+
+#[test]
+fn year_with_weeks_starting_on_sunday() {
+    let mut options = Options::default();
+    options.week_start = Weekday::Sun;
+
+    check("week_start_sunday", &render_year(2015, &options).unwrap());
+}
+
+#[test]
+fn julian_leap_year() {
+    let mut options = Options::default();
+    options.julian = true;
+    options.layout.day_width = 4;
+
+    check("julian", &render_year(2016, &options).unwrap());
+}
+
+#[test]
+fn months_per_line() {
+    for &months in &[1, 4, 6, 12] {
+        let calendar = whole_year(2015).months_per_line(months).to_string();
+        check(&format!("months_per_line_{}", months), &calendar);
+    }
+}
+
+#[test]
+fn compact_workweek() {
+    let calendar = whole_year(2015).layout(LayoutConfig::compact())
+                                   .workweek(true)
+                                   .to_string();
+    check("compact_workweek", &calendar);
+}
+
+#[test]
+fn titles_and_weekday_header() {
+    let calendar = whole_year(2015).title_format("%b %Y".parse().unwrap())
+                                   .weekday_header(true)
+                                   .week_start(Weekday::Sun)
+                                   .to_string();
+    check("titles_weekday_header", &calendar);
+}
+
+#[test]
+fn vertical_months() {
+    check("vertical", &whole_year(2015).vertical(true).to_string());
+}
+
+#[test]
+fn flipped_weeks() {
+    check("flipped", &whole_year(2015).flip_weeks(true).week_start(Weekday::Sun).to_string());
+}
+
+#[test]
+fn reform_of_1752() {
+    let calendar = whole_year(1752).reckoning(Reckoning::reform_in(1752).unwrap())
+                                   .to_string();
+    check("reform_1752", &calendar);
+}
+
+#[test]
+fn hebrew_overlay() {
+    check("overlay_hebrew", &whole_year(2015).overlay(Some(System::Hebrew)).to_string());
+}
+
+#[test]
+fn moon_phases() {
+    check("moon", &whole_year(2015).moon(true).to_string());
+}